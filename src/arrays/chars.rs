@@ -0,0 +1,48 @@
+/// J's three character types: literal (bytes), unicode (UTF-16) and unicode4 (code points).
+///
+/// Characters are always stored as code points, and every char array carries one of these as a
+/// tag: `u:` sets it, `3!:0` reports it, and only literal arrays are bytes which `7 u:` decodes.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum CharWidth {
+    Literal,
+    Unicode,
+    Unicode4,
+}
+
+impl CharWidth {
+    pub fn of(c: char) -> CharWidth {
+        match u32::from(c) {
+            0..=0xff => CharWidth::Literal,
+            0x100..=0xffff => CharWidth::Unicode,
+            _ => CharWidth::Unicode4,
+        }
+    }
+
+    /// the width needed for all of `chars`; empty arrays are literal
+    pub fn of_all<'c>(chars: impl IntoIterator<Item = &'c char>) -> CharWidth {
+        chars
+            .into_iter()
+            .map(|&c| CharWidth::of(c))
+            .max()
+            .unwrap_or(CharWidth::Literal)
+    }
+
+    /// the width of decoded text: non-ASCII code points aren't bytes, whatever their size
+    pub fn of_text<'c>(chars: impl IntoIterator<Item = &'c char>) -> CharWidth {
+        let mut ascii = true;
+        let width = CharWidth::of_all(chars.into_iter().inspect(|c| ascii &= c.is_ascii()));
+        match ascii {
+            true => CharWidth::Literal,
+            false => width.max(CharWidth::Unicode),
+        }
+    }
+
+    /// the `3!:0` type number
+    pub fn type_code(self) -> i64 {
+        match self {
+            CharWidth::Literal => 2,
+            CharWidth::Unicode => 131072,
+            CharWidth::Unicode4 => 262144,
+        }
+    }
+}
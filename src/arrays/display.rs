@@ -8,10 +8,12 @@ use num::{BigInt, BigRational};
 use num_traits::{One, Zero};
 use unicode_width::UnicodeWidthStr;
 
+use crate::arrays::{Sparse, Symbol};
 use crate::{impl_array, JArray};
 
 pub fn nd(mut f: impl fmt::Write, arr: &JArray) -> fmt::Result {
     match arr {
+        JArray::SparseArray(s) => write!(f, "{s:?}"),
         JArray::BoxArray(_) => impl_array!(arr, |a: &ArrayBase<_, _>| write!(f, "|{}|", a)),
         _ => impl_array!(arr, |a: &ArrayBase<_, _>| write!(f, "{}", a)),
     }
@@ -20,12 +22,16 @@ pub fn nd(mut f: impl fmt::Write, arr: &JArray) -> fmt::Result {
 pub fn jsoft(mut f: impl fmt::Write, arr: &JArray) -> fmt::Result {
     match arr {
         JArray::BoxArray(arr) => br_box(f, arr.view()),
-        JArray::CharArray(arr) => br_char(f, arr.view()),
+        JArray::CharArray(arr, _) => br_char(f, arr.view()),
+        JArray::SparseArray(s) => br_sparse(f, s),
         _ => impl_array!(arr, |arr: &ArrayBase<_, _>| br(&mut f, arr.view())),
     }
 }
 
 trait JFormat {
+    /// columns of numbers line up on the right, but columns of symbols on the left
+    const LEFT_ALIGNED: bool = false;
+
     fn j_format(&self) -> String;
 }
 
@@ -89,15 +95,17 @@ fn br<T: JFormat>(mut f: impl fmt::Write, arr: ArrayViewD<T>) -> fmt::Result {
     for (rn, row) in table {
         for (col, (target, item)) in widths.iter().zip(row.into_iter()).enumerate() {
             let len = width(&item);
-            write!(
-                f,
-                "{}",
-                (0..(target - len)).map(|_| ' ').collect::<String>()
-            )?;
+            let padding = (0..(target - len)).map(|_| ' ').collect::<String>();
+            if !T::LEFT_ALIGNED {
+                write!(f, "{padding}")?;
+            }
             if col != 0 {
                 write!(f, " ")?;
             }
             write!(f, "{item}")?;
+            if T::LEFT_ALIGNED {
+                write!(f, "{padding}")?;
+            }
         }
         write!(f, "\n")?;
         if rn == last {
@@ -245,6 +253,58 @@ fn br_char(mut f: impl fmt::Write, arr: ArrayViewD<char>) -> fmt::Result {
     Ok(())
 }
 
+/// a line for each stored atom: its index, then its value, like `1 2 │ 7`
+fn br_sparse(mut f: impl fmt::Write, s: &Sparse) -> fmt::Result {
+    let indices = s
+        .indices()
+        .iter()
+        .map(|index| index.iter().map(|i| i.to_string()).collect_vec())
+        .collect_vec();
+    let index_widths = (0..s.shape().len())
+        .map(|k| {
+            indices
+                .iter()
+                .map(|index| index[k].len())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect_vec();
+    let values = s
+        .values()
+        .outer_iter()
+        .map_err(|_| fmt::Error)?
+        .map(|v| v.j_format().trim_end_matches('\n').to_string())
+        .collect_vec();
+    let value_width = values
+        .iter()
+        .flat_map(|v| v.split('\n').map(width))
+        .max()
+        .unwrap_or(0);
+    let index_width = index_widths.iter().sum::<usize>() + index_widths.len().saturating_sub(1);
+
+    for (index, value) in indices.iter().zip(values) {
+        let index = index
+            .iter()
+            .zip(&index_widths)
+            .map(|(i, w)| format!("{i:>w$}"))
+            .join(" ");
+        for (n, line) in value.split('\n').enumerate() {
+            let prefix = if n == 0 {
+                index.clone()
+            } else {
+                rep(' ', index_width)
+            };
+            // numbers line up on the right, as in any other column of them
+            let padding = match s.values() {
+                JArray::BoxArray(_) | JArray::CharArray(..) => String::new(),
+                _ => rep(' ', value_width - width(line)),
+            };
+            writeln!(f, "{prefix} │ {padding}{line}")?;
+        }
+    }
+    Ok(())
+}
+
 fn compute_dimension_spacing<T>(arr: &ArrayViewD<T>) -> Vec<usize> {
     arr.shape()
         .iter()
@@ -283,6 +343,14 @@ macro_rules! j_format_is_display {
 j_format_is_display!(u8);
 j_format_is_display!(char);
 
+impl JFormat for Symbol {
+    const LEFT_ALIGNED: bool = true;
+
+    fn j_format(&self) -> String {
+        format!("{self}")
+    }
+}
+
 #[inline]
 fn sign_lift<T: JFormat + num_traits::sign::Signed>(val: T, f: impl FnOnce(T) -> String) -> String {
     if val.is_negative() {
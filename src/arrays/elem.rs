@@ -3,6 +3,7 @@ use num::{BigInt, BigRational};
 use std::cmp::Ordering;
 use std::fmt::Debug;
 
+use crate::arrays::Symbol;
 use crate::number::Num;
 use crate::JArray;
 
@@ -10,6 +11,7 @@ use crate::JArray;
 pub enum Elem {
     Num(Num),
     Char(char),
+    Symbol(Symbol),
     Boxed(JArray),
 }
 
@@ -48,6 +50,18 @@ impl From<&char> for Elem {
     }
 }
 
+impl From<Symbol> for Elem {
+    fn from(value: Symbol) -> Self {
+        Elem::Symbol(value)
+    }
+}
+
+impl From<&Symbol> for Elem {
+    fn from(value: &Symbol) -> Self {
+        Elem::Symbol(value.clone())
+    }
+}
+
 impl From<JArray> for Elem {
     fn from(value: JArray) -> Self {
         Elem::Boxed(value)
@@ -65,6 +79,7 @@ impl PartialEq for Elem {
             (Elem::Num(l), Elem::Num(r)) => l == r,
             (Elem::Boxed(l), Elem::Boxed(r)) => l == r,
             (Elem::Char(l), Elem::Char(r)) => l == r,
+            (Elem::Symbol(l), Elem::Symbol(r)) => l == r,
             _ => false,
         }
     }
@@ -81,6 +96,10 @@ impl PartialOrd for Elem {
             (Num(_), _) => Some(Ordering::Less),
             (_, Num(_)) => Some(Ordering::Greater),
 
+            (Symbol(l), Symbol(r)) => l.partial_cmp(r),
+            (Symbol(_), _) => Some(Ordering::Less),
+            (_, Symbol(_)) => Some(Ordering::Greater),
+
             (Char(l), Char(r)) => l.partial_cmp(r),
             (Char(_), _) => Some(Ordering::Less),
             (_, Char(_)) => Some(Ordering::Greater),
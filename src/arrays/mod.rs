@@ -1,16 +1,22 @@
 #![allow(clippy::enum_variant_names)]
 
+mod chars;
 pub mod display;
 mod elem;
 mod into_vec;
 mod nd_ext;
 mod owned;
+mod sparse;
+mod symbol;
 mod word;
 
+pub use chars::CharWidth;
 pub use elem::Elem;
 pub use into_vec::IntoVec;
 pub use nd_ext::*;
 pub use owned::{ArcArrayD, BoxArray, CowArrayD, JArray, JArrayKind};
+pub use sparse::Sparse;
+pub use symbol::{Symbol, SymbolTable};
 pub use word::Word;
 
 // All terminology should match J terminology:
@@ -21,7 +27,7 @@ macro_rules! reduce_arrays {
     ($arr:expr, $func:expr) => {
         match $arr {
             JArrays::BoolArrays(ref a) => JArray::BoolArray($func(a)?),
-            JArrays::CharArrays(ref a) => JArray::CharArray($func(a)?),
+            JArrays::CharArrays(ref a) => JArray::from_chars($func(a)?),
             JArrays::IntArrays(ref a) => JArray::IntArray($func(a)?),
             JArrays::ExtIntArrays(ref a) => JArray::ExtIntArray($func(a)?),
             JArrays::RationalArrays(ref a) => JArray::RationalArray($func(a)?),
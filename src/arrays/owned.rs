@@ -1,3 +1,4 @@
+use std::sync::Arc;
 use std::{fmt, iter};
 
 use anyhow::{anyhow, ensure, Context, Result};
@@ -10,7 +11,10 @@ use num::{BigInt, BigRational};
 use num_traits::ToPrimitive;
 
 use super::nd_ext::len_of_0;
+use crate::arrays::chars::CharWidth;
 use crate::arrays::elem::Elem;
+use crate::arrays::sparse::Sparse;
+use crate::arrays::symbol::Symbol;
 use crate::arrays::{display, size_of_shape_checked};
 use crate::cells::fill_promote_reshape;
 use crate::number::Num;
@@ -29,29 +33,23 @@ pub enum JArrayKind {
     Rational,
     Float,
     Complex,
+    Symbol,
     Box,
 }
 
 #[derive(Clone)]
 pub enum JArray {
     BoolArray(ArcArrayD<u8>),
-    CharArray(ArcArrayD<char>),
+    /// code points, tagged with the character type they're treated as
+    CharArray(ArcArrayD<char>, CharWidth),
     IntArray(ArcArrayD<i64>),
     ExtIntArray(ArcArrayD<BigInt>),
     RationalArray(ArcArrayD<BigRational>),
     FloatArray(ArcArrayD<f64>),
     ComplexArray(ArcArrayD<Complex64>),
+    SymbolArray(ArcArrayD<Symbol>),
     BoxArray(BoxArray),
-    // TODO: Some or all of these?
-    // UnicodeCharArray(_)
-    // Unicode4CharArray(_)
-    // SymbolArray(_)
-    // SparseBoolArray(_)
-    // SparseCharArray(_)
-    // SparseIntArray(_)
-    // SparseFloatArray(_)
-    // SparseComplexArray(_)
-    // SparseBoxArray(_)
+    SparseArray(Arc<Sparse>),
 }
 
 impl fmt::Debug for JArray {
@@ -60,18 +58,20 @@ impl fmt::Debug for JArray {
         if f.alternate() {
             match self {
                 BoolArray(a) => write!(f, "BoolArray({a:?})"),
-                CharArray(a) => write!(f, "CharArray({a:?})"),
+                CharArray(a, w) => write!(f, "CharArray({w:?}, {a:?})"),
                 IntArray(a) => write!(f, "IntArray({a:?})"),
                 ExtIntArray(a) => write!(f, "ExtIntArray({a:?})"),
                 RationalArray(a) => write!(f, "RationalArray({a:?})"),
                 FloatArray(a) => write!(f, "FloatArray({a:?})"),
                 ComplexArray(a) => write!(f, "ComplexArray({a:?})"),
+                SymbolArray(a) => write!(f, "SymbolArray({a:?})"),
                 BoxArray(a) => write!(f, "BoxArray({a:?})"),
+                SparseArray(a) => write!(f, "SparseArray({a:?})"),
             }
         } else {
             match self {
                 BoolArray(a) => write!(f, "BoolArray({a})"),
-                CharArray(a) => {
+                CharArray(a, _) => {
                     if a.shape().len() <= 1 {
                         write!(f, "CharArray({:?})", a.iter().collect::<String>())
                     } else {
@@ -83,7 +83,9 @@ impl fmt::Debug for JArray {
                 RationalArray(a) => write!(f, "RationalArray({a})"),
                 FloatArray(a) => write!(f, "FloatArray({a})"),
                 ComplexArray(a) => write!(f, "ComplexArray({a})"),
+                SymbolArray(a) => write!(f, "SymbolArray({a})"),
                 BoxArray(a) => write!(f, "BoxArray({a:?})"),
+                SparseArray(a) => write!(f, "SparseArray({a:?})"),
             }
         }
     }
@@ -91,11 +93,19 @@ impl fmt::Debug for JArray {
 
 impl PartialEq for JArray {
     fn eq(&self, other: &Self) -> bool {
+        if self.is_sparse() || other.is_sparse() {
+            return match (self, other, self.to_dense(), other.to_dense()) {
+                (_, _, Ok(l), Ok(r)) => l == r,
+                // too big to compare by value
+                (JArray::SparseArray(l), JArray::SparseArray(r), _, _) => l == r,
+                _ => false,
+            };
+        }
         if self.shape() != other.shape() || self.len_of_0() != other.len_of_0() {
             return false;
         }
 
-        self.clone().into_elems() == other.clone().into_elems()
+        self.clone().into_elems().ok() == other.clone().into_elems().ok()
     }
 }
 
@@ -105,13 +115,15 @@ macro_rules! impl_array {
     ($arr:ident, $func:expr) => {
         match $arr {
             JArray::BoolArray(a) => $func(a),
-            JArray::CharArray(a) => $func(a),
+            JArray::CharArray(a, _) => $func(a),
             JArray::IntArray(a) => $func(a),
             JArray::ExtIntArray(a) => $func(a),
             JArray::RationalArray(a) => $func(a),
             JArray::FloatArray(a) => $func(a),
             JArray::ComplexArray(a) => $func(a),
+            JArray::SymbolArray(a) => $func(a),
             JArray::BoxArray(a) => $func(a),
+            JArray::SparseArray(_) => unreachable!("sparse arrays are handled before impl_array!"),
         }
     };
 }
@@ -120,13 +132,15 @@ macro_rules! map_array {
     ($arr:ident, $func:expr) => {
         match $arr {
             JArray::BoolArray(a) => JArray::BoolArray($func(a)),
-            JArray::CharArray(a) => JArray::CharArray($func(a)),
+            JArray::CharArray(a, w) => JArray::CharArray($func(a), *w),
             JArray::IntArray(a) => JArray::IntArray($func(a)),
             JArray::ExtIntArray(a) => JArray::ExtIntArray($func(a)),
             JArray::RationalArray(a) => JArray::RationalArray($func(a)),
             JArray::FloatArray(a) => JArray::FloatArray($func(a)),
             JArray::ComplexArray(a) => JArray::ComplexArray($func(a)),
+            JArray::SymbolArray(a) => JArray::SymbolArray($func(a)),
             JArray::BoxArray(a) => JArray::BoxArray($func(a)),
+            JArray::SparseArray(_) => unreachable!("sparse arrays are handled before map_array!"),
         }
     };
 }
@@ -136,12 +150,13 @@ macro_rules! map_kind {
     ($kind:ident, $func:expr) => {
         match $kind {
             JArrayKind::Bool => JArray::BoolArray($func()?),
-            JArrayKind::Char => JArray::CharArray($func()?),
+            JArrayKind::Char => JArray::from_chars($func()?),
             JArrayKind::Int => JArray::IntArray($func()?),
             JArrayKind::ExtInt => JArray::ExtIntArray($func()?),
             JArrayKind::Rational => JArray::RationalArray($func()?),
             JArrayKind::Float => JArray::FloatArray($func()?),
             JArrayKind::Complex => JArray::ComplexArray($func()?),
+            JArrayKind::Symbol => JArray::SymbolArray($func()?),
             JArrayKind::Box => JArray::BoxArray($func()?),
         }
     };
@@ -152,7 +167,9 @@ macro_rules! impl_homo {
     ($x:ident, $y:ident, $func:expr) => {
         match ($x, $y) {
             (JArray::BoolArray(x), JArray::BoolArray(y)) => Ok(JArray::BoolArray($func(x, y)?)),
-            (JArray::CharArray(x), JArray::CharArray(y)) => Ok(JArray::CharArray($func(x, y)?)),
+            (JArray::CharArray(x, xw), JArray::CharArray(y, yw)) => {
+                Ok(JArray::CharArray($func(x, y)?, *xw.max(yw)))
+            }
             (JArray::IntArray(x), JArray::IntArray(y)) => Ok(JArray::IntArray($func(x, y)?)),
             (JArray::ExtIntArray(x), JArray::ExtIntArray(y)) => {
                 Ok(JArray::ExtIntArray($func(x, y)?))
@@ -164,6 +181,9 @@ macro_rules! impl_homo {
             (JArray::ComplexArray(x), JArray::ComplexArray(y)) => {
                 Ok(JArray::ComplexArray($func(x, y)?))
             }
+            (JArray::SymbolArray(x), JArray::SymbolArray(y)) => {
+                Ok(JArray::SymbolArray($func(x, y)?))
+            }
             (JArray::BoxArray(x), JArray::BoxArray(y)) => Ok(JArray::BoxArray($func(x, y)?)),
             _ => Err(JError::DomainError).context("expected homo arrays"),
         }
@@ -181,6 +201,9 @@ impl JArray {
 
     /// does the array contain zero elements, regardless of shape
     pub fn is_empty(&self) -> bool {
+        if let JArray::SparseArray(s) = self {
+            return s.shape().contains(&0);
+        }
         impl_array!(self, |a: &ArrayBase<_, _>| { a.is_empty() })
     }
 
@@ -191,24 +214,58 @@ impl JArray {
 
     /// the length of the outermost axis, the length of `outer_iter`.
     pub fn len_of_0(&self) -> usize {
+        if let JArray::SparseArray(s) = self {
+            return s.shape().first().copied().unwrap_or(1);
+        }
         impl_array!(self, len_of_0)
     }
 
     /// the number of elements in the array; the product of the shape (1 for atoms)
     pub fn tally(&self) -> usize {
+        if let JArray::SparseArray(s) = self {
+            return s.shape().iter().product();
+        }
         impl_array!(self, ArrayBase::len)
     }
 
     pub fn len_of(&self, axis: Axis) -> usize {
+        if let JArray::SparseArray(s) = self {
+            return s.shape()[axis.index()];
+        }
         impl_array!(self, |a: &ArrayBase<_, _>| a.len_of(axis))
     }
 
     pub fn shape<'s>(&'s self) -> &[usize] {
+        if let JArray::SparseArray(s) = self {
+            return s.shape();
+        }
         impl_array!(self, ArrayBase::shape)
     }
 
     pub fn transpose<'s>(&'s self) -> JArray {
-        impl_array!(self, |a: &'s ArrayBase<_, _>| a.t().to_shared().into())
+        if let JArray::SparseArray(s) = self {
+            let reversed = (0..s.shape().len()).rev().collect_vec();
+            return s
+                .permute(&reversed)
+                .expect("reversing the axes is valid")
+                .into();
+        }
+        impl_array!(self, |a: &'s ArrayBase<_, _>| JArray::from(
+            a.t().to_shared()
+        ))
+        .keep_char_width(self)
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        matches!(self, JArray::SparseArray(_))
+    }
+
+    /// a sparse array as a dense one, or a dense array as it is
+    pub fn to_dense(&self) -> Result<JArray> {
+        match self {
+            JArray::SparseArray(s) => s.to_dense(),
+            _ => Ok(self.clone()),
+        }
     }
 
     /// converts a singleton list into an atom (you almost certainly do not want this),
@@ -233,11 +290,14 @@ impl JArray {
         }
     }
 
-    pub fn select(&self, axis: Axis, ix: &[usize]) -> JArray {
-        impl_array!(self, |a: &ArrayBase<_, _>| a
-            .select(axis, ix)
-            .into_shared()
-            .into())
+    pub fn select(&self, axis: Axis, ix: &[usize]) -> Result<JArray> {
+        if self.is_sparse() {
+            return self.to_dense()?.select(axis, ix);
+        }
+        Ok(
+            impl_array!(self, |a: &ArrayBase<_, _>| JArray::from(a.select(axis, ix)))
+                .keep_char_width(self),
+        )
     }
 
     pub fn slice_axis(&self, axis: Axis, slice: Slice) -> Result<JArray> {
@@ -250,17 +310,28 @@ impl JArray {
                 "slice end, {end}, past end of axis {index}, of length {this_dim}"
             );
         }
+        if self.is_sparse() {
+            return self.to_dense()?.slice_axis(axis, slice);
+        }
         Ok(impl_array!(self, |a: &ArrayBase<_, _>| JArray::from(
             a.slice_axis(axis, slice).to_shared()
-        )))
+        ))
+        .keep_char_width(self))
     }
 
     #[deprecated = "reshape() should always be sufficient?"]
     pub fn into_shape(self, shape: impl IntoDimension<Dim = IxDyn>) -> Result<JArray> {
-        impl_array!(self, |a: ArrayBase<_, _>| Ok(a
-            .into_shape(shape)?
-            .to_shared()
-            .into()))
+        if self.is_sparse() {
+            return self.to_dense()?.reshape(shape);
+        }
+        let width = self.char_width();
+        let arr = impl_array!(self, |a: ArrayBase<_, _>| Ok::<_, anyhow::Error>(
+            JArray::from(a.into_shape(shape)?.to_shared())
+        ))?;
+        Ok(match width {
+            Some(width) => arr.with_char_width(width),
+            None => arr,
+        })
     }
 
     pub fn reshape(&self, shape: impl IntoDimension<Dim = IxDyn>) -> Result<JArray> {
@@ -268,7 +339,11 @@ impl JArray {
         if size_of_shape_checked(&dim)? != self.tally() {
             return Err(JError::DomainError).context("impossible reshape required");
         }
-        impl_array!(self, |a: &ArrayBase<_, _>| Ok(a.reshape(dim).into()))
+        if self.is_sparse() {
+            return self.to_dense()?.reshape(dim);
+        }
+        impl_array!(self, |a: &ArrayBase<_, _>| Ok(JArray::from(a.reshape(dim))))
+            .map(|r| r.keep_char_width(self))
     }
 
     /// Create an array containing the same data and the same trailing shape, but with
@@ -287,7 +362,7 @@ impl JArray {
             .expect("rank extension is always valid")
     }
 
-    pub fn create_cleared(&self) -> JArray {
+    pub fn create_cleared(&self) -> Result<JArray> {
         let empty_first = |shape: &[usize]| -> Vec<usize> {
             if shape.is_empty() {
                 vec![0]
@@ -297,25 +372,29 @@ impl JArray {
                 shape
             }
         };
-        map_array!(self, |a: &ArrayBase<_, _>| ArcArrayD::from_shape_vec(
-            empty_first(a.shape()),
-            Vec::new()
-        )
-        .expect("static shape"))
+        if self.is_sparse() {
+            return self.to_dense()?.create_cleared();
+        }
+        Ok(map_array!(self, |a: &ArrayBase<_, _>| {
+            ArcArrayD::from_shape_vec(empty_first(a.shape()), Vec::new()).expect("static shape")
+        }))
     }
 
-    pub fn outer_iter<'v>(&'v self) -> Box<dyn OuterIter + 'v> {
-        if self.shape().is_empty() {
+    pub fn outer_iter<'v>(&'v self) -> Result<Box<dyn OuterIter + 'v>> {
+        Ok(if self.shape().is_empty() {
             Box::new(iter::once(self.clone()))
+        } else if self.is_sparse() {
+            Box::new(self.to_dense()?.outer_iter()?.collect_vec().into_iter())
         } else {
             impl_array!(self, |x: &'v ArrayBase<_, _>| Box::new(
-                x.outer_iter().map(|v| v.to_shared().into())
+                x.outer_iter()
+                    .map(|v| JArray::from(v.to_shared()).keep_char_width(self))
             ))
-        }
+        })
     }
 
     /// rank_iter, but the other way up, and more picky about its arguments
-    pub fn dims_iter(&self, dims: usize) -> Vec<JArray> {
+    pub fn dims_iter(&self, dims: usize) -> Result<Vec<JArray>> {
         assert!(
             dims <= self.shape().len(),
             "{dims} must be shorter than us: {}",
@@ -329,18 +408,20 @@ impl JArray {
     }
 
     // AA TODO: Real iterator instead of Vec
-    pub fn rank_iter(&self, rank: i16) -> Vec<JArray> {
+    pub fn rank_iter(&self, rank: i16) -> Result<Vec<JArray>> {
         // Similar to ndarray::axis_chunks_iter but j style ranks.
         // ndarray Axis(0) is the largest axis whereas for j 0 is atoms, 1 is lists etc
         debug!("rank_iter rank: {}", rank);
         if rank > self.shape().len() as i16 || self.is_empty() {
-            vec![self.clone()]
+            Ok(vec![self.clone()])
+        } else if self.is_sparse() {
+            self.to_dense()?.rank_iter(rank)
         } else if rank == 0 {
-            impl_array!(self, |x: &ArrayBase<_, _>| x
+            Ok(impl_array!(self, |x: &ArrayBase<_, _>| x
                 .iter()
                 .map(Elem::from)
-                .map(JArray::from)
-                .collect::<Vec<JArray>>())
+                .map(|e| JArray::from(e).keep_char_width(self))
+                .collect::<Vec<JArray>>()))
         } else {
             let shape = self.shape();
             let (leading, surplus) = if rank >= 0 {
@@ -358,19 +439,23 @@ impl JArray {
             ]
             .concat();
 
-            impl_array!(self, |x: &ArrayBase<_, _>| x
+            Ok(impl_array!(self, |x: &ArrayBase<_, _>| x
                 .exact_chunks(IxDyn(&iter_shape))
                 .into_iter()
-                .map(|x| x.into_shape(surplus.clone()).unwrap().into_owned().into())
-                .collect())
+                .map(|x| JArray::from(x.to_shape(surplus.clone()).unwrap().into_owned()))
+                .map(|x| x.keep_char_width(self))
+                .collect()))
         }
     }
 
-    pub fn into_elems(self) -> Vec<Elem> {
-        impl_array!(self, |a: ArcArrayD<_>| a
+    pub fn into_elems(self) -> Result<Vec<Elem>> {
+        if self.is_sparse() {
+            return self.to_dense()?.into_elems();
+        }
+        Ok(impl_array!(self, |a: ArcArrayD<_>| a
             .into_iter()
             .map(Elem::from)
-            .collect())
+            .collect()))
     }
 
     pub fn into_nums(self) -> Option<Vec<Num>> {
@@ -382,8 +467,9 @@ impl JArray {
             RationalArray(a) => a.into_iter().map(|v| v.into()).collect(),
             FloatArray(a) => a.into_iter().map(|v| v.into()).collect(),
             ComplexArray(a) => a.into_iter().map(|v| v.into()).collect(),
-            CharArray(_) => return None,
+            CharArray(..) | SymbolArray(_) => return None,
             BoxArray(_) => return None,
+            SparseArray(s) => return s.to_dense().ok()?.into_nums(),
         })
     }
 
@@ -391,13 +477,7 @@ impl JArray {
         if self.len_of_0() != 1 {
             return None;
         }
-        Some(
-            self.clone()
-                .into_elems()
-                .into_iter()
-                .next()
-                .expect("checked"),
-        )
+        self.clone().into_elems().ok()?.into_iter().next()
     }
 
     pub fn single_math_num(&self) -> Option<Num> {
@@ -412,8 +492,9 @@ impl JArray {
             RationalArray(a) => a.first().map(|v| v.clone().into()),
             FloatArray(a) => a.first().map(|&v| v.into()),
             ComplexArray(a) => a.first().map(|v| v.clone().into()),
-            CharArray(_) => return None,
+            CharArray(..) | SymbolArray(_) => return None,
             BoxArray(_) => return None,
+            SparseArray(s) => s.to_dense().ok()?.single_math_num(),
         }
     }
 
@@ -595,10 +676,42 @@ impl JArray {
     /// # use jr::JArray;
     /// assert_eq!(
     ///     JArray::from_string("hello"),
-    ///     JArray::CharArray(array!['h', 'e', 'l', 'l', 'o'].into_dyn().into_shared()),
+    ///     JArray::from(array!['h', 'e', 'l', 'l', 'o'].into_dyn()),
     /// );
     pub fn from_string(s: impl AsRef<str>) -> JArray {
-        JArray::from_list(s.as_ref().chars().collect_vec())
+        let chars = s.as_ref().chars().collect_vec();
+        let width = CharWidth::of_text(&chars);
+        JArray::CharArray(chars.into_array().into_shared(), width)
+    }
+
+    /// a char array of the narrowest type that holds its characters
+    pub fn from_chars(a: ArcArrayD<char>) -> JArray {
+        let width = CharWidth::of_all(a.iter());
+        JArray::CharArray(a, width)
+    }
+
+    /// the character type of a char array
+    pub fn char_width(&self) -> Option<CharWidth> {
+        match self {
+            JArray::CharArray(_, w) => Some(*w),
+            _ => None,
+        }
+    }
+
+    /// a char array retagged as `width`, which must hold its characters; other arrays as they are
+    pub fn with_char_width(self, width: CharWidth) -> JArray {
+        match self {
+            JArray::CharArray(a, _) => JArray::CharArray(a, width),
+            other => other,
+        }
+    }
+
+    /// a char array cut from `like`, which keeps its character type
+    pub fn keep_char_width(self, like: &JArray) -> JArray {
+        match like.char_width() {
+            Some(width) => self.with_char_width(width),
+            None => self,
+        }
     }
 }
 
@@ -607,7 +720,7 @@ impl JArray {
         use JArray::*;
         Some(match self {
             BoolArray(a) => a.map(|&v| v as f32),
-            CharArray(a) => a.map(|&v| v as u32 as f32),
+            CharArray(a, _) => a.map(|&v| v as u32 as f32),
             IntArray(a) => a.map(|&v| v as f32),
             ExtIntArray(a) => a.map(|v| v.to_f32().unwrap_or(f32::NAN)),
             RationalArray(a) => a.map(|v| v.to_f32().unwrap_or(f32::NAN)),
@@ -626,10 +739,23 @@ impl JArray {
         })
     }
 
+    /// coerce bool and int arrays into float arrays, and return float arrays as-is; shape preserved
+    pub fn to_f64(&self) -> Option<CowArrayD<'_, f64>> {
+        use JArray::*;
+        Some(match self {
+            BoolArray(a) => a.map(|&v| f64::from(v)).into(),
+            IntArray(a) => a.map(|&v| v as f64).into(),
+            FloatArray(a) => a.into(),
+            _ => return None,
+        })
+    }
+
     pub fn to_rat(&self) -> Option<CowArrayD<BigRational>> {
         use JArray::*;
         Some(match self {
+            BoolArray(a) => a.map(|&v| BigRational::from_integer(v.into())).into(),
             IntArray(a) => a.map(|&v| BigRational::new(v.into(), 1.into())).into(),
+            ExtIntArray(a) => a.map(|v| BigRational::from_integer(v.clone())).into(),
             RationalArray(a) => a.into(),
             // TODO: entirely missing other implementations
             _ => return None,
@@ -640,7 +766,7 @@ impl JArray {
         use JArray::*;
         Some(match self {
             BoolArray(a) => a.map(|&v| Complex64::new(f64::from(v), 0.)).into(),
-            CharArray(a) => a.map(|&v| Complex64::new(f64::from(v as u32), 0.)).into(),
+            CharArray(a, _) => a.map(|&v| Complex64::new(f64::from(v as u32), 0.)).into(),
             IntArray(a) => a.map(|&v| Complex64::new(v as f64, 0.)).into(),
             ExtIntArray(a) => a
                 .map(|v| Complex64::new(v.to_f64().unwrap_or(f64::NAN), 0.))
@@ -652,7 +778,7 @@ impl JArray {
             FloatArray(a) => a.map(|&v| Complex64::new(v, 0.)).into(),
             ComplexArray(a) => a.into(),
             // ??
-            BoxArray(_) => return None,
+            SymbolArray(_) | BoxArray(_) | SparseArray(_) => return None,
         })
     }
 
@@ -665,7 +791,7 @@ impl JArray {
 
     pub fn when_char(&self) -> Option<&ArcArrayD<char>> {
         match self {
-            JArray::CharArray(arr) => Some(arr),
+            JArray::CharArray(arr, _) => Some(arr),
             _ => None,
         }
     }
@@ -733,12 +859,13 @@ macro_rules! impl_from_nd {
 }
 
 impl_from_nd!(u8, JArray::BoolArray);
-impl_from_nd!(char, JArray::CharArray);
+impl_from_nd!(char, JArray::from_chars);
 impl_from_nd!(i64, JArray::IntArray);
 impl_from_nd!(BigInt, JArray::ExtIntArray);
 impl_from_nd!(BigRational, JArray::RationalArray);
 impl_from_nd!(f64, JArray::FloatArray);
 impl_from_nd!(Complex64, JArray::ComplexArray);
+impl_from_nd!(Symbol, JArray::SymbolArray);
 impl_from_nd!(JArray, JArray::BoxArray);
 
 impl From<Num> for JArray {
@@ -757,7 +884,8 @@ impl From<Num> for JArray {
 impl From<Elem> for JArray {
     fn from(value: Elem) -> Self {
         match value {
-            Elem::Char(a) => JArray::CharArray(arr0ad(a)),
+            Elem::Char(a) => JArray::from_chars(arr0ad(a)),
+            Elem::Symbol(a) => JArray::SymbolArray(arr0ad(a)),
             Elem::Boxed(a) => JArray::BoxArray(arr0ad(a)),
             Elem::Num(a) => JArray::from(a),
         }
@@ -0,0 +1,241 @@
+//! Sparse arrays, as made by `$.`
+//! https://www.jsoftware.com/help/dictionary/d211.htm
+//!
+//! Every axis is sparse: the array is the positions of the atoms which (might) differ from the
+//! sparse element, and their values. Dense axes aren't supported.
+
+use std::iter;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::{EitherOrBoth, Itertools};
+use ndarray::prelude::*;
+
+use crate::arrays::{size_of_shape_checked, ArcArrayD, JArrayKind};
+use crate::number::{infer_kind_from_boxes, Promote};
+use crate::{map_kind, JArray, JError};
+
+/// the largest sparse array we'll convert back to a dense one
+const DENSE_LIMIT: usize = 1 << 26;
+
+#[derive(Clone, Debug)]
+pub struct Sparse {
+    shape: Vec<usize>,
+    /// one row per stored atom, in ascending order
+    indices: Vec<Vec<usize>>,
+    /// a list, with an atom for each of the `indices`
+    values: JArray,
+    /// the sparse element: the value of every atom which isn't stored
+    fill: JArray,
+}
+
+impl Sparse {
+    /// `indices` needn't be in order, but they must be distinct
+    pub fn new(
+        shape: Vec<usize>,
+        indices: Vec<Vec<usize>>,
+        values: JArray,
+        fill: JArray,
+    ) -> Result<Sparse> {
+        if !fill.shape().is_empty() {
+            return Err(JError::RankError).context("the sparse element must be an atom");
+        }
+        if values.shape() != [indices.len()] {
+            return Err(JError::LengthError)
+                .with_context(|| anyhow!("{} indices for {:?} values", indices.len(), values));
+        }
+        for index in &indices {
+            if index.len() != shape.len() || index.iter().zip(&shape).any(|(i, s)| i >= s) {
+                return Err(JError::IndexError)
+                    .with_context(|| anyhow!("{index:?} isn't in an array of shape {shape:?}"));
+            }
+        }
+
+        let order = (0..indices.len())
+            .sorted_by(|&l, &r| indices[l].cmp(&indices[r]))
+            .collect_vec();
+        if order
+            .iter()
+            .tuple_windows()
+            .any(|(&l, &r)| indices[l] == indices[r])
+        {
+            return Err(JError::NonUniqueSparseElements).context("an index is repeated");
+        }
+        let values = values.select(Axis(0), &order)?;
+        let indices = order.into_iter().map(|i| indices[i].clone()).collect();
+
+        let (values, fill) = unify(values, fill)?;
+        if matches!(
+            infer_kind_from_boxes(std::slice::from_ref(&fill)),
+            JArrayKind::ExtInt | JArrayKind::Rational | JArrayKind::Symbol
+        ) {
+            return Err(JError::DomainError).context("no sparse extended, rationals or symbols");
+        }
+        Ok(Sparse {
+            shape,
+            indices,
+            values,
+            fill,
+        })
+    }
+
+    /// an array of nothing but `fill`
+    pub fn empty(shape: Vec<usize>, fill: JArray) -> Result<Sparse> {
+        let values = fill.rank_extend(1).select(Axis(0), &[])?;
+        Sparse::new(shape, Vec::new(), values, fill)
+    }
+
+    /// the atoms of `arr` which aren't `fill`
+    pub fn from_dense(arr: &JArray, fill: JArray) -> Result<Sparse> {
+        let fill_elem = fill
+            .single_elem()
+            .ok_or(JError::RankError)
+            .context("the sparse element must be an atom")?;
+        let positions = arr
+            .clone()
+            .into_elems()?
+            .into_iter()
+            .positions(|e| e != fill_elem)
+            .collect_vec();
+        let indices = positions.iter().map(|&p| unravel(p, arr.shape())).collect();
+        let values = arr
+            .reshape(vec![arr.tally()])?
+            .select(Axis(0), &positions)?;
+        Sparse::new(arr.shape().to_vec(), indices, values, fill)
+    }
+
+    pub fn to_dense(&self) -> Result<JArray> {
+        let tally = size_of_shape_checked(&IxDyn(&self.shape))?;
+        if tally > DENSE_LIMIT {
+            return Err(JError::LimitError)
+                .with_context(|| anyhow!("a dense array of shape {:?} is too big", self.shape));
+        }
+        let fill = self
+            .fill
+            .single_elem()
+            .ok_or(JError::DomainError)
+            .context("the sparse element must be an atom")?;
+        let mut elems = vec![fill; tally];
+        let strides = strides(&self.shape);
+        for (index, value) in self.indices.iter().zip(self.values.clone().into_elems()?) {
+            elems[ravel(index, &strides)] = value;
+        }
+        let kind = infer_kind_from_boxes(std::slice::from_ref(&self.fill));
+        Ok(map_kind!(kind, || ArcArrayD::from_shape_vec(
+            IxDyn(&self.shape),
+            elems.iter().cloned().map(Promote::promote).collect(),
+        )))
+    }
+
+    /// the axes in the order `perm`, so axis `k` of the result is axis `perm[k]` of this
+    pub fn permute(&self, perm: &[usize]) -> Result<Sparse> {
+        let shape = perm.iter().map(|&p| self.shape[p]).collect();
+        let indices = self
+            .indices
+            .iter()
+            .map(|index| perm.iter().map(|&p| index[p]).collect())
+            .collect();
+        Sparse::new(shape, indices, self.values.clone(), self.fill.clone())
+    }
+
+    /// the same atoms as a list, as `,` makes
+    pub fn ravel(&self) -> Result<Sparse> {
+        let strides = strides(&self.shape);
+        let tally = size_of_shape_checked(&IxDyn(&self.shape))?;
+        let indices = self
+            .indices
+            .iter()
+            .map(|index| vec![ravel(index, &strides)])
+            .collect();
+        Sparse::new(vec![tally], indices, self.values.clone(), self.fill.clone())
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn indices(&self) -> &[Vec<usize>] {
+        &self.indices
+    }
+
+    pub fn values(&self) -> &JArray {
+        &self.values
+    }
+
+    pub fn fill(&self) -> &JArray {
+        &self.fill
+    }
+
+    /// the stored atoms, with their indices
+    pub fn entries(&self) -> Result<impl Iterator<Item = (&Vec<usize>, JArray)>> {
+        Ok(self.indices.iter().zip(self.values.outer_iter()?))
+    }
+
+    /// whether `eq` holds between each atom and the atom in the same place in `other`, which
+    /// is the same shape; the sparse elements are only compared if some atom isn't stored
+    pub fn all_atoms(&self, other: &Sparse, eq: impl Fn(&JArray, &JArray) -> bool) -> bool {
+        let (Ok(entries), Ok(others)) = (self.entries(), other.entries()) else {
+            return false;
+        };
+        let mut stored = 0;
+        let all = entries
+            .merge_join_by(others, |(l, _), (r, _)| l.cmp(r))
+            .all(|e| {
+                stored += 1;
+                match e {
+                    EitherOrBoth::Both((_, l), (_, r)) => eq(&l, &r),
+                    EitherOrBoth::Left((_, l)) => eq(&l, &other.fill),
+                    EitherOrBoth::Right((_, r)) => eq(&self.fill, &r),
+                }
+            });
+        let tally = size_of_shape_checked(&IxDyn(&self.shape)).ok();
+        all && (tally == Some(stored) || eq(&self.fill, &other.fill))
+    }
+}
+
+impl PartialEq for Sparse {
+    fn eq(&self, other: &Self) -> bool {
+        self.shape == other.shape
+            && self.fill == other.fill
+            && self.indices == other.indices
+            && self.values == other.values
+    }
+}
+
+/// `values` and `fill`, promoted to the same kind
+fn unify(values: JArray, fill: JArray) -> Result<(JArray, JArray)> {
+    let kind_of = |arr: &JArray| infer_kind_from_boxes(std::slice::from_ref(arr));
+    if kind_of(&values) == kind_of(&fill) {
+        return Ok((values, fill));
+    }
+    let n = values.tally();
+    let all = JArray::from_fill_promote(values.outer_iter()?.chain(iter::once(fill)))?;
+    let fill = all.select(Axis(0), &[n])?.reshape(IxDyn(&[]))?;
+    Ok((all.select(Axis(0), &(0..n).collect_vec())?, fill))
+}
+
+fn strides(shape: &[usize]) -> Vec<usize> {
+    let mut strides = vec![1; shape.len()];
+    for k in (0..shape.len().saturating_sub(1)).rev() {
+        strides[k] = strides[k + 1] * shape[k + 1];
+    }
+    strides
+}
+
+fn ravel(index: &[usize], strides: &[usize]) -> usize {
+    index.iter().zip(strides).map(|(i, s)| i * s).sum()
+}
+
+pub fn unravel(mut position: usize, shape: &[usize]) -> Vec<usize> {
+    let mut index = vec![0; shape.len()];
+    for k in (0..shape.len()).rev() {
+        index[k] = position % shape[k];
+        position /= shape[k];
+    }
+    index
+}
+
+impl From<Sparse> for JArray {
+    fn from(value: Sparse) -> JArray {
+        JArray::SparseArray(value.into())
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// An element of a symbol array: a cheap, shared string.
+///
+/// Symbols are created through a [`SymbolTable`], so equal symbols usually share their text,
+/// but everything else about them (comparison, ordering, display) only looks at the text.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(Arc<str>);
+
+impl Symbol {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Symbol {
+    /// the fill for symbol arrays
+    fn default() -> Self {
+        Symbol(Arc::from(""))
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}", self.0)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}", self.0)
+    }
+}
+
+/// The interned symbols, in the order they were first seen, for `s:`
+#[derive(Default, Debug)]
+pub struct SymbolTable {
+    symbols: Vec<Symbol>,
+    indexes: HashMap<Symbol, usize>,
+}
+
+impl SymbolTable {
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        let index = self.index_of(&Symbol(Arc::from(name)));
+        self.symbols[index].clone()
+    }
+
+    /// the symbol's position in the table, adding it if it's new (e.g. made by another `Ctx`)
+    pub fn index_of(&mut self, symbol: &Symbol) -> usize {
+        if let Some(&index) = self.indexes.get(symbol) {
+            return index;
+        }
+        self.symbols.push(symbol.clone());
+        self.indexes.insert(symbol.clone(), self.symbols.len() - 1);
+        self.symbols.len() - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Symbol> {
+        self.symbols.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// the total length of the symbols' text
+    pub fn string_bytes(&self) -> usize {
+        self.symbols.iter().map(|s| s.0.len()).sum()
+    }
+}
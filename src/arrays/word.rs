@@ -8,8 +8,9 @@ use ndarray::prelude::*;
 
 use crate::eval::quote_arr;
 use crate::modifiers::ModifierImpl;
+use crate::scan::str_to_primitive;
 use crate::verbs::{stringify, VerbImpl};
-use crate::{primitive_verbs, JArray, JError};
+use crate::{primitive_verbs, Ctx, JArray, JError};
 
 // A Word is a part of speech.
 #[derive(Clone, PartialEq, Debug)]
@@ -57,9 +58,12 @@ pub enum Word {
     For(Option<String>),
     While,
     Whilst,
+    Break,
+    Continue,
 
     Select,
     Case,
+    FCase,
 
     Assert,
 }
@@ -77,7 +81,7 @@ impl Word {
             If | Do | Else | ElseIf | End => true,
             For(_) | While | Whilst => true,
             Try | Catch | CatchD | CatchT => true,
-            Select | Case => true,
+            Select | Case | FCase => true,
             Assert => true,
             LP | RP | Name(_) | IsLocal | IsGlobal => false,
             Verb(_) | Noun(_) | Adverb(_) | Conjunction(_) => false,
@@ -87,7 +91,7 @@ impl Word {
             | AssertLine(_)
             | TryBlock(_)
             | SelectBlock(_) => false,
-            Throw | Return => false,
+            Throw | Return | Break | Continue => false,
             NewLine => false,
             StartOfLine | Nothing => false,
             Comment => unreachable!("should have been removed from the stream by now"),
@@ -118,7 +122,7 @@ macro_rules! impl_from_atom {
         }
     };
 }
-impl_from_atom!(char, JArray::CharArray);
+impl_from_atom!(char, JArray::from_chars);
 impl_from_atom!(u8, JArray::BoolArray);
 impl_from_atom!(i32, JArray::IntArray);
 impl_from_atom!(i64, JArray::IntArray);
@@ -151,11 +155,14 @@ impl Word {
             IfBlock(block) => format!("if. {} end.", stringify(block)),
             SelectBlock(block) => format!("select. {} end.", stringify(block)),
             Case => "case.".to_string(),
+            FCase => "fcase.".to_string(),
             Do => "do.".to_string(),
             Else => "else.".to_string(),
             ElseIf => "elseif.".to_string(),
             End => "end.".to_string(),
             Return => "return.".to_string(),
+            Break => "break.".to_string(),
+            Continue => "continue.".to_string(),
             NewLine => "\n".to_string(),
             IsLocal => "=.".to_string(),
             IsGlobal => "=:".to_string(),
@@ -176,4 +183,97 @@ impl Word {
             _ => Err(JError::NonceError).with_context(|| anyhow!("can't Word::boxed_ar {self:?}")),
         }
     }
+
+    /// the word `ar` is the `boxed_ar` of, with its modifiers applied again
+    pub fn from_boxed_ar(ctx: &mut Ctx, ar: &JArray) -> Result<Word> {
+        use Word::*;
+        // https://code.jsoftware.com/wiki/Vocabulary/Foreigns#m5
+        let b = match ar {
+            JArray::CharArray(s, _) if s.shape().len() <= 1 => {
+                return ar_primitive(&s.iter().collect::<String>())
+            }
+            JArray::BoxArray(b) if b.shape() == [2] => b,
+            _ => return Err(JError::DomainError).with_context(|| anyhow!("{ar:?} isn't an ar")),
+        };
+        let op = match &b[0] {
+            JArray::CharArray(op, _) if op.shape().len() <= 1 => op.iter().collect::<String>(),
+            op => {
+                return Err(JError::DomainError).with_context(|| anyhow!("{op:?} isn't an ar op"))
+            }
+        };
+        let mut args = |n: usize| -> Result<Vec<Word>> {
+            match &b[1] {
+                JArray::BoxArray(args) if args.shape() == [n] => {
+                    args.iter().map(|ar| Word::from_boxed_ar(ctx, ar)).collect()
+                }
+                args => Err(JError::DomainError)
+                    .with_context(|| anyhow!("{op:?} takes {n} ars, not {args:?}")),
+            }
+        };
+        Ok(match op.as_str() {
+            "0" => Noun(b[1].clone()),
+            "2" => {
+                let [l, r] = <[Word; 2]>::try_from(args(2)?).expect("checked above");
+                Verb(VerbImpl::Hook {
+                    l: Box::new(l),
+                    r: Box::new(r),
+                })
+            }
+            "3" => {
+                let [f, g, h] = <[Word; 3]>::try_from(args(3)?).expect("checked above");
+                Verb(VerbImpl::Fork {
+                    f: Box::new(f),
+                    g: Box::new(g),
+                    h: Box::new(h),
+                })
+            }
+            "4" => return Err(JError::NonceError).context("modifier trains have no ar"),
+            _ => match ar_primitive(&op)? {
+                Adverb(a) => {
+                    let [u] = <[Word; 1]>::try_from(args(1)?).expect("checked above");
+                    applied(a.form_adverb(ctx, &u)?)?
+                }
+                Conjunction(c) => {
+                    let [u, v] = <[Word; 2]>::try_from(args(2)?).expect("checked above");
+                    applied(c.form_conjunction(ctx, &u, &v)?)?
+                }
+                _ => {
+                    return Err(JError::DomainError)
+                        .with_context(|| anyhow!("{op:?} isn't a modifier"))
+                }
+            },
+        })
+    }
+}
+
+/// a primitive's ar, or a name's
+fn ar_primitive(s: &str) -> Result<Word> {
+    let number = |n: &str| match n {
+        "_" => Some(f64::INFINITY),
+        "__" => Some(f64::NEG_INFINITY),
+        _ => match n.strip_prefix('_') {
+            Some(n) => n.parse::<u32>().ok().map(|n| -f64::from(n)),
+            None => n.parse::<u32>().ok().map(f64::from),
+        },
+    };
+    if let Some(n) = s.strip_suffix(':').and_then(number) {
+        return Ok(Word::Verb(VerbImpl::Number(n)));
+    }
+    match str_to_primitive(s)? {
+        Some(w @ (Word::Noun(_) | Word::Verb(_) | Word::Adverb(_) | Word::Conjunction(_))) => Ok(w),
+        Some(w) => Err(JError::DomainError).with_context(|| anyhow!("{w:?} has no ar")),
+        None if s.starts_with(|c: char| c.is_ascii_alphabetic())
+            && s.chars().all(|c| c == '_' || c.is_ascii_alphanumeric()) =>
+        {
+            Ok(Word::Name(s.to_string()))
+        }
+        None => Err(JError::DomainError).with_context(|| anyhow!("{s:?} isn't a primitive")),
+    }
+}
+
+fn applied((farcical, word): (bool, Word)) -> Result<Word> {
+    match farcical {
+        false => Ok(word),
+        true => Err(JError::DomainError).context("a modifier in an ar ran, rather than deriving"),
+    }
 }
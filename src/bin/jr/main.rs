@@ -1,49 +1,148 @@
-use std::collections::VecDeque;
+use std::fs;
+use std::io::{self, BufRead, IsTerminal};
+use std::process::ExitCode;
+use std::thread;
 
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use cfg_if::cfg_if;
-use jr::{feed, Ctx, EvalOutput};
+use jr::{feed, Ctx, ErrorSite, EvalOutput, JArray, JError, Word};
 use log::warn;
 
 #[cfg(feature = "tui")]
 mod tui;
 
-fn main() -> Result<()> {
+const USAGE: &str = "usage: jr [-e sentence]... [script.ijs [args...]]";
+
+/// room for verbs nested as deeply as the `Ctx::stack_limit`, even in a debug build
+const STACK_SIZE: usize = 1 << 30;
+
+fn main() -> Result<ExitCode> {
     env_logger::init();
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)?
+        .join()
+        .map_err(|_| anyhow!("the interpreter panicked"))?
+}
+
+fn run() -> Result<ExitCode> {
+    let argv = std::env::args().collect::<Vec<_>>();
+    let mut sentences = Vec::new();
+    let mut script = None;
+    let mut it = argv.iter().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "-e" => sentences.push(it.next().ok_or_else(|| anyhow!("{USAGE}"))?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(ExitCode::SUCCESS);
+            }
+            _ => {
+                // everything after the script is for the script, via ARGV
+                script = Some(arg);
+                break;
+            }
+        }
+    }
+
+    let mut ctx = Ctx::root();
+    ctx.argv = argv.clone();
+    let boxed_argv = JArray::from_list(argv.iter().map(JArray::from_string).collect::<Vec<_>>());
+    ctx.eval_mut()
+        .locales
+        .assign_global("ARGV_z_", Word::Noun(boxed_argv))?;
+
+    if script.is_some() || !sentences.is_empty() {
+        let run = sentences
+            .into_iter()
+            .try_for_each(|sentence| eval(sentence, &mut ctx).map(|_| ()))
+            .and_then(|()| match script {
+                Some(script) => run_script(script, &mut ctx),
+                None => Ok(()),
+            });
+        return Ok(match run {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => fail(e),
+        });
+    }
+
+    if !io::stdin().is_terminal() {
+        return quiet_drive(&mut ctx);
+    }
 
     println!("jr {}", env!("CARGO_PKG_VERSION"));
 
     cfg_if! {
     if #[cfg(feature = "tui")] {
-        tui::drive()?
+        tui::drive(&mut ctx)
     } else {
-        plain_drive()?
+        plain_drive(&mut ctx)
     }
     }
-
-    Ok(())
 }
 
 #[cfg(not(feature = "tui"))]
-fn plain_drive() -> Result<()> {
-    use std::io::{self, Write};
-
-    let mut names = HashMap::new();
+fn plain_drive(ctx: &mut Ctx) -> Result<ExitCode> {
+    use std::io::Write;
 
     let mut buffer = String::new();
     let stdin = io::stdin();
     let mut stdout = io::stdout();
+    let mut state = EvalState::Regular;
     loop {
-        stdout.write_all(b"   ")?; //prompt
-        stdout.flush()?;
-        stdin.read_line(&mut buffer)?;
-
-        if eval(&buffer, &mut names)? {
-            break;
+        if state == EvalState::Regular {
+            stdout.write_all(b"   ")?; //prompt
+            stdout.flush()?;
         }
         buffer.truncate(0);
+        if stdin.read_line(&mut buffer)? == 0 {
+            return Ok(ExitCode::SUCCESS);
+        }
+
+        state = match repl_eval(&buffer, ctx) {
+            ReplState::Running(state) => state,
+            ReplState::Exit(code) => return Ok(code),
+        };
+        if state == EvalState::Done {
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+}
+
+/// like jconsole with piped input: no banner and no prompts; errors are reported and evaluation
+/// carries on, but the exit status remembers them
+fn quiet_drive(ctx: &mut Ctx) -> Result<ExitCode> {
+    let mut status = ExitCode::SUCCESS;
+    for line in io::stdin().lock().lines() {
+        if let Err(e) = eval(&line?, ctx) {
+            if let Some(code) = requested_exit(&e) {
+                return Ok(code);
+            }
+            report(&e);
+            status = ExitCode::FAILURE;
+        }
     }
+    Ok(status)
+}
 
+fn run_script(path: &str, ctx: &mut Ctx) -> Result<()> {
+    let script = match fs::read_to_string(path) {
+        Ok(script) => script,
+        Err(e) => {
+            return Err(JError::FileNameError).with_context(|| anyhow!("reading {path:?}: {e}"))
+        }
+    };
+    ctx.scripts.push((path.to_string(), script.clone()));
+
+    // like `0!:0`, scripts are run silently
+    for (off, line) in script.split('\n').enumerate() {
+        if let Err(mut e) = feed(line, ctx) {
+            if let Some(site) = e.downcast_mut::<ErrorSite>() {
+                site.in_script(path, off + 1);
+            }
+            return Err(e);
+        }
+    }
     Ok(())
 }
 
@@ -54,37 +153,63 @@ enum EvalState {
     MoreInput,
 }
 
-fn eval(buffer: &str, ctx: &mut Ctx) -> Result<EvalState> {
+enum ReplState {
+    Running(EvalState),
+    Exit(ExitCode),
+}
+
+/// errors are reported and then forgotten, unless they're a request to exit
+fn repl_eval(buffer: &str, ctx: &mut Ctx) -> ReplState {
     let buffer = buffer.trim();
     if "exit" == buffer || buffer.is_empty() {
-        return Ok(EvalState::Done);
+        return ReplState::Running(EvalState::Done);
+    }
+
+    match eval(buffer, ctx) {
+        Ok(state) => ReplState::Running(state),
+        Err(e) => match requested_exit(&e) {
+            Some(code) => ReplState::Exit(code),
+            None => {
+                report(&e);
+                ReplState::Running(EvalState::Regular)
+            }
+        },
     }
+}
 
-    match feed(buffer, ctx) {
-        //Ok(output) => println!("{:?}", output),
-        Ok(EvalOutput::Regular(output)) => println!("{}", output),
-        Ok(EvalOutput::Return(_)) => {
+fn eval(buffer: &str, ctx: &mut Ctx) -> Result<EvalState> {
+    match feed(buffer, ctx)? {
+        EvalOutput::Regular(Word::Nothing) => (),
+        EvalOutput::Regular(output) => println!("{}", output),
+        EvalOutput::Return(_) => {
             println!("weird error: return in interactive context");
         }
-        Ok(EvalOutput::Suspension) | Ok(EvalOutput::InDefinition) => {
-            return Ok(EvalState::MoreInput)
-        }
-        Err(e) => {
-            warn!("{:?}", e);
-            let mut stack: VecDeque<_> = e.chain().rev().collect();
-
-            println!(
-                "error: {}",
-                stack
-                    .pop_front()
-                    .expect("chain contains at least the error")
-            );
-
-            for error in stack {
-                println!("cause: {}", error);
-            }
+        EvalOutput::Break | EvalOutput::Continue => {
+            println!("weird error: break. or continue. outside of a loop");
         }
+        EvalOutput::Suspension | EvalOutput::InDefinition => return Ok(EvalState::MoreInput),
     }
 
     Ok(EvalState::Regular)
 }
+
+/// `2!:55`'s status, if that's what stopped evaluation
+fn requested_exit(e: &anyhow::Error) -> Option<ExitCode> {
+    match JError::extract(e) {
+        Some(JError::Exit(code)) => Some(ExitCode::from(*code as u8)),
+        _ => None,
+    }
+}
+
+fn report(e: &anyhow::Error) {
+    warn!("{:?}", e);
+    print!("{}", JError::report(e));
+}
+
+/// the exit status for a non-interactive run that stopped on `e`
+fn fail(e: anyhow::Error) -> ExitCode {
+    requested_exit(&e).unwrap_or_else(|| {
+        report(&e);
+        ExitCode::FAILURE
+    })
+}
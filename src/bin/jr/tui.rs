@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::collections::HashSet;
 use std::fs;
 use std::path::PathBuf;
+use std::process::ExitCode;
 
 use anyhow::Result;
 use colored::{Color, Colorize as _};
@@ -13,10 +14,10 @@ use rustyline::hint::{Hint, Hinter};
 use rustyline::Context;
 use rustyline_derive::{Completer, Helper, Highlighter, Validator};
 
-use crate::EvalState;
+use crate::{EvalState, ReplState};
 use jr::{scan_with_locations, Ctx, Word};
 
-pub fn drive() -> Result<()> {
+pub fn drive(ctx: &mut Ctx) -> Result<ExitCode> {
     let data_dir = match directories::ProjectDirs::from("github", "AshyIsMe", "jr") {
         Some(dirs) => dirs.data_dir().to_path_buf(),
         None => PathBuf::new(),
@@ -33,8 +34,6 @@ pub fn drive() -> Result<()> {
     }
     rl.set_auto_add_history(true);
 
-    let mut ctx = Ctx::root();
-
     let mut state = EvalState::Regular;
 
     loop {
@@ -48,14 +47,20 @@ pub fn drive() -> Result<()> {
             Err(other) => Err(other)?,
         };
 
-        state = super::eval(&line, &mut ctx)?;
+        state = match super::repl_eval(&line, ctx) {
+            ReplState::Running(state) => state,
+            ReplState::Exit(code) => {
+                rl.save_history(&hist_file)?;
+                return Ok(code);
+            }
+        };
         if state == EvalState::Done {
             break;
         }
     }
 
     rl.save_history(&hist_file)?;
-    Ok(())
+    Ok(ExitCode::SUCCESS)
 }
 
 #[derive(Completer, Helper, Validator, Highlighter, Default)]
@@ -22,7 +22,15 @@ use crate::{map_kind, Elem, JArray, JError};
 // }
 /// Kinda-internal version of [`fill_promote_list`] which reshapes the result to be compatible
 /// with the input, which is what the agreement internals want, but probably isn't what you want.
-pub fn fill_promote_reshape((frame, data): VerbResult) -> Result<JArray> {
+pub fn fill_promote_reshape(result: VerbResult) -> Result<JArray> {
+    fill_promote_reshape_with(result, None)
+}
+
+/// [`fill_promote_reshape`], padding with the atom `fill`, rather than with zeros
+pub fn fill_promote_reshape_with(
+    (frame, data): VerbResult,
+    fill: Option<&JArray>,
+) -> Result<JArray> {
     if frame.is_empty() && data.len() == 1 {
         return Ok(data.into_iter().next().expect("just checked"));
     }
@@ -34,11 +42,11 @@ pub fn fill_promote_reshape((frame, data): VerbResult) -> Result<JArray> {
         .ok_or(JError::NonceError)
         .context("non-empty macrocells")?;
 
-    // rank extend every child
+    // rank extend every (dense) child
     let results = data
         .into_iter()
-        .map(|arr| arr.rank_extend(max_rank))
-        .collect_vec();
+        .map(|arr| Ok(arr.to_dense()?.rank_extend(max_rank)))
+        .collect::<Result<Vec<_>>>()?;
 
     // max each dimension
     let target_inner_shape = results
@@ -63,16 +71,53 @@ pub fn fill_promote_reshape((frame, data): VerbResult) -> Result<JArray> {
         .copied()
         .collect_vec();
 
-    let kind = infer_kind_from_boxes(&results);
+    let kind = match fill {
+        Some(fill) => infer_kind_from_boxes(&[&results[..], std::slice::from_ref(fill)].concat()),
+        None => infer_kind_from_boxes(&results),
+    };
+
+    // characters and symbols don't mix with anything else; empty arrays, and the zeros which
+    // stand in for fill, fit in anywhere
+    let fits = |arr: &JArray| match (kind, arr) {
+        (_, arr) if arr.is_empty() => true,
+        (JArrayKind::Char, JArray::CharArray(..)) => true,
+        (JArrayKind::Symbol, JArray::SymbolArray(_)) => true,
+        (JArrayKind::Char | JArrayKind::Symbol, JArray::BoolArray(a)) => a.iter().all(|&b| b == 0),
+        (JArrayKind::Char | JArrayKind::Symbol, _) => false,
+        (_, JArray::CharArray(..) | JArray::SymbolArray(_)) => false,
+        _ => true,
+    };
+    if !results.iter().chain(fill).all(fits) {
+        return Err(JError::DomainError).context("characters and symbols don't mix with others");
+    }
+
+    // characters are as wide as the widest of the parts
+    let width = results
+        .iter()
+        .chain(fill)
+        .filter_map(JArray::char_width)
+        .max();
+    let widen = |arr: JArray| match width {
+        Some(width) => arr.with_char_width(width),
+        None => arr,
+    };
 
     if target_shape.iter().any(|dim| 0 == *dim) {
-        return Ok(map_kind!(kind, || ArcArray::from_shape_vec(
+        return Ok(widen(map_kind!(kind, || ArcArray::from_shape_vec(
             IxDyn(&target_shape),
             Vec::new()
-        )));
+        ))));
     }
 
-    return Ok(map_kind!(kind, || {
+    let fill = match fill {
+        Some(fill) => fill
+            .single_elem()
+            .ok_or(JError::LengthError)
+            .context("fill is an atom")?,
+        None => Elem::Num(Num::zero()),
+    };
+
+    return Ok(widen(map_kind!(kind, || {
         let mut big_daddy = Vec::with_capacity(size_of_shape_checked(&IxDyn(&target_shape))?);
         for arr in results {
             if arr.shape() == target_inner_shape {
@@ -80,14 +125,19 @@ pub fn fill_promote_reshape((frame, data): VerbResult) -> Result<JArray> {
                 continue;
             }
 
-            push_with_shape(&mut big_daddy, &target_inner_shape, arr)?;
+            push_with_shape(&mut big_daddy, &target_inner_shape, arr, &fill)?;
         }
-        Ok::<_, anyhow::Error>(ArcArrayD::from_shape_vec(target_shape, big_daddy)?.into())
-    }));
+        Ok::<_, anyhow::Error>(ArcArrayD::from_shape_vec(target_shape, big_daddy)?)
+    })));
 }
 
 // recursive implementation; lops off the start of the dims, recurses on that, then later fills
-fn push_with_shape<T: Promote>(out: &mut Vec<T>, target: &[usize], arr: JArray) -> Result<()> {
+fn push_with_shape<T: Promote>(
+    out: &mut Vec<T>,
+    target: &[usize],
+    arr: JArray,
+    fill: &Elem,
+) -> Result<()> {
     assert!(
         !target.is_empty(),
         "recursion has presumably gone wrong somewhere"
@@ -108,14 +158,14 @@ fn push_with_shape<T: Promote>(out: &mut Vec<T>, target: &[usize], arr: JArray)
     if remaining_dims.is_empty() {
         push_all(out, arr);
     } else {
-        let children = arr.outer_iter();
+        let children = arr.outer_iter()?;
         assert_eq!(initial_size, children.len());
         for item in children {
-            push_with_shape(out, remaining_dims, item.to_owned())?;
+            push_with_shape(out, remaining_dims, item.to_owned(), fill)?;
         }
     }
 
-    let fill = T::promote(Elem::Num(Num::zero()));
+    let fill = T::promote(fill.clone());
     let fills_needed = this_dim_size - initial_size;
     let fills_per_dim = remaining_dims.iter().product::<usize>();
 
@@ -134,26 +184,30 @@ fn push_all<T: Promote>(out: &mut Vec<T>, arr: JArray) {
     // couldn't get impl_array to work here, which would be less gross
     match arr {
         JArray::BoolArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
-        JArray::CharArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
+        JArray::CharArray(arr, _) => out.extend(arr.into_iter().map(conv!(T))),
         JArray::IntArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
         JArray::ExtIntArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
         JArray::RationalArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
         JArray::FloatArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
         JArray::ComplexArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
+        JArray::SymbolArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
         JArray::BoxArray(arr) => out.extend(arr.into_iter().map(conv!(T))),
+        JArray::SparseArray(_) => unreachable!("densified in fill_promote_reshape"),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::arrays::ArcArrayD;
-    use crate::{arr0ad, JArray};
+    use crate::{arr0ad, Elem, JArray, Num};
     use ndarray::array;
+    use num_traits::Zero;
 
     fn push(target: &[usize], arr: ArcArrayD<i64>) -> Vec<i64> {
         let mut out: Vec<i64> = Vec::new();
         let arr = JArray::IntArray(arr).rank_extend(target.len());
-        super::push_with_shape(&mut out, target, arr).expect("push success");
+        let zero = Elem::Num(Num::zero());
+        super::push_with_shape(&mut out, target, arr, &zero).expect("push success");
         out.into_iter().collect()
     }
 
@@ -51,8 +51,8 @@ pub fn generate_cells(
         (true, true) | (false, true) => x_surplus,
     };
 
-    let x_macrocells = x.dims_iter(common_dims);
-    let y_macrocells = y.dims_iter(common_dims);
+    let x_macrocells = x.dims_iter(common_dims)?;
+    let y_macrocells = y.dims_iter(common_dims)?;
 
     assert_eq!(x_macrocells.len(), y_macrocells.len());
 
@@ -67,7 +67,7 @@ pub fn generate_cells(
 
 pub fn monad_cells(y: &JArray, arg_rank: Rank) -> Result<(Vec<JArray>, Vec<usize>)> {
     let frame = frame_of(y.shape(), arg_rank)?;
-    Ok((y.rank_iter(arg_rank.raw_u8().into()), frame))
+    Ok((y.rank_iter(arg_rank.raw_u8().into())?, frame))
 }
 
 pub fn monad_apply(
@@ -85,8 +85,14 @@ pub fn apply_cells(
     cells
         .iter()
         .flat_map(|(x, y)| {
-            let x_parts = x.rank_iter(x_arg_rank.raw_u8().into());
-            let y_parts = y.rank_iter(y_arg_rank.raw_u8().into());
+            let parts = (
+                x.rank_iter(x_arg_rank.raw_u8().into()),
+                y.rank_iter(y_arg_rank.raw_u8().into()),
+            );
+            let (x_parts, y_parts) = match parts {
+                (Ok(x_parts), Ok(y_parts)) => (x_parts, y_parts),
+                (Err(e), _) | (_, Err(e)) => return vec![Err(e)],
+            };
             match (x_parts.len(), y_parts.len()) {
                 (1, _) | (_, 1) => (),
                 _ => unreachable!(
@@ -103,7 +109,6 @@ pub fn apply_cells(
                 .map(|(x, y)| f(&x, &y))
                 // TODO: comedy borrow checker
                 .collect_vec()
-                .into_iter()
         })
         .collect()
 }
@@ -1,5 +1,5 @@
 mod flatten;
 mod gen_apply;
 
-pub use flatten::fill_promote_reshape;
+pub use flatten::{fill_promote_reshape, fill_promote_reshape_with};
 pub use gen_apply::{apply_cells, generate_cells, monad_apply, monad_cells};
@@ -1,9 +1,16 @@
 mod locales;
 
 use anyhow::{ensure, Context, Result};
+use std::collections::BTreeMap;
+use std::fs::File;
 use std::ops::{Deref, DerefMut};
+use std::path::PathBuf;
 
+use crate::arrays::SymbolTable;
 use crate::eval::Qs;
+use crate::modifiers::FoldControl;
+use crate::number::DEFAULT_TOLERANCE;
+use crate::verbs::VerbImpl;
 use crate::JError;
 
 // :)
@@ -11,11 +18,38 @@ pub use locales::Eval;
 // :(
 pub use locales::Names;
 
+/// far deeper than sensible recursion, but short of overflowing the stack
+pub const DEFAULT_STACK_LIMIT: usize = 10_000;
+
 #[derive(Debug)]
 pub struct Ctx {
     eval: Eval,
     pub input_buffers: Option<InputBuffers>,
     pub scripts: Vec<(String, String)>,
+    /// the most recent error picked up by a `catch.`, for `13!:11` and friends
+    pub last_error: Option<anyhow::Error>,
+    /// the command line, program name first, for `2!:4` and `ARGV_z_`
+    pub argv: Vec<String>,
+    /// files opened by `1!:21`, by file number; they're closed when the `Ctx` is dropped
+    pub files: BTreeMap<i64, OpenFile>,
+    /// every symbol made by `s:`, in creation order
+    pub symbols: SymbolTable,
+    /// the folds which are running, innermost last, for `Z:`
+    pub folds: Vec<FoldControl>,
+    /// the verbs being executed which `$:` could mean, innermost last
+    pub selves: Vec<VerbImpl>,
+    /// how many verbs, other than primitives, are running inside one another
+    pub depth: usize,
+    /// how deep that may get before it's a stack error, set by `9!:99`
+    pub stack_limit: usize,
+    /// the comparison tolerance of the primitives, set by `9!:19`
+    pub tolerance: f64,
+}
+
+#[derive(Debug)]
+pub struct OpenFile {
+    pub path: PathBuf,
+    pub file: File,
 }
 
 #[derive(Debug)]
@@ -39,6 +73,15 @@ impl Ctx {
                 other_input_buffer: String::new(),
             }),
             scripts: Vec::new(),
+            last_error: None,
+            argv: Vec::new(),
+            files: BTreeMap::new(),
+            symbols: SymbolTable::default(),
+            folds: Vec::new(),
+            selves: Vec::new(),
+            depth: 0,
+            stack_limit: DEFAULT_STACK_LIMIT,
+            tolerance: DEFAULT_TOLERANCE,
         }
     }
 
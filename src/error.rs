@@ -1,3 +1,5 @@
+use std::fmt;
+
 use thiserror::Error;
 
 // TODO: https://code.jsoftware.com/wiki/Vocabulary/ErrorMessages
@@ -75,6 +77,10 @@ pub enum JError {
 
     #[error("{0} (legacy)")]
     Legacy(String),
+
+    /// `2!:55`, which nothing should catch
+    #[error("exit requested, with status {0}")]
+    Exit(i32),
 }
 
 impl JError {
@@ -85,4 +91,196 @@ impl JError {
     pub fn extract(err: &anyhow::Error) -> Option<&JError> {
         err.root_cause().downcast_ref::<JError>()
     }
+
+    /// the error number, as reported by `13!:11`, from jsource's `je.h`
+    pub fn number(&self) -> i64 {
+        use JError::*;
+        match self {
+            Break => 2,
+            DomainError => 3,
+            IllFormedName => 4,
+            IllFormedNumber => 5,
+            IndexError => 6,
+            InterfaceError => 7,
+            LengthError | ShapeError(_) => 9,
+            LimitError => 10,
+            NonceError => 11,
+            AssertionFailure => 12,
+            OpenQuote => 13,
+            RankError => 14,
+            SpellingError => 16,
+            StackError => 17,
+            SyntaxError => 19,
+            StackSuspension | Legacy(_) | Exit(_) => 20,
+            ValueError => 21,
+            OutOfMemory => 22,
+            ControlError => 23,
+            FileNameError => 25,
+            FileNumberError => 26,
+            TimeLimit => 27,
+            SecurityViolation => 28,
+            NonUniqueSparseElements => 29,
+            LocaleError => 30,
+            NaNError => 33,
+            NounResultWasRequired => 34,
+            UncaughtThrow => 35,
+            FoldLimit => 36,
+        }
+    }
+
+    /// the short description jsoftware prints, e.g. `|domain error`
+    pub fn short_name(&self) -> &'static str {
+        use JError::*;
+        match self {
+            Break => "break",
+            DomainError => "domain error",
+            IllFormedName => "ill-formed name",
+            IllFormedNumber => "ill-formed number",
+            IndexError => "index error",
+            InterfaceError => "interface error",
+            LengthError | ShapeError(_) => "length error",
+            LimitError => "limit error",
+            NonceError => "nonce error",
+            AssertionFailure => "assertion failure",
+            OpenQuote => "open quote",
+            RankError => "rank error",
+            SpellingError => "spelling error",
+            StackError => "stack error",
+            SyntaxError => "syntax error",
+            StackSuspension | Legacy(_) => "system error",
+            Exit(_) => "exit",
+            ValueError => "value error",
+            OutOfMemory => "out of memory",
+            ControlError => "control error",
+            FileNameError => "file name error",
+            FileNumberError => "file number error",
+            TimeLimit => "time limit",
+            SecurityViolation => "security violation",
+            NonUniqueSparseElements => "non-unique sparse elements",
+            LocaleError => "locale error",
+            NaNError => "NaN error",
+            NounResultWasRequired => "noun result was required",
+            UncaughtThrow => "uncaught throw.",
+            FoldLimit => "fold limit",
+        }
+    }
+}
+
+/// Where evaluation of a sentence failed, attached to errors by `eval_suspendable`, and rendered
+/// the way jsoftware does by `JError::report`.
+#[derive(Debug, Clone)]
+pub struct ErrorSite {
+    /// the failing sentence, as words
+    words: Vec<String>,
+    /// the index of the word where the failure was noticed, usually the verb being executed
+    gap: usize,
+    /// the sentence as it was typed, and the byte offset of the gap in it, when that's known
+    source: Option<(String, usize)>,
+    /// the sentence's position in its explicit definition
+    line: Option<usize>,
+    /// set once the error has escaped from the explicit definition it happened in
+    left_definition: bool,
+    /// the name the definition was called by, if any
+    name: Option<String>,
+    named: bool,
+    /// the script, and line in the script, being loaded, for errors during `0!:k`
+    script: Option<(String, usize)>,
+}
+
+impl ErrorSite {
+    pub fn new(words: Vec<String>, gap: usize) -> Self {
+        ErrorSite {
+            words,
+            gap,
+            source: None,
+            line: None,
+            left_definition: false,
+            name: None,
+            named: false,
+            script: None,
+        }
+    }
+
+    /// whether the failing sentence is the one being run directly, i.e. not inside a definition
+    pub fn is_top_level(&self) -> bool {
+        self.line.is_none()
+    }
+
+    pub fn gap(&self) -> usize {
+        self.gap
+    }
+
+    pub fn in_source(&mut self, source: &str, gap: usize) {
+        self.source = Some((source.to_string(), gap));
+    }
+
+    pub fn at_line(&mut self, line: usize) {
+        if !self.left_definition {
+            self.line = Some(line);
+        }
+    }
+
+    pub fn leave_definition(&mut self) {
+        self.left_definition = true;
+    }
+
+    pub fn called_as(&mut self, name: Option<&str>) {
+        if self.left_definition && !self.named {
+            self.name = name.map(|s| s.to_string());
+            self.named = true;
+        }
+    }
+
+    pub fn in_script(&mut self, path: &str, line: usize) {
+        if self.script.is_none() {
+            self.script = Some((path.to_string(), line));
+        }
+    }
+
+    fn split(&self) -> (String, String) {
+        match &self.source {
+            Some((source, gap)) => (
+                source[..*gap].trim().to_string(),
+                source[*gap..].trim().to_string(),
+            ),
+            None => (
+                self.words[..self.gap.min(self.words.len())].join(" "),
+                self.words[self.gap.min(self.words.len())..].join(" "),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ErrorSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (left, right) = self.split();
+        write!(f, "at {left:?} <here> {right:?}")
+    }
+}
+
+impl JError {
+    /// jconsole's rendering of an error, e.g. `|domain error` followed by the failing sentence,
+    /// split where the failure happened
+    pub fn report(err: &anyhow::Error) -> String {
+        let mut out = match JError::extract(err) {
+            Some(e) => format!("|{}", e.short_name()),
+            None => format!("|{}", err.root_cause()),
+        };
+        let Some(site) = err.downcast_ref::<ErrorSite>() else {
+            out.push('\n');
+            return out;
+        };
+        if let Some(name) = &site.name {
+            out.push_str(&format!(": {name}"));
+        }
+        let (left, right) = site.split();
+        out.push_str(&format!("\n|   {left}    {right}\n"));
+        if let (Some(name), Some(line)) = (&site.name, site.line) {
+            out.push_str(&format!("|{name}[{line}]\n"));
+        }
+        if let Some((path, line)) = &site.script {
+            out.push_str(&format!("|[-{line}] {path}\n"));
+        }
+        out
+    }
 }
@@ -4,7 +4,7 @@ use std::sync::Arc;
 use anyhow::{anyhow, bail, ensure, Context, Result};
 use itertools::Itertools;
 
-use crate::eval::eval_lines;
+use crate::eval::eval_definition;
 use crate::modifiers::{ModifierImpl, OwnedAdverb, OwnedConjunction};
 use crate::verbs::{BivalentOwned, PartialDef, PartialImpl, VerbImpl};
 use crate::{rank, HasEmpty, JArray, JError, Word};
@@ -176,9 +176,9 @@ pub fn create_def(mode: char, def: Vec<Word>) -> Result<Word> {
             f: Arc::new(move |ctx, u| {
                 let mut ctx = ctx.nest();
                 ctx.eval_mut().locales.assign_local("u", u.clone())?;
-                eval_lines(&def, &mut ctx)
+                eval_definition(&def, &mut ctx)
                     .context("anonymous")
-                    .map(|r| r.into_word())
+                    .and_then(|r| r.into_word())
                     .and_then(must_be_modifier_result)
             }),
         })),
@@ -192,9 +192,9 @@ pub fn create_def(mode: char, def: Vec<Word>) -> Result<Word> {
 
                 ctx.eval_mut().locales.assign_local("v", v.clone())?;
                 ctx.eval_mut().locales.assign_local("n", v.clone())?;
-                eval_lines(&def, &mut ctx)
+                eval_definition(&def, &mut ctx)
                     .context("anonymous")
-                    .map(|r| r.into_word())
+                    .and_then(|r| r.into_word())
                     .and_then(must_be_modifier_result)
             }),
         })),
@@ -206,9 +206,9 @@ pub fn create_def(mode: char, def: Vec<Word>) -> Result<Word> {
                     ctx.eval_mut()
                         .locales
                         .assign_local("y", Word::Noun(y.clone()))?;
-                    eval_lines(&body, &mut ctx)
+                    eval_definition(&body, &mut ctx)
                         .context("anonymous")
-                        .map(|r| r.into_word())
+                        .and_then(|r| r.into_word())
                         .map(nothing_to_empty)
                         .and_then(must_be_noun)
                 }),
@@ -233,9 +233,9 @@ pub fn create_def(mode: char, def: Vec<Word>) -> Result<Word> {
                     ctx.eval_mut()
                         .locales
                         .assign_local("y", Word::Noun(y.clone()))?;
-                    eval_lines(&body, &mut ctx)
+                    eval_definition(&body, &mut ctx)
                         .context("anonymous")
-                        .map(|r| r.into_word())
+                        .and_then(|r| r.into_word())
                         .map(nothing_to_empty)
                         .and_then(must_be_noun)
                 }),
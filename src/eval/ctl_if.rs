@@ -4,7 +4,7 @@ use log::debug;
 use num_traits::Zero;
 
 use crate::eval::{eval_lines, BlockEvalResult};
-use crate::{eval, Ctx, Elem, HasEmpty, JArray, JError, Num, Word};
+use crate::{Ctx, Elem, HasEmpty, JArray, JError, Num, Word};
 
 pub fn control_if(ctx: &mut Ctx, def: &[Word]) -> Result<BlockEvalResult> {
     debug!("control if.");
@@ -49,15 +49,7 @@ fn eval_cond_and_block(ctx: &mut Ctx, def: &[Word]) -> Result<Option<BlockEvalRe
         .ok_or(JError::SyntaxError)
         .context("no do. in if.")?;
 
-    let cond = match eval(cond.to_vec(), ctx).context("if statement conditional")? {
-        Word::Noun(arr) => arr
-            .into_elems()
-            .into_iter()
-            .next()
-            .map(|e| e != Elem::Num(Num::zero()))
-            .unwrap_or_default(),
-        _ => return Err(JError::NounResultWasRequired).context("evaluating if conditional"),
-    };
+    let cond = eval_cond(ctx, cond).context("if statement conditional")?;
 
     if cond {
         Ok(Some(
@@ -68,6 +60,20 @@ fn eval_cond_and_block(ctx: &mut Ctx, def: &[Word]) -> Result<Option<BlockEvalRe
     }
 }
 
+/// true if the T block is empty, or its result is empty, or the first atom of its result is nonzero
+pub fn eval_cond(ctx: &mut Ctx, cond: &[Word]) -> Result<bool> {
+    match eval_lines(cond, ctx)?.into_word()? {
+        Word::Nothing => Ok(true),
+        Word::Noun(arr) => Ok(arr
+            .into_elems()?
+            .into_iter()
+            .next()
+            .map(|e| e != Elem::Num(Num::zero()))
+            .unwrap_or(true)),
+        _ => Err(JError::NounResultWasRequired).context("evaluating conditional"),
+    }
+}
+
 pub fn split_once(words: &[Word], f: impl Fn(&Word) -> bool) -> Option<(&[Word], &[Word])> {
     words
         .iter()
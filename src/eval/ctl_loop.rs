@@ -1,13 +1,11 @@
 use crate::eval::controls::must_be_noun;
-use crate::eval::ctl_if::split_once;
+use crate::eval::ctl_if::{eval_cond, split_once};
 use crate::eval::{eval_lines, BlockEvalResult};
-use crate::{eval, Ctx, HasEmpty, JArray, JError, Word};
+use crate::{arr0d, eval, Ctx, HasEmpty, JArray, JError, Word};
 use anyhow::{Context, Result};
 
+/// `for_xyz.` also assigns each item to `xyz`, and its position to `xyz_index`, as local names
 pub fn control_for(ctx: &mut Ctx, style: Option<&str>, def: &[Word]) -> Result<BlockEvalResult> {
-    if style.is_some() {
-        return Err(JError::NonceError).context("for_ijk. not supported");
-    }
     let (cond, block) = split_once(def, |w| matches!(w, Word::Do))
         .ok_or(JError::SyntaxError)
         .context("no do. in for.")?;
@@ -15,14 +13,47 @@ pub fn control_for(ctx: &mut Ctx, style: Option<&str>, def: &[Word]) -> Result<B
     let arr = eval(cond.to_vec(), ctx).and_then(must_be_noun)?;
 
     let mut last = Word::Noun(JArray::empty());
-    for _item in arr.outer_iter() {
+    for (index, item) in arr.outer_iter()?.enumerate() {
+        if let Some(name) = style {
+            let locales = &mut ctx.eval_mut().locales;
+            locales.assign_local(name, Word::Noun(item))?;
+            locales.assign_local(
+                format!("{name}_index"),
+                Word::Noun(JArray::from(arr0d(i64::try_from(index)?))),
+            )?;
+        }
         match eval_lines(block, ctx)? {
             BlockEvalResult::Regular(n) => {
                 last = n;
             }
             BlockEvalResult::Return(v) => return Ok(BlockEvalResult::Return(v)),
+            BlockEvalResult::Break => break,
+            BlockEvalResult::Continue => continue,
+        }
+    }
+
+    Ok(BlockEvalResult::Regular(last))
+}
+
+/// `while.` checks the condition before every run of the block, `whilst.` skips the first check
+pub fn control_while(ctx: &mut Ctx, whilst: bool, def: &[Word]) -> Result<BlockEvalResult> {
+    let (cond, block) = split_once(def, |w| matches!(w, Word::Do))
+        .ok_or(JError::SyntaxError)
+        .context("no do. in while.")?;
+
+    let mut last = Word::Noun(JArray::empty());
+    let mut first = true;
+    while (whilst && first) || eval_cond(ctx, cond).context("while. conditional")? {
+        first = false;
+        match eval_lines(block, ctx).context("while. block")? {
+            BlockEvalResult::Regular(n) => {
+                last = n;
+            }
+            BlockEvalResult::Return(v) => return Ok(BlockEvalResult::Return(v)),
+            BlockEvalResult::Break => break,
+            BlockEvalResult::Continue => continue,
         }
     }
 
-    Ok(BlockEvalResult::Return(last))
+    Ok(BlockEvalResult::Regular(last))
 }
@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use itertools::Itertools;
+use log::debug;
+
+use crate::eval::controls::must_be_noun;
+use crate::eval::ctl_if::split_once;
+use crate::eval::{eval_lines, BlockEvalResult};
+use crate::{Ctx, Elem, HasEmpty, JArray, JError, Word};
+
+/// https://code.jsoftware.com/wiki/Vocabulary/select
+pub fn control_select(ctx: &mut Ctx, def: &[Word]) -> Result<BlockEvalResult> {
+    debug!("control select.");
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    for point in def
+        .iter()
+        .positions(|w| matches!(w, Word::Case | Word::FCase))
+    {
+        blocks.push(&def[start..point]);
+        start = point;
+    }
+    blocks.push(&def[start..]);
+
+    let subject = blocks.remove(0);
+    let subject = eval_lines(subject, ctx)
+        .and_then(|r| r.into_word())
+        .and_then(must_be_noun)
+        .context("select. subject")?;
+    let subject = boxed_elems(subject)?;
+
+    let mut falling = false;
+    let mut last = Word::Noun(JArray::empty());
+    for block in blocks {
+        let (cond, follow) = split_once(&block[1..], |w| matches!(w, Word::Do))
+            .ok_or(JError::SyntaxError)
+            .context("no do. in case.")?;
+
+        if !falling && !case_matches(ctx, &subject, cond)? {
+            continue;
+        }
+
+        match eval_lines(follow, ctx).context("case. block")? {
+            BlockEvalResult::Regular(w) => last = w,
+            other => return Ok(other),
+        }
+
+        // fcase. falls through to the following block, whatever its test
+        falling = matches!(block[0], Word::FCase);
+        if !falling {
+            break;
+        }
+    }
+
+    Ok(BlockEvalResult::Regular(last))
+}
+
+/// an empty test always matches, otherwise it's `+./ (<^:(0=L.) select) e.&, (<^:(0=L.) case)`
+fn case_matches(ctx: &mut Ctx, subject: &[Elem], cond: &[Word]) -> Result<bool> {
+    if cond.iter().all(|w| matches!(w, Word::NewLine)) {
+        return Ok(true);
+    }
+    let cond = eval_lines(cond, ctx)
+        .and_then(|r| r.into_word())
+        .and_then(must_be_noun)
+        .context("case. test")?;
+    let cond = boxed_elems(cond)?;
+    Ok(subject.iter().any(|s| cond.contains(s)))
+}
+
+fn boxed_elems(arr: JArray) -> Result<Vec<Elem>> {
+    match arr {
+        JArray::BoxArray(_) => arr.into_elems(),
+        other => Ok(vec![Elem::Boxed(other)]),
+    }
+}
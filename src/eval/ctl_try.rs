@@ -1,17 +1,48 @@
-use crate::eval::ctl_if::split_once;
 use crate::eval::{eval_lines, BlockEvalResult};
 use crate::{Ctx, JError, Word};
 use anyhow::{Context, Result};
+use itertools::Itertools;
 
+/// `catch.` and `catchd.` pick up errors, `catcht.` picks up only `throw.`s; nothing stops `2!:55`
 pub fn control_try(ctx: &mut Ctx, def: &[Word]) -> Result<BlockEvalResult> {
-    let (block, handle) = split_once(def, |w| {
-        matches!(w, Word::Catch | Word::CatchT | Word::CatchD)
-    })
-    .ok_or(JError::SyntaxError)
-    .context("no catch?. in try.")?;
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for point in def
+        .iter()
+        .positions(|w| matches!(w, Word::Catch | Word::CatchT | Word::CatchD))
+    {
+        sections.push(&def[start..point]);
+        start = point;
+    }
+    sections.push(&def[start..]);
+    let block = sections.remove(0);
+    if sections.is_empty() {
+        return Err(JError::SyntaxError).context("no catch?. in try.");
+    }
+
+    let err = match eval_lines(block, ctx) {
+        Ok(b) => return Ok(b),
+        Err(e) => e,
+    };
+
+    let thrown = match JError::extract(&err) {
+        Some(JError::Exit(_)) => return Err(err),
+        Some(JError::UncaughtThrow) => true,
+        _ => false,
+    };
+    let handler = sections.into_iter().find(|s| match s[0] {
+        Word::CatchT => thrown,
+        Word::Catch | Word::CatchD => !thrown,
+        _ => unreachable!("split on catch?. above"),
+    });
 
-    match eval_lines(block, ctx) {
-        Ok(b) => Ok(b),
-        Err(_) => eval_lines(handle, ctx),
+    match handler {
+        Some(handler) => {
+            if !thrown {
+                ctx.last_error = Some(err);
+            }
+            eval_lines(&handler[1..], ctx)
+        }
+        None => Err(err),
     }
 }
@@ -1,12 +1,13 @@
 mod controls;
 mod ctl_if;
 mod ctl_loop;
+mod ctl_select;
 mod ctl_try;
 mod semi;
 
 use std::borrow::Borrow;
 use std::collections::VecDeque;
-use std::iter::repeat;
+use std::iter::{once, repeat};
 
 use crate::verbs::j_monad_eval;
 use crate::{arr0ad, Ctx, JArray};
@@ -16,7 +17,7 @@ use log::{debug, trace};
 
 use ndarray::{ArcArray, IxDyn};
 
-use crate::error::JError;
+use crate::error::{ErrorSite, JError};
 // TODO: oh come on, this is clearly an eval concept
 pub use crate::eval::controls::create_def;
 // TODO: oh come on, this is clearly an eval concept
@@ -25,7 +26,8 @@ pub use crate::eval::controls::resolve_controls;
 pub use semi::{quote_arr, MaybeVerb, VerbNoun};
 
 use crate::eval::ctl_if::control_if;
-use crate::eval::ctl_loop::control_for;
+use crate::eval::ctl_loop::{control_for, control_while};
+use crate::eval::ctl_select::control_select;
 use crate::eval::ctl_try::control_try;
 use crate::modifiers::ModifierImpl;
 use crate::verbs::{v_open, VerbImpl};
@@ -41,6 +43,8 @@ pub struct Qs {
 pub enum EvalOutput {
     Regular(Word),
     Return(Word),
+    Break,
+    Continue,
     Suspension,
     InDefinition,
 }
@@ -68,7 +72,12 @@ pub fn feed(line: &str, ctx: &mut Ctx) -> Result<EvalOutput> {
         }
         return eval_suspendable(vec![], ctx);
     }
-    let mut tokens = crate::scan(&format!("{}{line}", buf.other_input_buffer))?;
+    let source = format!("{}{line}", buf.other_input_buffer);
+    let located = match crate::scan_with_locations(&source) {
+        Ok(located) => located,
+        Err(e) => return Err(e.context(whole_line_site(&source))),
+    };
+    let mut tokens = located.iter().map(|(_, w)| w.clone()).collect_vec();
     if !resolve_controls(&mut tokens)? {
         buf.other_input_buffer.push_str(line);
         buf.other_input_buffer.push('\n');
@@ -76,7 +85,33 @@ pub fn feed(line: &str, ctx: &mut Ctx) -> Result<EvalOutput> {
     }
     buf.other_input_buffer.clear();
     debug!("tokens: {:?}", tokens);
-    eval_suspendable(tokens, ctx).with_context(|| anyhow!("evaluating {:?}", line))
+    // the locations are only meaningful if control resolution didn't rearrange anything
+    let located = (located.len() == tokens.len()).then_some(located);
+    eval_suspendable(tokens, ctx)
+        .map_err(|mut e| {
+            match e.downcast_mut::<ErrorSite>() {
+                Some(site) if site.is_top_level() => {
+                    if let Some(located) = &located {
+                        let gap = located
+                            .get(site.gap())
+                            .map(|((start, _), _)| *start)
+                            .unwrap_or(source.len());
+                        site.in_source(&source, gap);
+                    }
+                }
+                Some(_) => (),
+                None => return e.context(whole_line_site(&source)),
+            }
+            e
+        })
+        .with_context(|| anyhow!("evaluating {:?}", line))
+}
+
+/// for errors which aren't attributable to a word, like those from scanning
+fn whole_line_site(source: &str) -> ErrorSite {
+    let mut site = ErrorSite::new(vec![], 0);
+    site.in_source(source, source.len());
+    site
 }
 
 pub fn eval(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<Word> {
@@ -85,6 +120,9 @@ pub fn eval(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<Word> {
         EvalOutput::Return(_) => {
             Err(JError::SyntaxError).context("return in a context which doesn't support return")
         }
+        EvalOutput::Break | EvalOutput::Continue => {
+            Err(JError::ControlError).context("break. or continue. outside of a loop")
+        }
         EvalOutput::InDefinition | EvalOutput::Suspension => Err(JError::StackSuspension)
             .context("suspended in a context which doesn't support suspension"),
     }
@@ -95,13 +133,29 @@ pub fn eval(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<Word> {
 pub enum BlockEvalResult {
     Regular(Word),
     Return(Word),
+    Break,
+    Continue,
 }
 
 impl BlockEvalResult {
-    fn into_word(self) -> Word {
+    fn into_word(self) -> Result<Word> {
         match self {
-            BlockEvalResult::Regular(word) => word,
-            BlockEvalResult::Return(word) => word,
+            BlockEvalResult::Regular(word) => Ok(word),
+            BlockEvalResult::Return(word) => Ok(word),
+            BlockEvalResult::Break | BlockEvalResult::Continue => {
+                Err(JError::ControlError).context("break. or continue. outside of a loop")
+            }
+        }
+    }
+}
+
+impl From<BlockEvalResult> for EvalOutput {
+    fn from(value: BlockEvalResult) -> Self {
+        match value {
+            BlockEvalResult::Regular(word) => EvalOutput::Regular(word),
+            BlockEvalResult::Return(word) => EvalOutput::Return(word),
+            BlockEvalResult::Break => EvalOutput::Break,
+            BlockEvalResult::Continue => EvalOutput::Continue,
         }
     }
 }
@@ -111,16 +165,27 @@ pub fn eval_lines(sentence: &[Word], ctx: &mut Ctx) -> Result<BlockEvalResult> {
     let mut word = Word::Nothing;
     for (rel_pos, sentence) in sentence
         .split(|w| matches!(w, Word::NewLine))
+        .filter(|sub| !sub.is_empty())
         .enumerate()
-        .filter(|(_, sub)| !sub.is_empty())
     {
         word = match eval_suspendable(sentence.to_vec(), ctx)
+            .map_err(|mut e| {
+                if let Some(site) = e.downcast_mut::<ErrorSite>() {
+                    site.at_line(rel_pos);
+                }
+                e
+            })
             .with_context(|| anyhow!("evaluating line {} *of block*: {sentence:?}", rel_pos + 1))?
         {
             EvalOutput::Regular(word) => word,
+            EvalOutput::Return(Word::Nothing) => {
+                return Ok(BlockEvalResult::Return(word));
+            }
             EvalOutput::Return(word) => {
                 return Ok(BlockEvalResult::Return(word));
             }
+            EvalOutput::Break => return Ok(BlockEvalResult::Break),
+            EvalOutput::Continue => return Ok(BlockEvalResult::Continue),
             EvalOutput::InDefinition | EvalOutput::Suspension => {
                 return Err(JError::StackSuspension)
                     .context("suspended in a context which doesn't support suspension")
@@ -130,6 +195,16 @@ pub fn eval_lines(sentence: &[Word], ctx: &mut Ctx) -> Result<BlockEvalResult> {
     Ok(BlockEvalResult::Regular(word))
 }
 
+/// runs the body of an explicit definition, noting in any error that it escaped the definition
+pub fn eval_definition(def: &[Word], ctx: &mut Ctx) -> Result<BlockEvalResult> {
+    eval_lines(def, ctx).map_err(|mut e| {
+        if let Some(site) = e.downcast_mut::<ErrorSite>() {
+            site.leave_definition();
+        }
+        e
+    })
+}
+
 pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput> {
     if sentence
         .iter()
@@ -163,7 +238,7 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
             .insert(2, Word::Noun(JArray::from_string(sus.data)));
         sus.qs
     } else {
-        let mut queue = VecDeque::from(sentence);
+        let mut queue = VecDeque::from(sentence.clone());
         queue.push_front(Word::StartOfLine);
         Qs {
             queue,
@@ -171,6 +246,75 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
         }
     };
 
+    // where each word on the stack came from, as indexes into the original sentence
+    let spans = if sentence.is_empty() {
+        // restored from suspension, where we've lost track
+        qs.stack.iter().map(|_| None).collect()
+    } else {
+        VecDeque::new()
+    };
+
+    let mut progress = Progress::default();
+    eval_qs(qs, spans, ctx, &mut progress).map_err(|err| progress.attach(err, &sentence))
+}
+
+type Span = Option<(usize, usize)>;
+
+/// the state of a sentence's evaluation, for reporting where an error happened
+#[derive(Default)]
+struct Progress {
+    /// the words of the verb (or similar) currently being executed
+    executing: Span,
+}
+
+impl Progress {
+    fn attach(self, mut err: anyhow::Error, sentence: &[Word]) -> anyhow::Error {
+        if let Some(site) = err.downcast_mut::<ErrorSite>() {
+            let name = match self.executing {
+                Some((start, end)) if end == start + 1 => match sentence.get(start) {
+                    Some(Name(n)) => Some(n.as_str()),
+                    _ => None,
+                },
+                _ => None,
+            };
+            site.called_as(name);
+            return err;
+        }
+        if sentence.is_empty() {
+            return err;
+        }
+        err.context(ErrorSite::new(
+            sentence.iter().map(site_word).collect(),
+            self.executing.map(|(start, _)| start).unwrap_or(0),
+        ))
+    }
+
+    /// the failure is blamed on the (rightmost) verb which has a noun to its right
+    fn executing(&mut self, fragment: [&Word; 4], spans: &[Span; 4]) {
+        let k = (1..=2)
+            .rev()
+            .find(|&k| matches!(fragment[k], Verb(_)) && matches!(fragment[k + 1], Noun(_)))
+            .unwrap_or(1);
+        self.executing = spans[k];
+    }
+}
+
+/// words as jconsole echoes them in errors
+fn site_word(w: &Word) -> String {
+    match w {
+        Noun(JArray::CharArray(arr, _)) if arr.shape().len() <= 1 => {
+            format!("'{}'", arr.iter().collect::<String>().replace('\'', "''"))
+        }
+        other => other.name(),
+    }
+}
+
+fn eval_qs(
+    qs: Qs,
+    mut spans: VecDeque<Span>,
+    ctx: &mut Ctx,
+    progress: &mut Progress,
+) -> Result<EvalOutput> {
     let mut stack = qs.stack;
     let mut queue = qs.queue;
     debug!("starting eval: {stack:?} {queue:?}");
@@ -183,31 +327,44 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
         trace!("stack step: {:?}", stack);
 
         let fragment = get_fragment(&mut stack);
+        let fspans: [Span; 4] = spans
+            .drain(..spans.len().min(4))
+            .chain(repeat(None))
+            .next_tuple::<(_, _, _, _)>()
+            .expect("infinite iterator can't be empty")
+            .into();
         trace!("fragment: {:?}", fragment);
         let fragment = resolve_names(fragment, ctx)?;
+        progress.executing(
+            [&fragment.0, &fragment.1, &fragment.2, &fragment.3],
+            &fspans,
+        );
+        // how many words at the start of the fragment are left untouched by its reduction
+        let mut kept = 1;
+        let mut moved = None;
 
         let result: Result<Vec<Word>> = match fragment {
             (IfBlock(def), b, c, d) => {
                 match control_if(ctx, &def)? {
                     BlockEvalResult::Regular(w) => Ok(vec![w, b, c, d]),
-                    BlockEvalResult::Return(w) => {
-                        // TODO: not clear that it's valid to early exit the evaluation here
-                        return Ok(EvalOutput::Return(w));
-                    }
+                    // TODO: not clear that it's valid to early exit the evaluation here
+                    other => return Ok(other.into()),
                 }
             }
-            (SelectBlock(_), _, _, _) => Err(JError::NonceError).context("select block"),
+            (SelectBlock(def), b, c, d) => match control_select(ctx, &def)? {
+                BlockEvalResult::Regular(w) => Ok(vec![w, b, c, d]),
+                // TODO: not clear that it's valid to early exit the evaluation here
+                other => return Ok(other.into()),
+            },
             (TryBlock(def), b, c, d) => match control_try(ctx, &def)? {
-                BlockEvalResult::Regular(_) => Ok(vec![b, c, d]),
-                BlockEvalResult::Return(v) => {
-                    // TODO: not clear that it's valid to early exit the evaluation here
-                    return Ok(EvalOutput::Return(v));
-                }
+                BlockEvalResult::Regular(w) => Ok(vec![w, b, c, d]),
+                // TODO: not clear that it's valid to early exit the evaluation here
+                other => return Ok(other.into()),
             },
             (AssertLine(def), _b, _c, _d) => {
                 let word = eval_lines(&def, ctx).context("assert body")?;
                 // Signals assertion failure if followed by a noun which is neither Boolean (1) nor an array: (1 1 1 …) of all 1's.
-                match word.into_word() {
+                match word.into_word()? {
                     Word::Noun(JArray::BoolArray(b)) => {
                         if j_monad_eval("(*./@:(1&=)@:,)", &JArray::BoolArray(b)).unwrap()
                             == JArray::from(arr0ad(1u8))
@@ -225,17 +382,25 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
             }
             (ForBlock(style, def), b, c, d) => {
                 match control_for(ctx, style.as_ref().map(String::as_str), &def)? {
-                    BlockEvalResult::Regular(_) => Ok(vec![b, c, d]),
-                    BlockEvalResult::Return(v) => {
-                        // TODO: not clear that it's valid to early exit the evaluation here
-                        return Ok(EvalOutput::Return(v));
-                    }
+                    BlockEvalResult::Regular(w) => Ok(vec![w, b, c, d]),
+                    // TODO: not clear that it's valid to early exit the evaluation here
+                    other => return Ok(other.into()),
                 }
             }
-            (WhileBlock(_, _), _, _, _) => Err(JError::NonceError).context("while block"),
-            (Throw, _, _, _) => Err(JError::NonceError).context("throw"),
+            (WhileBlock(check_last, def), b, c, d) => {
+                match control_while(ctx, check_last, &def)? {
+                    BlockEvalResult::Regular(w) => Ok(vec![w, b, c, d]),
+                    // TODO: not clear that it's valid to early exit the evaluation here
+                    other => return Ok(other.into()),
+                }
+            }
+            (Break, _, _, _) => return Ok(EvalOutput::Break),
+            (Continue, _, _, _) => return Ok(EvalOutput::Continue),
+            // unwinds to the nearest catcht., see control_try
+            (Throw, _, _, _) => Err(JError::UncaughtThrow).context("throw."),
             (Return, a, b, c) => {
                 wants_to_return = true;
+                kept = 0;
                 Ok(vec![a, b, c])
             }
             (ref w, Verb(v), Noun(y), any)
@@ -244,7 +409,7 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 debug!("0 monad");
                 Ok(vec![
                     fragment.0,
-                    v.exec(ctx, None, &y).map(Word::Noun)?,
+                    v.exec_phrase(ctx, None, &y).map(Word::Noun)?,
                     any,
                 ])
             }
@@ -255,10 +420,11 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 ) =>
             {
                 debug!("1 monad");
+                kept = 2;
                 Ok(vec![
                     fragment.0,
                     Verb(u.clone()),
-                    v.exec(ctx, None, &y).map(Word::Noun)?,
+                    v.exec_phrase(ctx, None, &y).map(Word::Noun)?,
                 ])
             }
             (ref w, Noun(x), Verb(ref v), Noun(y))
@@ -270,7 +436,7 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 debug!("2 dyad");
                 Ok(vec![
                     fragment.0,
-                    v.exec(ctx, Some(&x), &y)
+                    v.exec_phrase(ctx, Some(&x), &y)
                         .context("evaluating 2 dyad")
                         .map(Word::Noun)?,
                 ])
@@ -415,6 +581,7 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 if matches!(w, Conjunction(_) | Adverb(_) | Verb(_) | Noun(_) | Name(_)) =>
             {
                 debug!("7 Is Local Name w");
+                kept = 0;
                 ctx.eval_mut().locales.assign_local(n, w.clone())?;
                 Ok(vec![w.clone(), any])
             }
@@ -422,9 +589,10 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 if matches!(w, Conjunction(_) | Adverb(_) | Verb(_) | Noun(_) | Name(_)) =>
             {
                 debug!("7 Is Local Noun w");
+                kept = 0;
                 let (arr, names) = string_assignment(names, w)?;
 
-                for (name, val) in names.into_iter().zip(arr.outer_iter()) {
+                for (name, val) in names.into_iter().zip(arr.outer_iter()?) {
                     ctx.eval_mut()
                         .locales
                         .assign_local(name, Noun(v_open(&val)?))?;
@@ -435,6 +603,7 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 if matches!(w, Conjunction(_) | Adverb(_) | Verb(_) | Noun(_) | Name(_)) =>
             {
                 debug!("7 Is Global Name w");
+                kept = 0;
                 ctx.eval_mut().locales.assign_global(n, w.clone())?;
                 Ok(vec![w, any])
             }
@@ -442,9 +611,10 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
                 if matches!(w, Conjunction(_) | Adverb(_) | Verb(_) | Noun(_) | Name(_)) =>
             {
                 debug!("7 Is Global Noun w");
+                kept = 0;
                 let (arr, names) = string_assignment(names, w)?;
 
-                for (name, val) in names.into_iter().zip(arr.outer_iter()) {
+                for (name, val) in names.into_iter().zip(arr.outer_iter()?) {
                     ctx.eval_mut()
                         .locales
                         .assign_global(name, Noun(v_open(&val)?))?;
@@ -454,10 +624,14 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
             //// LP (C|A|V|N) RP anything - 8 Paren
             (LP, w, RP, any) if matches!(w, Conjunction(_) | Adverb(_) | Verb(_) | Noun(_)) => {
                 debug!("8 Paren");
+                kept = 0;
                 Ok(vec![w.clone(), any])
             }
             (w1, w2, w3, w4) => match queue.pop_back() {
-                Some(v) => Ok(vec![v, w1, w2, w3, w4]),
+                Some(v) => {
+                    moved = Some(queue.len());
+                    Ok(vec![v, w1, w2, w3, w4])
+                }
                 None => {
                     converged = true;
                     Ok(vec![w1, w2, w3, w4])
@@ -465,12 +639,15 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
             },
         };
 
-        stack.retain(|w| !matches!(w, Nothing));
-
         debug!("result: {:?} with {stack:?}", result);
-        stack = vec![result?, stack.into()].concat().into(); //push_front
-
-        stack.retain(|w| !matches!(w, Nothing));
+        let result = result?;
+        let rspans = result_spans(&fspans, result.len(), kept, moved);
+        for (w, span) in result.into_iter().zip(rspans).rev() {
+            if !matches!(w, Nothing) {
+                stack.push_front(w);
+                spans.push_front(span);
+            }
+        }
     }
     trace!("DEBUG stack: {:?}", stack);
     let mut new_stack: VecDeque<Word> = stack
@@ -481,6 +658,10 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
         .into();
     debug!("finish eval: {:?}", new_stack);
     if new_stack.is_empty() {
+        if wants_to_return {
+            // a bare `return.`, the enclosing block knows what the result was
+            return Ok(EvalOutput::Return(Word::Nothing));
+        }
         return Ok(EvalOutput::Regular(Word::Nothing));
     }
     match new_stack.pop_front() {
@@ -496,7 +677,7 @@ pub fn eval_suspendable(sentence: Vec<Word>, ctx: &mut Ctx) -> Result<EvalOutput
 
 fn string_assignment(names: JArray, w: Word) -> Result<(JArray, Vec<String>)> {
     let Noun(arr) = w else { return Err(JError::NonceError).context("non-noun on the right of noun assignment"); };
-    let JArray::CharArray(names) = names else { return Err(JError::NonceError).context("assigning to char arrays only please"); };
+    let JArray::CharArray(names, _) = names else { return Err(JError::NonceError).context("assigning to char arrays only please"); };
     if names.shape().len() != 1 {
         return Err(JError::NonceError)
             .context("lists of chars (strings), not multi-dimensional char arrays");
@@ -519,6 +700,28 @@ fn string_assignment(names: JArray, w: Word) -> Result<(JArray, Vec<String>)> {
     Ok((arr, names))
 }
 
+/// the spans for the words of a reduction's result: `kept` words from the start of the fragment,
+/// then the word they were reduced to, then the rest of the fragment
+fn result_spans(fspans: &[Span; 4], len: usize, kept: usize, moved: Option<usize>) -> Vec<Span> {
+    if let Some(pos) = moved {
+        // the queue starts with StartOfLine, which isn't in the sentence
+        let mut spans = vec![Some((pos.saturating_sub(1), pos))];
+        spans.extend_from_slice(fspans);
+        return spans;
+    }
+    let rest = len.saturating_sub(kept + 1).min(4 - kept);
+    let merged = fspans[kept..4 - rest]
+        .iter()
+        .flatten()
+        .copied()
+        .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)));
+    let mut spans = fspans[..kept].to_vec();
+    spans.push(merged);
+    spans.extend_from_slice(&fspans[4 - rest..]);
+    spans.truncate(len);
+    spans
+}
+
 fn get_fragment(stack: &mut VecDeque<Word>) -> (Word, Word, Word, Word) {
     stack
         .drain(..stack.len().min(4))
@@ -540,10 +743,15 @@ pub fn resolve_names(
 
     // Resolve Names only on the RHS of IsLocal/IsGlobal
     let mut resolved_words = Vec::new();
-    for w in words.iter().rev() {
+    let rights = once(None).chain(words.iter().rev().map(Some));
+    for (w, right) in words.iter().rev().zip(rights) {
         match w {
             IsGlobal => break,
             IsLocal => break,
+            // f. spots recursion back to the name it's fixing
+            Name(_) if matches!(right, Some(Adverb(a)) if a.name() == "f.") => {
+                resolved_words.push(w.clone())
+            }
             Name(ref n) => resolved_words.push(ctx.eval().locales.lookup(n)?.unwrap_or(w).clone()),
             _ => resolved_words.push(w.clone()),
         }
@@ -0,0 +1,421 @@
+//! jsoftware's binary representation of nouns, as produced by `3!:1` and `3!:3`
+//! https://www.jsoftware.com/help/dictionary/dx003.htm
+//!
+//! Every noun is a block: a header word (whose first byte is the layout flag), the type, the
+//! number of atoms, the rank, the shape, then the data, padded to a whole word. Boxes, extended
+//! integers and rationals store byte offsets to other blocks, relative to the start of their own.
+
+use anyhow::{anyhow, Context, Result};
+use ndarray::IxDyn;
+use num::complex::Complex64;
+use num::{BigInt, BigRational, Integer, Signed, ToPrimitive, Zero};
+
+use crate::arrays::{ArcArrayD, BoxArray, CharWidth};
+use crate::{JArray, JError};
+
+/// extended integers are stored as lists of digits in this base, least significant first
+const XBASE: i64 = 10_000;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Layout {
+    pub wide: bool,
+    pub little: bool,
+}
+
+impl Layout {
+    pub const NATIVE: Layout = Layout {
+        wide: cfg!(target_pointer_width = "64"),
+        little: cfg!(target_endian = "little"),
+    };
+
+    /// the `x` of `x 3!:1 y`; the `1x` modes are the same formats
+    pub fn from_mode(mode: i64) -> Result<Layout> {
+        let (wide, little) = match mode % 10 {
+            0 => (false, false),
+            1 => (false, true),
+            2 => (true, false),
+            3 => (true, true),
+            _ => return Err(JError::DomainError).with_context(|| anyhow!("mode {mode}")),
+        };
+        if !matches!(mode / 10, 0 | 1) || mode < 0 {
+            return Err(JError::DomainError).with_context(|| anyhow!("mode {mode}"));
+        }
+        Ok(Layout { wide, little })
+    }
+
+    fn from_flag(flag: u8) -> Result<Layout> {
+        match flag {
+            0xe0..=0xe3 => Layout::from_mode(i64::from(flag - 0xe0)),
+            _ => Err(JError::DomainError).with_context(|| anyhow!("unrecognised header {flag:#x}")),
+        }
+    }
+
+    fn flag(&self) -> u8 {
+        0xe0 + u8::from(self.wide) * 2 + u8::from(self.little)
+    }
+
+    pub fn word(&self) -> usize {
+        if self.wide {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn put_int(&self, out: &mut Vec<u8>, v: i64) -> Result<()> {
+        if self.wide {
+            out.extend(if self.little {
+                v.to_le_bytes()
+            } else {
+                v.to_be_bytes()
+            });
+        } else {
+            let v = i32::try_from(v)
+                .map_err(|_| JError::LimitError)
+                .with_context(|| anyhow!("{v} doesn't fit in a 32-bit representation"))?;
+            out.extend(if self.little {
+                v.to_le_bytes()
+            } else {
+                v.to_be_bytes()
+            });
+        }
+        Ok(())
+    }
+
+    fn put_float(&self, out: &mut Vec<u8>, v: f64) {
+        out.extend(if self.little {
+            v.to_le_bytes()
+        } else {
+            v.to_be_bytes()
+        });
+    }
+
+    /// a unicode (`size` 2) or unicode4 (`size` 4) character
+    fn put_char(&self, out: &mut Vec<u8>, c: char, size: usize) {
+        let v = u32::from(c);
+        if self.little {
+            out.extend(&v.to_le_bytes()[..size]);
+        } else {
+            out.extend(&v.to_be_bytes()[4 - size..]);
+        }
+    }
+
+    fn get_char(&self, b: &[u8]) -> Result<char> {
+        let mut v = [0u8; 4];
+        let v = if self.little {
+            v[..b.len()].copy_from_slice(b);
+            u32::from_le_bytes(v)
+        } else {
+            v[4 - b.len()..].copy_from_slice(b);
+            u32::from_be_bytes(v)
+        };
+        char::from_u32(v)
+            .ok_or(JError::DomainError)
+            .with_context(|| anyhow!("{v:#x} isn't a character"))
+    }
+
+    fn get_int(&self, bytes: &[u8], at: usize) -> Result<i64> {
+        let b = bytes
+            .get(at..at + self.word())
+            .ok_or(JError::DomainError)
+            .context("truncated binary representation")?;
+        Ok(match (self.wide, self.little) {
+            (true, true) => i64::from_le_bytes(b.try_into().expect("sized")),
+            (true, false) => i64::from_be_bytes(b.try_into().expect("sized")),
+            (false, true) => i64::from(i32::from_le_bytes(b.try_into().expect("sized"))),
+            (false, false) => i64::from(i32::from_be_bytes(b.try_into().expect("sized"))),
+        })
+    }
+
+    fn get_usize(&self, bytes: &[u8], at: usize) -> Result<usize> {
+        usize::try_from(self.get_int(bytes, at)?)
+            .map_err(|_| JError::DomainError)
+            .context("negative size in binary representation")
+    }
+
+    fn get_float(&self, bytes: &[u8], at: usize) -> Result<f64> {
+        let b: [u8; 8] = bytes
+            .get(at..at + 8)
+            .ok_or(JError::DomainError)
+            .context("truncated binary representation")?
+            .try_into()
+            .expect("sized");
+        Ok(if self.little {
+            f64::from_le_bytes(b)
+        } else {
+            f64::from_be_bytes(b)
+        })
+    }
+}
+
+pub fn encode(arr: &JArray, layout: Layout) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    encode_block(&mut out, arr, layout)?;
+    Ok(out)
+}
+
+fn encode_block(out: &mut Vec<u8>, arr: &JArray, layout: Layout) -> Result<()> {
+    let start = out.len();
+    let word = layout.word();
+    out.push(layout.flag());
+    out.resize(start + word, 0);
+    let width = match arr {
+        JArray::CharArray(_, w) => Some(*w),
+        _ => None,
+    };
+    let kind = match arr {
+        JArray::BoolArray(_) => 1,
+        JArray::CharArray(..) => width.expect("chars").type_code(),
+        JArray::IntArray(_) => 4,
+        JArray::FloatArray(_) => 8,
+        JArray::ComplexArray(_) => 16,
+        JArray::BoxArray(_) => 32,
+        JArray::ExtIntArray(_) => 64,
+        JArray::RationalArray(_) => 128,
+        JArray::SymbolArray(_) => {
+            return Err(JError::NonceError).context("symbols only have meaning in this session")
+        }
+        JArray::SparseArray(_) => return Err(JError::NonceError).context("sparse arrays"),
+    };
+    layout.put_int(out, kind)?;
+    layout.put_int(out, i64::try_from(arr.shape().iter().product::<usize>())?)?;
+    layout.put_int(out, i64::try_from(arr.shape().len())?)?;
+    for &dim in arr.shape() {
+        layout.put_int(out, i64::try_from(dim)?)?;
+    }
+
+    let data = out.len();
+    // sub-blocks, for the things which are stored by reference
+    let subs: Vec<JArray> = match arr {
+        JArray::BoolArray(a) => {
+            out.extend(a.iter());
+            vec![]
+        }
+        JArray::CharArray(a, _) => {
+            match width.expect("chars") {
+                CharWidth::Literal => out.extend(a.iter().map(|&c| u32::from(c) as u8)),
+                CharWidth::Unicode => a.iter().for_each(|&c| layout.put_char(out, c, 2)),
+                CharWidth::Unicode4 => a.iter().for_each(|&c| layout.put_char(out, c, 4)),
+            }
+            vec![]
+        }
+        JArray::IntArray(a) => {
+            for &v in a.iter() {
+                layout.put_int(out, v)?;
+            }
+            vec![]
+        }
+        JArray::FloatArray(a) => {
+            a.iter().for_each(|&v| layout.put_float(out, v));
+            vec![]
+        }
+        JArray::ComplexArray(a) => {
+            for v in a.iter() {
+                layout.put_float(out, v.re);
+                layout.put_float(out, v.im);
+            }
+            vec![]
+        }
+        JArray::BoxArray(a) => a.iter().cloned().collect(),
+        JArray::SymbolArray(_) | JArray::SparseArray(_) => unreachable!("rejected above"),
+        JArray::ExtIntArray(a) => a.iter().map(digits).collect(),
+        JArray::RationalArray(a) => a
+            .iter()
+            .flat_map(|r| [digits(r.numer()), digits(r.denom())])
+            .collect(),
+    };
+    let bytes = matches!(arr, JArray::BoolArray(_)) || width == Some(CharWidth::Literal);
+    if bytes && out.len() > data {
+        // non-empty bytes get a terminator, even when that takes a whole extra word
+        out.push(0);
+    }
+    pad(out, data, word);
+
+    if subs.is_empty() {
+        return Ok(());
+    }
+    let slots = out.len();
+    out.resize(slots + subs.len() * word, 0);
+    for (i, sub) in subs.iter().enumerate() {
+        let offset = i64::try_from(out.len() - start)?;
+        let mut slot = Vec::with_capacity(word);
+        layout.put_int(&mut slot, offset)?;
+        out[slots + i * word..slots + (i + 1) * word].copy_from_slice(&slot);
+        encode_block(out, sub, layout)?;
+    }
+    Ok(())
+}
+
+fn pad(out: &mut Vec<u8>, from: usize, word: usize) {
+    let len = out.len() - from;
+    out.resize(from + len.div_ceil(word) * word, 0);
+}
+
+fn digits(v: &BigInt) -> JArray {
+    let negative = v.is_negative();
+    let mut v = v.abs();
+    let mut digits = Vec::new();
+    let base = BigInt::from(XBASE);
+    loop {
+        let (rest, digit) = v.div_rem(&base);
+        let digit = digit.to_i64().expect("less than the base");
+        digits.push(if negative { -digit } else { digit });
+        v = rest;
+        if v.is_zero() {
+            break;
+        }
+    }
+    JArray::from_list(digits)
+}
+
+pub fn decode(bytes: &[u8]) -> Result<JArray> {
+    let flag = *bytes
+        .first()
+        .ok_or(JError::DomainError)
+        .context("empty binary representation")?;
+    decode_block(bytes, 0, Layout::from_flag(flag)?)
+}
+
+fn decode_block(bytes: &[u8], start: usize, layout: Layout) -> Result<JArray> {
+    let word = layout.word();
+    if bytes.get(start).copied() != Some(layout.flag()) {
+        return Err(JError::DomainError).with_context(|| anyhow!("no block header at {start}"));
+    }
+    let kind = layout.get_int(bytes, start + word)?;
+    let elements = layout.get_usize(bytes, start + 2 * word)?;
+    let rank = layout.get_usize(bytes, start + 3 * word)?;
+    let shape = (0..rank)
+        .map(|i| layout.get_usize(bytes, start + (4 + i) * word))
+        .collect::<Result<Vec<_>>>()?;
+    if shape.iter().product::<usize>() != elements {
+        return Err(JError::DomainError).context("shape doesn't match the atom count");
+    }
+    let data = start + (4 + rank) * word;
+    let shape = IxDyn(&shape);
+
+    let raw = |len: usize| {
+        bytes
+            .get(data..data + len)
+            .ok_or(JError::DomainError)
+            .context("truncated binary representation")
+    };
+    let ints = |len: usize| {
+        (0..len)
+            .map(|i| layout.get_int(bytes, data + i * word))
+            .collect::<Result<Vec<_>>>()
+    };
+    let floats = |len: usize| {
+        (0..len)
+            .map(|i| layout.get_float(bytes, data + i * 8))
+            .collect::<Result<Vec<_>>>()
+    };
+    let subs = |len: usize| {
+        ints(len)?
+            .into_iter()
+            .map(|off| {
+                let off = usize::try_from(off)
+                    .ok()
+                    .filter(|&off| off > 0)
+                    .ok_or(JError::DomainError)
+                    .context("invalid offset")?;
+                decode_block(bytes, start + off, layout)
+            })
+            .collect::<Result<Vec<_>>>()
+    };
+
+    Ok(match kind {
+        1 => JArray::BoolArray(ArcArrayD::from_shape_vec(shape, raw(elements)?.to_vec())?),
+        2 => JArray::CharArray(
+            ArcArrayD::from_shape_vec(
+                shape,
+                raw(elements)?.iter().map(|&c| char::from(c)).collect(),
+            )?,
+            CharWidth::Literal,
+        ),
+        131072 | 262144 => {
+            let (size, width) = match kind {
+                131072 => (2, CharWidth::Unicode),
+                _ => (4, CharWidth::Unicode4),
+            };
+            JArray::CharArray(
+                ArcArrayD::from_shape_vec(
+                    shape,
+                    raw(elements * size)?
+                        .chunks(size)
+                        .map(|b| layout.get_char(b))
+                        .collect::<Result<_>>()?,
+                )?,
+                width,
+            )
+        }
+        4 => JArray::IntArray(ArcArrayD::from_shape_vec(shape, ints(elements)?)?),
+        8 => JArray::FloatArray(ArcArrayD::from_shape_vec(shape, floats(elements)?)?),
+        16 => JArray::ComplexArray(ArcArrayD::from_shape_vec(
+            shape,
+            floats(elements * 2)?
+                .chunks(2)
+                .map(|c| Complex64::new(c[0], c[1]))
+                .collect(),
+        )?),
+        32 => JArray::BoxArray(BoxArray::from_shape_vec(shape, subs(elements)?)?),
+        64 => JArray::ExtIntArray(ArcArrayD::from_shape_vec(
+            shape,
+            subs(elements)?
+                .iter()
+                .map(undigits)
+                .collect::<Result<_>>()?,
+        )?),
+        128 => JArray::RationalArray(ArcArrayD::from_shape_vec(
+            shape,
+            subs(elements * 2)?
+                .chunks(2)
+                .map(|c| {
+                    let denom = undigits(&c[1])?;
+                    if denom.is_zero() {
+                        return Err(JError::DomainError).context("zero denominator");
+                    }
+                    Ok(BigRational::new(undigits(&c[0])?, denom))
+                })
+                .collect::<Result<_>>()?,
+        )?),
+        1024 | 2048 | 4096 | 8192 | 16384 | 32768 => {
+            return Err(JError::NonceError).context("sparse arrays")
+        }
+        65536 => return Err(JError::NonceError).context("symbols only have meaning in a session"),
+        _ => return Err(JError::DomainError).with_context(|| anyhow!("unknown type {kind}")),
+    })
+}
+
+fn undigits(arr: &JArray) -> Result<BigInt> {
+    let JArray::IntArray(arr) = arr else {
+        return Err(JError::DomainError).context("extended digits must be integers");
+    };
+    let digits = arr.iter().copied().collect::<Vec<_>>();
+    Ok(digits
+        .into_iter()
+        .rev()
+        .fold(BigInt::zero(), |acc, d| acc * XBASE + d))
+}
+
+/// one row of hex per word, in memory order, as `3!:3` displays it
+pub fn to_hex(bytes: &[u8], layout: Layout) -> Vec<String> {
+    bytes
+        .chunks(layout.word())
+        .map(|w| w.iter().map(|b| format!("{b:02x}")).collect())
+        .collect()
+}
+
+pub fn from_hex(rows: &str) -> Result<Vec<u8>> {
+    let hex = rows
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+    hex.chunks(2)
+        .map(|pair| {
+            let pair = pair.iter().collect::<String>();
+            u8::from_str_radix(&pair, 16)
+                .map_err(|_| JError::DomainError)
+                .with_context(|| anyhow!("invalid hex: {pair:?}"))
+        })
+        .collect()
+}
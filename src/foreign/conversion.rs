@@ -1,5 +1,6 @@
 use anyhow::{Context, Result};
 
+use crate::foreign::binary::{self, Layout};
 use crate::{arr0d, JArray, JError, Num};
 
 pub fn f_type(y: &JArray) -> Result<JArray> {
@@ -11,59 +12,57 @@ pub fn f_type(y: &JArray) -> Result<JArray> {
         RationalArray(_) => Ok(JArray::from(arr0d(128i64))),
         FloatArray(_) => Ok(JArray::from(arr0d(8i64))),
         ComplexArray(_) => Ok(JArray::from(arr0d(16i64))),
-        CharArray(_) => Ok(JArray::from(arr0d(2i64))),
+        CharArray(_, w) => Ok(JArray::from(arr0d(w.type_code()))),
         BoxArray(_) => Ok(JArray::from(arr0d(32i64))),
-        // UnicodeCharArray(_) => Ok(JArray::from(arr0d(131072i64))),
-        // Unicode4CharArray(_) => Ok(JArray::from(arr0d(262144i64))),
-        // SymbolArray(_) => Ok(JArray::from(arr0d(65536i64))),
-        // SparseBoolArray(_) => Ok(JArray::from(arr0d(1024i64))),
-        // SparseCharArray(_) => Ok(JArray::from(arr0d(2048i64))),
-        // SparseIntArray(_) => Ok(JArray::from(arr0d(4096i64))),
-        // SparseFloatArray(_) => Ok(JArray::from(arr0d(8192i64))),
-        // SparseComplexArray(_) => Ok(JArray::from(arr0d(16384i64))),
-        // SparseBoxArray(_) => Ok(JArray::from(arr0d(32768i64))),
+        SymbolArray(_) => Ok(JArray::from(arr0d(65536i64))),
+        // only literal sparse arrays, so no unicode ones
+        SparseArray(s) => match s.fill() {
+            CharArray(..) => Ok(JArray::from(arr0d(2048i64))),
+            fill => Ok(JArray::from(arr0d(1024 * f_type(fill)?.approx_i64_one()?))),
+        },
     }
 }
 
-pub fn f_dump_hex(x: Option<&JArray>, y: &JArray) -> Result<JArray> {
-    if cfg!(not(target_pointer_width = "64")) {
-        return Err(JError::NonceError).context("only support 64-bit (laziness)");
-    }
-
-    if cfg!(not(target_endian = "little")) {
-        return Err(JError::NonceError).context("haha, very funny");
-    }
-
+fn layout(x: Option<&JArray>) -> Result<Layout> {
     match x {
-        Some(x) => match x.single_math_num() {
-            Some(x) if x == Num::Int(3) || x == Num::Int(11) => (),
-            _ => return Err(JError::NonceError).context("unsupported mode"),
-        },
-        None => (),
+        Some(x) => Layout::from_mode(x.approx_i64_one().context("binary representation mode")?),
+        None => Ok(Layout::NATIVE),
     }
+}
 
-    let mut result = Vec::with_capacity(8);
-    result.push(0xe3); // 64-bit, reversed
-
-    match y {
-        JArray::IntArray(arr) => {
-            result.push(4);
-            // note: not JArray.len()
-            result.push(i64::try_from(arr.len())?);
-            result.push(i64::try_from(arr.shape().len())?);
-            for shape in arr.shape() {
-                result.push(i64::try_from(*shape)?);
-            }
-            result.extend(arr.iter().copied());
-        }
+// 3!:1
+pub fn f_binary_rep(x: Option<&JArray>, y: &JArray) -> Result<JArray> {
+    let bytes = binary::encode(y, layout(x)?)?;
+    Ok(JArray::from_list(
+        bytes.into_iter().map(char::from).collect::<Vec<_>>(),
+    ))
+}
 
-        _ => return Err(JError::NonceError).context("only int arrays (don't ask)"),
-    }
+// 3!:2, accepting either 3!:1 or 3!:3's output
+pub fn f_from_binary_rep(y: &JArray) -> Result<JArray> {
+    let JArray::CharArray(arr, _) = y else {
+        return Err(JError::DomainError).context("binary representation must be literal");
+    };
+    let bytes = if arr.shape().len() == 2 {
+        binary::from_hex(&arr.iter().collect::<String>())?
+    } else {
+        arr.iter()
+            .map(|&c| u8::try_from(c))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| JError::DomainError)
+            .context("binary representation must be bytes")?
+    };
+    binary::decode(&bytes)
+}
 
+// 3!:3
+pub fn f_dump_hex(x: Option<&JArray>, y: &JArray) -> Result<JArray> {
+    let layout = layout(x)?;
+    let bytes = binary::encode(y, layout)?;
     JArray::from_fill_promote(
-        result
+        binary::to_hex(&bytes, layout)
             .into_iter()
-            .map(|x| JArray::from_string(format!("{:016x}", x.to_be()))),
+            .map(JArray::from_string),
     )
 }
 
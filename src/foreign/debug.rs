@@ -0,0 +1,22 @@
+//! https://www.jsoftware.com/help/dictionary/dx013.htm
+
+use anyhow::Result;
+
+use crate::{arr0ad, Ctx, HasEmpty, JArray, JError};
+
+// 13!:11
+pub fn f_last_error_number(ctx: &Ctx) -> Result<JArray> {
+    let number = match &ctx.last_error {
+        Some(e) => JError::extract(e).map(JError::number).unwrap_or(20),
+        None => 0,
+    };
+    Ok(JArray::IntArray(arr0ad(number)))
+}
+
+// 13!:12
+pub fn f_last_error_message(ctx: &Ctx) -> Result<JArray> {
+    Ok(match &ctx.last_error {
+        Some(e) => JArray::from_string(JError::report(e)),
+        None => JArray::empty(),
+    })
+}
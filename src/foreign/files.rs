@@ -1,43 +1,343 @@
 //! https://www.jsoftware.com/help/dictionary/dx001.htm
 
 use std::fs;
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
 
 use anyhow::{anyhow, Context, Result};
+use ndarray::IxDyn;
 
-use crate::arrays::BoxArray;
-use crate::{arr0d, JArray, JError};
+use crate::arrays::{BoxArray, CharWidth};
+use crate::ctx::OpenFile;
+use crate::{arr0d, Ctx, HasEmpty, JArray, JError};
+
+/// the first number handed out by `1!:21`, as 0, 1 and 2 are the standard streams in jsoftware
+const FIRST_FILE_NUMBER: i64 = 3;
+
+// 1!:0
+pub fn f_list_dir(y: &JArray) -> Result<JArray> {
+    let pattern = match y {
+        JArray::BoxArray(y) => arg_to_string(y)?,
+        _ => y
+            .when_string()
+            .ok_or(JError::DomainError)
+            .context("directory pattern must be a string")?,
+    };
+    let (dir, pattern) = match pattern.rfind('/') {
+        Some(i) => (&pattern[..=i], &pattern[i + 1..]),
+        None => ("", pattern.as_str()),
+    };
+    let pattern = if pattern.is_empty() { "*" } else { pattern };
+
+    let entries = match fs::read_dir(if dir.is_empty() { "." } else { dir }) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return Err(JError::FileNameError)
+                .context(e)
+                .with_context(|| anyhow!("listing {dir:?}"))
+        }
+    };
+
+    let mut rows = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(|s| s.to_string()) else {
+            continue;
+        };
+        if !glob_matches(pattern, &name) {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        rows.push((name, meta));
+    }
+    rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    let mut table = Vec::with_capacity(rows.len() * 6);
+    for (name, meta) in &rows {
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        table.push(JArray::from_string(name));
+        table.push(JArray::from_list(timestamp(modified)));
+        table.push(JArray::from(arr0d(
+            i64::try_from(meta.len()).context("file length over 2^63")?,
+        )));
+        table.push(JArray::from_string(permissions(meta)));
+        let mut attributes = ['-'; 6];
+        if name.starts_with('.') {
+            attributes[1] = 'h';
+        }
+        if meta.is_dir() {
+            attributes[4] = 'd';
+        }
+        table.push(JArray::from_string(attributes.iter().collect::<String>()));
+        table.push(JArray::from_string(mode_string(meta)));
+    }
+
+    Ok(JArray::BoxArray(BoxArray::from_shape_vec(
+        IxDyn(&[rows.len(), 6]),
+        table,
+    )?))
+}
 
 // 1!:1
-pub fn f_read_file(y: &JArray) -> Result<JArray> {
-    let path = noun_to_fs_path(y)?;
+pub fn f_read_file(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    let (path, mut file) = match y {
+        JArray::BoxArray(_) => {
+            let path = noun_to_fs_path(y)?;
+            return match fs::read(&path) {
+                Ok(bytes) => Ok(bytes_to_literal(bytes)),
+                Err(e) => Err(JError::FileNameError)
+                    .context(e)
+                    .with_context(|| anyhow!("reading {path:?}")),
+            };
+        }
+        _ => {
+            let open = open_file(ctx, y)?;
+            (&open.path, &open.file)
+        }
+    };
 
-    match fs::read_to_string(&path) {
-        Ok(s) => Ok(JArray::from_string(s)),
-        Err(e) => Err(JError::FileNameError)
+    let mut bytes = Vec::new();
+    if let Err(e) = file
+        .seek(SeekFrom::Start(0))
+        .and_then(|_| file.read_to_end(&mut bytes))
+    {
+        return Err(JError::InterfaceError)
             .context(e)
-            .with_context(|| anyhow!("reading {path:?}")),
+            .with_context(|| anyhow!("reading {path:?}"));
     }
+    Ok(bytes_to_literal(bytes))
 }
 
-// 1!:4 (0)
-pub fn f_file_size(y: &JArray) -> Result<JArray> {
-    let path = noun_to_fs_path(y)?;
+/// a file's contents as a literal, one char per byte
+fn bytes_to_literal(bytes: Vec<u8>) -> JArray {
+    JArray::from_list(bytes.into_iter().map(char::from).collect::<Vec<_>>())
+}
 
-    let meta = match fs::metadata(&path) {
-        Ok(meta) => meta,
-        Err(err) => {
+// 1!:2
+pub fn f_write_file(x: &JArray, y: &JArray) -> Result<JArray> {
+    write_or_append(x, y, false)
+}
+
+// 1!:3
+pub fn f_append_file(x: &JArray, y: &JArray) -> Result<JArray> {
+    write_or_append(x, y, true)
+}
+
+fn write_or_append(x: &JArray, y: &JArray, append: bool) -> Result<JArray> {
+    if !matches!(y, JArray::BoxArray(_)) {
+        // the "file numbers" for the session: 2 is the screen, 4 is stderr, both take any noun
+        match y.approx_i64_one().context("file number")? {
+            2 => println!("{x}"),
+            4 => eprintln!("{x}"),
+            _ => return Err(JError::FileNumberError).context("only 2 and 4 are open"),
+        }
+        return Ok(JArray::empty());
+    }
+
+    let path = noun_to_new_fs_path(y)?;
+    let bytes = bytes_to_write(x)?;
+
+    let mut file = match fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(&path)
+    {
+        Ok(file) => file,
+        Err(e) => {
             return Err(JError::FileNameError)
-                .context(err)
-                .with_context(|| anyhow!("reading {path:?}"))
+                .context(e)
+                .with_context(|| anyhow!("opening {path:?}"))
         }
     };
+    if let Err(e) = file.write_all(&bytes) {
+        return Err(JError::InterfaceError)
+            .context(e)
+            .with_context(|| anyhow!("writing {path:?}"));
+    }
+    Ok(JArray::empty())
+}
+
+/// the bytes of a string: literals are bytes already, and unicode is written as UTF-8
+fn bytes_to_write(x: &JArray) -> Result<Vec<u8>> {
+    let text = x
+        .when_string()
+        .ok_or(JError::DomainError)
+        .context("can only write strings to files")?;
+    match x.char_width() {
+        Some(CharWidth::Literal) => text
+            .chars()
+            .map(|c| {
+                u8::try_from(c)
+                    .map_err(|_| JError::DomainError)
+                    .with_context(|| anyhow!("{c:?} isn't a byte"))
+            })
+            .collect(),
+        _ => Ok(text.into_bytes()),
+    }
+}
+
+// 1!:4 (0)
+pub fn f_file_size(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    let meta = match y {
+        JArray::BoxArray(_) => {
+            let path = noun_to_fs_path(y)?;
+            fs::metadata(&path).with_context(|| anyhow!("reading {path:?}"))
+        }
+        _ => {
+            let open = open_file(ctx, y)?;
+            open.file
+                .metadata()
+                .with_context(|| anyhow!("reading {:?}", open.path))
+        }
+    };
+    let meta = match meta {
+        Ok(meta) => meta,
+        Err(err) => return Err(JError::FileNameError).context(err),
+    };
 
     Ok(JArray::from(arr0d(
         i64::try_from(meta.len()).context("file length over 2^63")?,
     )))
 }
 
+// 1!:11
+pub fn f_read_indexed(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    let (file, range) = file_and_range(y)?;
+    let mut file = FileRef::new(ctx, &file)?;
+    let len = file.len()?;
+    let (index, count) = match range[..] {
+        [index] => (index, None),
+        [index, count] => (index, Some(count)),
+        _ => return Err(JError::LengthError).context("index, or index and length, please"),
+    };
+    let start = if index < 0 { index + len } else { index };
+    let end = count.map_or(len, |count| start.saturating_add(count));
+    if start < 0 || end < start || end > len {
+        return Err(JError::IndexError).context("reading outside the file");
+    }
+
+    let mut buf = vec![0u8; usize::try_from(end - start)?];
+    file.seek(start)?;
+    if let Err(e) = file.file().read_exact(&mut buf) {
+        return Err(JError::InterfaceError).context(e);
+    }
+    Ok(bytes_to_literal(buf))
+}
+
+// 1!:12
+pub fn f_write_indexed(ctx: &mut Ctx, x: &JArray, y: &JArray) -> Result<JArray> {
+    let bytes = bytes_to_write(x)?;
+    let (file, range) = file_and_range(y)?;
+    let [index] = range[..] else {
+        return Err(JError::LengthError).context("just an index, please");
+    };
+    let mut file = FileRef::new(ctx, &file)?;
+    let index = if index < 0 {
+        index + file.len()?
+    } else {
+        index
+    };
+    if index < 0 {
+        return Err(JError::IndexError).context("writing before the start of the file");
+    }
+
+    file.seek(index)?;
+    if let Err(e) = file.file().write_all(&bytes) {
+        return Err(JError::InterfaceError).context(e);
+    }
+    Ok(JArray::empty())
+}
+
+// 1!:20
+pub fn f_open_files(ctx: &Ctx) -> Result<JArray> {
+    let table = ctx
+        .files
+        .iter()
+        .flat_map(|(number, open)| {
+            [
+                JArray::from(arr0d(*number)),
+                JArray::from_string(open.path.display().to_string()),
+            ]
+        })
+        .collect::<Vec<_>>();
+    Ok(JArray::BoxArray(BoxArray::from_shape_vec(
+        IxDyn(&[ctx.files.len(), 2]),
+        table,
+    )?))
+}
+
+// 1!:21
+pub fn f_open_file(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    let path = noun_to_new_fs_path(y)?;
+    let file = match fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(&path)
+    {
+        Ok(file) => file,
+        Err(e) => {
+            return Err(JError::FileNameError)
+                .context(e)
+                .with_context(|| anyhow!("opening {path:?}"))
+        }
+    };
+
+    let number = (FIRST_FILE_NUMBER..)
+        .find(|n| !ctx.files.contains_key(n))
+        .expect("unbounded");
+    ctx.files.insert(number, OpenFile { path, file });
+    Ok(JArray::from(arr0d(number)))
+}
+
+// 1!:22
+pub fn f_close_file(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    let number = match y {
+        JArray::BoxArray(_) => {
+            let path = noun_to_new_fs_path(y)?;
+            ctx.files
+                .iter()
+                .find(|(_, open)| open.path == path)
+                .map(|(number, _)| *number)
+                .ok_or(JError::FileNameError)
+                .with_context(|| anyhow!("{path:?} isn't open"))?
+        }
+        _ => y.approx_i64_one().context("file number")?,
+    };
+    match ctx.files.remove(&number) {
+        Some(_) => Ok(JArray::empty()),
+        None => Err(JError::FileNumberError).with_context(|| anyhow!("file number {number}")),
+    }
+}
+
+// 1!:55
+pub fn f_erase_file(y: &JArray) -> Result<JArray> {
+    let JArray::BoxArray(y) = y else {
+        return Err(JError::DomainError).context("boxed file names please");
+    };
+    for path in arg_to_string_list(y)? {
+        let erased = match fs::metadata(&path) {
+            Ok(meta) if meta.is_dir() => fs::remove_dir(&path),
+            _ => fs::remove_file(&path),
+        };
+        if let Err(e) = erased {
+            return Err(JError::FileNameError)
+                .context(e)
+                .with_context(|| anyhow!("erasing {path:?}"));
+        }
+    }
+    Ok(JArray::empty())
+}
+
 // 1!:43 (_)
 pub fn f_file_cwd() -> Result<JArray> {
     Ok(JArray::from_string(
@@ -55,7 +355,7 @@ pub fn arg_to_string_list(y: &BoxArray) -> Result<Vec<String>> {
     }
 
     y.iter().map(|path| {
-        let JArray::CharArray(path) = path else { return Err(JError::NonceError).context("string required") };
+        let JArray::CharArray(path, _) = path else { return Err(JError::NonceError).context("string required") };
         if path.shape().len() > 1 {
             return Err(JError::NonceError).context("single string required");
         }
@@ -85,3 +385,162 @@ pub fn noun_to_fs_path(y: &JArray) -> Result<PathBuf> {
             .with_context(|| anyhow!("canonicalising {path:?}")),
     }
 }
+
+/// like `noun_to_fs_path`, but for files which may not exist yet
+pub fn noun_to_new_fs_path(y: &JArray) -> Result<PathBuf> {
+    let JArray::BoxArray(y) = y else {
+        return Err(JError::DomainError).context("boxed file name please");
+    };
+    let path = arg_to_string(y)?;
+    if path.is_empty() {
+        return Err(JError::InterfaceError).context("empty file name");
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// `*` and `?` only, which is all jsoftware's `1!:0` supports
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    fn go(pattern: &[char], name: &[char]) -> bool {
+        match (pattern.first(), name.first()) {
+            (None, None) => true,
+            (Some('*'), _) => {
+                go(&pattern[1..], name) || (!name.is_empty() && go(pattern, &name[1..]))
+            }
+            (Some('?'), Some(_)) => go(&pattern[1..], &name[1..]),
+            (Some(p), Some(n)) if p == n => go(&pattern[1..], &name[1..]),
+            _ => false,
+        }
+    }
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    go(&pattern, &name)
+}
+
+/// year month day hour minute second, in UTC, as we have no timezone database
+fn timestamp(secs: i64) -> Vec<i64> {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+
+    // Howard Hinnant's days_from_civil, backwards
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    vec![year, month, day, rem / 3600, rem / 60 % 60, rem % 60]
+}
+
+#[cfg(unix)]
+fn permissions(meta: &fs::Metadata) -> String {
+    mode_string(meta)[1..4].to_string()
+}
+
+#[cfg(not(unix))]
+fn permissions(meta: &fs::Metadata) -> String {
+    if meta.permissions().readonly() {
+        "r-x"
+    } else {
+        "rwx"
+    }
+    .to_string()
+}
+
+#[cfg(unix)]
+fn mode_string(meta: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    let kind = if meta.is_dir() { 'd' } else { '-' };
+    std::iter::once(kind)
+        .chain((0..9).rev().map(|bit| {
+            if mode & (1 << bit) == 0 {
+                '-'
+            } else {
+                ['x', 'w', 'r'][bit % 3]
+            }
+        }))
+        .collect()
+}
+
+#[cfg(not(unix))]
+fn mode_string(meta: &fs::Metadata) -> String {
+    format!(
+        "{}{}",
+        if meta.is_dir() { 'd' } else { '-' },
+        permissions(meta).repeat(3)
+    )
+}
+
+/// an open file number, or a file name, for the duration of one operation
+enum FileRef<'c> {
+    Number(&'c mut OpenFile),
+    Name(fs::File),
+}
+
+impl<'c> FileRef<'c> {
+    fn new(ctx: &'c mut Ctx, y: &JArray) -> Result<Self> {
+        Ok(match y {
+            JArray::BoxArray(_) | JArray::CharArray(..) => {
+                let path = match y.when_string() {
+                    Some(path) => PathBuf::from(path),
+                    None => noun_to_new_fs_path(y)?,
+                };
+                match fs::OpenOptions::new().read(true).write(true).open(&path) {
+                    Ok(file) => FileRef::Name(file),
+                    Err(e) => {
+                        return Err(JError::FileNameError)
+                            .context(e)
+                            .with_context(|| anyhow!("opening {path:?}"))
+                    }
+                }
+            }
+            _ => FileRef::Number(open_file(ctx, y)?),
+        })
+    }
+
+    fn file(&mut self) -> &mut fs::File {
+        match self {
+            FileRef::Number(open) => &mut open.file,
+            FileRef::Name(file) => file,
+        }
+    }
+
+    fn len(&mut self) -> Result<i64> {
+        match self.file().metadata() {
+            Ok(meta) => i64::try_from(meta.len()).context("file length over 2^63"),
+            Err(e) => Err(JError::InterfaceError).context(e),
+        }
+    }
+
+    fn seek(&mut self, pos: i64) -> Result<()> {
+        let pos = u64::try_from(pos).context("negative offset")?;
+        match self.file().seek(SeekFrom::Start(pos)) {
+            Ok(_) => Ok(()),
+            Err(e) => Err(JError::InterfaceError).context(e),
+        }
+    }
+}
+
+fn open_file<'c>(ctx: &'c mut Ctx, y: &JArray) -> Result<&'c mut OpenFile> {
+    let number = y.approx_i64_one().context("file number")?;
+    ctx.files
+        .get_mut(&number)
+        .ok_or(JError::FileNumberError)
+        .with_context(|| anyhow!("file number {number}"))
+}
+
+/// `file ; index [, length]`, where the file is a name or a number
+fn file_and_range(y: &JArray) -> Result<(JArray, Vec<i64>)> {
+    let JArray::BoxArray(y) = y else {
+        return Err(JError::DomainError).context("file ; index please");
+    };
+    if y.shape() != [2] {
+        return Err(JError::LengthError).context("file ; index please");
+    }
+    let range = y[1].approx_i64_list().context("file index")?;
+    Ok((y[0].clone(), range))
+}
@@ -1,6 +1,7 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
-use crate::{arr0ad, JArray};
+use crate::number::tolerance_of;
+use crate::{arr0ad, Ctx, HasEmpty, JArray};
 
 pub fn f_os_type() -> Result<JArray> {
     Ok(JArray::IntArray(arr0ad(match std::env::consts::OS {
@@ -14,3 +15,27 @@ pub fn f_is_secure() -> Result<JArray> {
     // it's rust, of course it's secure
     Ok(JArray::IntArray(arr0ad(1)))
 }
+
+// 9!:18
+pub fn f_tolerance(ctx: &Ctx) -> Result<JArray> {
+    Ok(JArray::FloatArray(arr0ad(ctx.tolerance)))
+}
+
+// 9!:19
+pub fn f_set_tolerance(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    ctx.tolerance = tolerance_of(y)?;
+    Ok(JArray::empty())
+}
+
+// 9!:98
+pub fn f_stack_limit(ctx: &Ctx) -> Result<JArray> {
+    Ok(JArray::IntArray(arr0ad(i64::try_from(ctx.stack_limit)?)))
+}
+
+// 9!:99
+pub fn f_set_stack_limit(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    ctx.stack_limit = y
+        .approx_usize_one()
+        .context("a stack limit is a count of verbs")?;
+    Ok(JArray::empty())
+}
@@ -1,9 +1,11 @@
 use anyhow::{ensure, Context, Result};
 
-use crate::{arr0d, JArray, JError};
+use crate::{arr0d, Ctx, JArray, JError};
 
 pub fn f_shell_out(y: &JArray) -> Result<JArray> {
-    let JArray::CharArray(y) = y else { return Err(JError::NonceError).context("string required") };
+    let JArray::CharArray(y, _) = y else {
+        return Err(JError::NonceError).context("string required");
+    };
     // TODO: new lines
     let y = y.iter().collect::<String>();
     let result = match y.as_str() {
@@ -16,7 +18,9 @@ pub fn f_shell_out(y: &JArray) -> Result<JArray> {
 
 pub fn f_getenv(y: &JArray) -> Result<JArray> {
     ensure!(y.shape().len() <= 1, "rank 0");
-    let JArray::CharArray(y) = y else { return Err(JError::NonceError).context("string required") };
+    let JArray::CharArray(y, _) = y else {
+        return Err(JError::NonceError).context("string required");
+    };
     let y = y.iter().collect::<String>();
     use std::env::VarError;
     match std::env::var(y) {
@@ -31,3 +35,23 @@ pub fn f_getenv(y: &JArray) -> Result<JArray> {
 pub fn f_getpid() -> Result<JArray> {
     Ok(JArray::from(arr0d(i64::from(std::process::id()))))
 }
+
+// 2!:4
+pub fn f_argv(ctx: &Ctx) -> Result<JArray> {
+    Ok(JArray::from_list(
+        ctx.argv.iter().map(JArray::from_string).collect::<Vec<_>>(),
+    ))
+}
+
+// 2!:55, the empty list meaning success, as in `exit ''`
+pub fn f_exit(y: &JArray) -> Result<JArray> {
+    let code = if y.is_empty() {
+        0
+    } else {
+        y.approx_i64_one().context("exit status")?
+    };
+    let code = i32::try_from(code)
+        .map_err(|_| JError::DomainError)
+        .context("exit status out of range")?;
+    Err(JError::Exit(code).into())
+}
@@ -1,9 +1,12 @@
+pub(crate) mod binary;
 mod conversion;
+mod debug;
 mod files;
 mod global_param;
 mod host;
 mod locales;
 mod names;
+mod representation;
 mod scripts;
 mod times;
 
@@ -13,14 +16,22 @@ use crate::{rank, JError};
 
 use crate::verbs::BivalentOwned;
 use conversion::*;
+use debug::*;
 use files::*;
 use global_param::*;
 use host::*;
 use locales::*;
 use names::*;
+pub(crate) use representation::f_define;
+use representation::*;
 use scripts::*;
 use times::*;
 
+/// whether `l!:r` is given sparse arguments as they are, rather than dense copies
+pub fn takes_sparse(l: i64, r: i64) -> bool {
+    matches!((l, r), (3, 0))
+}
+
 /// https://www.jsoftware.com/help/dictionary/xmain.htm
 pub fn foreign(l: i64, r: i64) -> Result<BivalentOwned> {
     let unsupported = |name: &'static str| -> Result<BivalentOwned> {
@@ -54,39 +65,43 @@ pub fn foreign(l: i64, r: i64) -> Result<BivalentOwned> {
             iii,
             BivalentOwned::from_monad(move |ctx, y| f_load_script(ctx, k, y)),
         ),
-        (1, 0) => unimplemented("list dir"),
-        (1, 1) => (zii, BivalentOwned::from_monad(|_, y| f_read_file(y))),
-        (1, 2) => unimplemented("write file"),
-        (1, 3) => unimplemented("append file"),
-        (1, 4) => (zii, BivalentOwned::from_monad(|_, y| f_file_size(y))),
+        (1, 0) => (iii, BivalentOwned::from_monad(|_, y| f_list_dir(y))),
+        (1, 1) => (zii, BivalentOwned::from_monad(f_read_file)),
+        (1, 2) => (iii, BivalentOwned::from_dyad(|_, x, y| f_write_file(x, y))),
+        (1, 3) => (iii, BivalentOwned::from_dyad(|_, x, y| f_append_file(x, y))),
+        (1, 4) => (zii, BivalentOwned::from_monad(f_file_size)),
         (1, 5) => security_violation("mkdir"),
         (1, 6) => unimplemented("file attributes"),
         (1, 7) => unimplemented("file permissions"),
-        (1, 11) => unimplemented("offset read"),
-        (1, 12) => unimplemented("offset write"),
-        (1, 20) => unimplemented("list file handles"),
-        (1, 21) => unimplemented("open file handle"),
-        (1, 22) => unimplemented("close file handle"),
+        (1, 11) => (iii, BivalentOwned::from_monad(f_read_indexed)),
+        (1, 12) => (iii, BivalentOwned::from_dyad(f_write_indexed)),
+        (1, 20) => (iii, BivalentOwned::from_monad(|ctx, _| f_open_files(ctx))),
+        (1, 21) => (zii, BivalentOwned::from_monad(f_open_file)),
+        (1, 22) => (zii, BivalentOwned::from_monad(f_close_file)),
         (1, 30) => unimplemented("list file locks"),
         (1, 31) => unimplemented("lock file region"),
         (1, 32) => unimplemented("unlock file region"),
         (1, 43) => (iii, BivalentOwned::from_monad(|_, _| f_file_cwd())),
         (1, 44) => unimplemented("change dir"),
         (1, 46) => unimplemented("path of j.dll"),
-        (1, 55) => unimplemented("erase directory"),
+        (1, 55) => (iii, BivalentOwned::from_monad(|_, y| f_erase_file(y))),
         (1, _) => return unsupported("file"),
         (2, 0) => (iii, BivalentOwned::from_monad(|_, y| f_shell_out(y))),
         (2, 1) => security_violation("shell out (forked)"),
         (2, 3) => unimplemented("terminate and wait"),
+        (2, 4) => (iii, BivalentOwned::from_monad(|ctx, _| f_argv(ctx))),
         (2, 5) => (zii, BivalentOwned::from_monad(|_, y| f_getenv(y))),
         (2, 6) => (iii, BivalentOwned::from_monad(|_, _| f_getpid())),
         (2, 7) => unimplemented("get cpu features"),
         (2, 8) => unimplemented("errno"),
-        (2, 55) => unimplemented("terminate session"),
+        (2, 55) => (iii, BivalentOwned::from_monad(|_, y| f_exit(y))),
         (2, _) => return unsupported("host"),
         (3, 0) => (iii, BivalentOwned::from_monad(|_, y| f_type(y))),
-        (3, 1) => unimplemented("byte representation"),
-        (3, 2) => unimplemented("byte to hex representation"),
+        (3, 1) => (
+            iii,
+            BivalentOwned::from_bivalent(|_ctx, x, y| f_binary_rep(x, y)),
+        ),
+        (3, 2) => (iii, BivalentOwned::from_monad(|_, y| f_from_binary_rep(y))),
         (3, 3) => (
             iii,
             BivalentOwned::from_bivalent(|_ctx, x, y| f_dump_hex(x, y)),
@@ -122,12 +137,18 @@ pub fn foreign(l: i64, r: i64) -> Result<BivalentOwned> {
             zii,
             BivalentOwned::from_monad(|ctx, y| f_name_erase(ctx, y)),
         ),
-        (5, 0) => unimplemented("create from ar"),
-        (5, 1) => unimplemented("create ar"),
-        (5, 2) => unimplemented("ar boxed"),
-        (5, 4) => unimplemented("ar tree"),
-        (5, 5) => unimplemented("ar linear"),
-        (5, 6) => unimplemented("ar linear (paren)"),
+        // 5!:0 is an adverb, made by c_foreign
+        (5, 1) => (
+            zii,
+            BivalentOwned::from_monad(|ctx, y| f_atomic_rep(ctx, y)),
+        ),
+        (5, 2) => (zii, BivalentOwned::from_monad(|ctx, y| f_boxed_rep(ctx, y))),
+        (5, 4) => (zii, BivalentOwned::from_monad(|ctx, y| f_tree_rep(ctx, y))),
+        (5, 5) => (
+            zii,
+            BivalentOwned::from_monad(|ctx, y| f_linear_rep(ctx, y)),
+        ),
+        (5, 6) => (zii, BivalentOwned::from_monad(|ctx, y| f_paren_rep(ctx, y))),
         (5, 7) => unimplemented("something to do with valences"),
         (5, _) => return unsupported("representation"),
         (6, 0) => unimplemented("time current"),
@@ -163,8 +184,20 @@ pub fn foreign(l: i64, r: i64) -> Result<BivalentOwned> {
         (8, 2) => unimplemented("format table"),
         (8, _) => return unsupported("format"),
         (9, 12) => (iii, BivalentOwned::from_monad(|_, _| f_os_type())),
+        (9, 18) => (iii, BivalentOwned::from_monad(|ctx, _| f_tolerance(ctx))),
+        (9, 19) => (iii, BivalentOwned::from_monad(f_set_tolerance)),
         (9, 24) => (iii, BivalentOwned::from_monad(|_, _| f_is_secure())),
+        (9, 98) => (iii, BivalentOwned::from_monad(|ctx, _| f_stack_limit(ctx))),
+        (9, 99) => (iii, BivalentOwned::from_monad(f_set_stack_limit)),
         (9, _) => return unsupported("global param"),
+        (13, 11) => (
+            iii,
+            BivalentOwned::from_monad(|ctx, _| f_last_error_number(ctx)),
+        ),
+        (13, 12) => (
+            iii,
+            BivalentOwned::from_monad(|ctx, _| f_last_error_message(ctx)),
+        ),
         (13, 0..=23) => unimplemented("debug system"),
         (13, _) => return unsupported("debug"),
         (15, 0) => unimplemented("call dll"),
@@ -0,0 +1,331 @@
+//! https://code.jsoftware.com/wiki/Vocabulary/Foreigns#m5
+//!
+//! Every representation but the atomic one is drawn from the same [`Rep`] of the named word.
+
+use std::iter;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+
+use crate::foreign::files::arg_to_string;
+use crate::verbs::{stringify, PartialDef, PartialImpl, VerbImpl};
+use crate::{arr0ad, Ctx, JArray, JError, Word};
+
+/// a word, as the representations see it
+enum Rep {
+    Primitive(String),
+    Noun(JArray),
+    /// a fork or a hook
+    Train(Vec<Rep>),
+    /// a modifier and its operands; adverbs only have the left one
+    Derived {
+        op: String,
+        left: Box<Rep>,
+        right: Option<Box<Rep>>,
+    },
+}
+
+impl Rep {
+    fn of(word: &Word) -> Rep {
+        match word {
+            Word::Noun(arr) => Rep::Noun(arr.clone()),
+            Word::Verb(v) => Rep::of_verb(v),
+            Word::Adverb(m) | Word::Conjunction(m) => Rep::Primitive(m.name()),
+            _ => Rep::Primitive(word.name()),
+        }
+    }
+
+    fn of_verb(v: &VerbImpl) -> Rep {
+        match v {
+            VerbImpl::Fork { f, g, h } => Rep::Train(vec![Rep::of(f), Rep::of(g), Rep::of(h)]),
+            VerbImpl::Hook { l, r } => Rep::Train(vec![Rep::of(l), Rep::of(r)]),
+            VerbImpl::Partial(PartialImpl { def, .. }) => match &**def {
+                PartialDef::Adverb(a, u) => Rep::Derived {
+                    op: a.name(),
+                    left: Box::new(Rep::of(&u.clone().into())),
+                    right: None,
+                },
+                PartialDef::Conjunction(u, c, v) => Rep::Derived {
+                    op: c.name(),
+                    left: Box::new(Rep::of(&u.clone().into())),
+                    right: Some(Box::new(Rep::of(&v.clone().into()))),
+                },
+                PartialDef::Cor(n, def) => Rep::Derived {
+                    op: ":".to_string(),
+                    left: Box::new(Rep::Noun(JArray::IntArray(arr0ad(*n)))),
+                    right: Some(Box::new(Rep::Noun(JArray::from_string(
+                        stringify(def).trim(),
+                    )))),
+                },
+                PartialDef::Primitive(name) => Rep::Primitive(name.to_string()),
+            },
+            VerbImpl::Primitive(p) => Rep::Primitive(p.name.to_string()),
+            VerbImpl::Number(_) | VerbImpl::Cap => Rep::Primitive(match v.boxed_ar() {
+                Ok(JArray::CharArray(name, _)) => name.iter().collect(),
+                _ => v.name(),
+            }),
+        }
+    }
+}
+
+fn named(ctx: &Ctx, y: &JArray) -> Result<Word> {
+    let JArray::BoxArray(y) = y else {
+        return Err(JError::DomainError).context("representations take a boxed name");
+    };
+    let name = arg_to_string(y)?;
+    ctx.eval()
+        .locales
+        .lookup(&name)?
+        .cloned()
+        .ok_or(JError::ValueError)
+        .with_context(|| anyhow!("{name:?} isn't defined"))
+}
+
+// 5!:0
+pub fn f_define(ctx: &mut Ctx, u: &Word) -> Result<Word> {
+    match u {
+        Word::Noun(JArray::BoxArray(ar)) if ar.shape().is_empty() => {
+            Word::from_boxed_ar(ctx, &ar[[]])
+        }
+        Word::Noun(ar) => Word::from_boxed_ar(ctx, ar),
+        _ => Err(JError::DomainError).with_context(|| anyhow!("5!:0 takes an ar, not {u:?}")),
+    }
+}
+
+// 5!:1
+pub fn f_atomic_rep(ctx: &Ctx, y: &JArray) -> Result<JArray> {
+    Ok(JArray::BoxArray(arr0ad(named(ctx, y)?.boxed_ar()?)))
+}
+
+// 5!:2
+pub fn f_boxed_rep(ctx: &Ctx, y: &JArray) -> Result<JArray> {
+    Ok(match boxed(&Rep::of(&named(ctx, y)?)) {
+        rep @ JArray::BoxArray(_) => rep,
+        leaf => JArray::BoxArray(arr0ad(leaf)),
+    })
+}
+
+fn boxed(rep: &Rep) -> JArray {
+    match rep {
+        Rep::Primitive(name) => JArray::from_string(name),
+        Rep::Noun(arr) => arr.clone(),
+        Rep::Train(tines) => JArray::from_list(tines.iter().map(boxed).collect_vec()),
+        Rep::Derived { op, left, right } => JArray::from_list(
+            iter::once(boxed(left))
+                .chain(iter::once(JArray::from_string(op)))
+                .chain(right.iter().map(|r| boxed(r)))
+                .collect_vec(),
+        ),
+    }
+}
+
+// 5!:4
+pub fn f_tree_rep(ctx: &Ctx, y: &JArray) -> Result<JArray> {
+    let rep = Rep::of(&named(ctx, y)?);
+    let (lines, anchor) = tree(&rep);
+    let lead = if matches!(rep, Rep::Train(_)) {
+        ""
+    } else {
+        " "
+    };
+    let lines = lines.iter().enumerate().map(|(k, line)| match k == anchor {
+        true => format!("──{lead}{line}"),
+        false => format!("  {lead}{line}"),
+    });
+    JArray::from_fill_promote(lines.map(JArray::from_string))
+}
+
+/// the lines of the tree, and the one its parent's branch joins
+fn tree(rep: &Rep) -> (Vec<String>, usize) {
+    match rep {
+        Rep::Primitive(name) => (vec![name.clone()], 0),
+        Rep::Noun(arr) => (vec![linear_noun(arr)], 0),
+        Rep::Train(tines) => branches(&tines.iter().collect_vec()),
+        Rep::Derived { op, left, right } => {
+            let operands = iter::once(&**left).chain(right.as_deref()).collect_vec();
+            let (lines, anchor) = branches(&operands);
+            let label = lines.iter().enumerate().map(|(k, line)| match k == anchor {
+                true => format!("{op} ─{line}"),
+                false => format!("{}  {line}", " ".repeat(op.chars().count())),
+            });
+            (label.collect(), anchor)
+        }
+    }
+}
+
+/// a column joining the children, and the children; the parent joins the middle one
+fn branches(children: &[&Rep]) -> (Vec<String>, usize) {
+    let mut blocks = Vec::new();
+    let mut anchors = Vec::new();
+    for child in children {
+        let (lines, anchor) = tree(child);
+        anchors.push(blocks.len() + anchor);
+        let lead = if matches!(child, Rep::Train(_)) {
+            ""
+        } else {
+            " "
+        };
+        blocks.extend(lines.into_iter().map(|line| format!("{lead}{line}")));
+    }
+    let mid = (children.len() - 1) / 2;
+    let (first, last) = (anchors[0], anchors[anchors.len() - 1]);
+    let lines = blocks.into_iter().enumerate().map(|(row, line)| {
+        let (column, branch) = match anchors.iter().position(|&a| a == row) {
+            Some(_) if children.len() == 1 => ('─', '─'),
+            Some(k) if k == mid && k == 0 => ('┬', '─'),
+            Some(k) if k == mid && k == children.len() - 1 => ('┴', '─'),
+            Some(k) if k == mid => ('┼', '─'),
+            Some(0) => ('┌', '─'),
+            Some(k) if k == children.len() - 1 => ('└', '─'),
+            Some(_) => ('├', '─'),
+            None if first < row && row < last => ('│', ' '),
+            None => (' ', ' '),
+        };
+        format!("{column}{branch}{line}")
+    });
+    let lines = lines.collect_vec();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let lines = lines
+        .into_iter()
+        .map(|l| {
+            let pad = width - l.chars().count();
+            l + &" ".repeat(pad)
+        })
+        .collect();
+    (lines, anchors[mid])
+}
+
+// 5!:5
+pub fn f_linear_rep(ctx: &Ctx, y: &JArray) -> Result<JArray> {
+    let rep = Rep::of(&named(ctx, y)?);
+    Ok(JArray::from_string(linear(&rep, Place::Top, false)))
+}
+
+// 5!:6
+pub fn f_paren_rep(ctx: &Ctx, y: &JArray) -> Result<JArray> {
+    let rep = Rep::of(&named(ctx, y)?);
+    Ok(JArray::from_string(linear(&rep, Place::Top, true)))
+}
+
+/// where a phrase is, which decides whether it needs parentheses
+#[derive(Copy, Clone, PartialEq)]
+enum Place {
+    Top,
+    /// in a train, or a modifier's left operand
+    Inner,
+    /// a modifier's right operand, which is only ever one word
+    Right,
+}
+
+fn linear(rep: &Rep, place: Place, parens: bool) -> String {
+    let wrap = |s: String, needed: bool| match needed {
+        true => format!("({s})"),
+        false => s,
+    };
+    match rep {
+        Rep::Primitive(name) => name.clone(),
+        Rep::Noun(arr) => wrap(linear_noun(arr), place != Place::Top && !simple_noun(arr)),
+        Rep::Train(tines) => {
+            let after_noun = |k: usize| k > 0 && matches!(tines[k - 1], Rep::Noun(_));
+            let mut tines = tines.iter().enumerate().map(|(k, t)| match after_noun(k) {
+                // `1 2 (3&+) ]` would strand the nouns together
+                true => linear(t, Place::Right, parens),
+                false => linear(t, Place::Inner, parens),
+            });
+            wrap(tines.join(" "), place != Place::Top)
+        }
+        Rep::Derived { op, left, right } => {
+            // a body of several lines can't be quoted, so it's a script, as it was typed
+            let script = match (op.as_str(), right.as_deref()) {
+                (":", Some(Rep::Noun(body))) => {
+                    body.when_string().filter(|body| body.trim().contains('\n'))
+                }
+                _ => None,
+            };
+            let words = match (op.as_str(), right.as_deref(), script) {
+                (_, _, Some(body)) => {
+                    let lines = body.lines().map(str::trim).filter(|l| !l.is_empty());
+                    iter::once(format!("{} : 0", linear(left, Place::Inner, parens)))
+                        .chain(lines.map(str::to_string))
+                        .chain(iter::once(")".to_string()))
+                        .join("\n")
+                }
+                (":", Some(right), None) => [
+                    linear(left, Place::Inner, parens),
+                    ":".to_string(),
+                    linear(right, Place::Right, parens),
+                ]
+                .join(" "),
+                _ => [
+                    Some(linear(left, Place::Inner, parens)),
+                    Some(op.clone()),
+                    right.as_ref().map(|r| linear(r, Place::Right, parens)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect(),
+            };
+            wrap(
+                words,
+                (parens && place != Place::Top) || place == Place::Right,
+            )
+        }
+    }
+}
+
+/// a noun which is one word, or a list of numbers
+fn simple_noun(arr: &JArray) -> bool {
+    match arr {
+        JArray::BoxArray(_) | JArray::SparseArray(_) => false,
+        _ => match arr.shape() {
+            [] => true,
+            [n] => *n > 1,
+            _ => false,
+        },
+    }
+}
+
+/// J which makes the noun
+fn linear_noun(arr: &JArray) -> String {
+    let atoms = |arr: &JArray| -> String {
+        match arr {
+            JArray::CharArray(c, _) => {
+                format!("'{}'", c.iter().collect::<String>().replace('\'', "''"))
+            }
+            JArray::BoxArray(b) => {
+                let boxed = |a: &JArray| match simple_noun(a) {
+                    true => format!("<{}", linear_noun(a)),
+                    false => format!("<({})", linear_noun(a)),
+                };
+                let boxes = b.iter().map(boxed).collect_vec();
+                let (last, init) = boxes.split_last().expect("not empty");
+                init.iter()
+                    .map(|b| format!("({b}),"))
+                    .chain(iter::once(last.clone()))
+                    .collect()
+            }
+            JArray::ExtIntArray(_) => format!("{}x", crate::eval::quote_arr(arr)),
+            _ if arr.tally() == 0 => "i.0".to_string(),
+            _ => crate::eval::quote_arr(arr),
+        }
+    };
+    match (arr, arr.shape()) {
+        (JArray::SparseArray(s), _) => match s.to_dense() {
+            Ok(dense) => format!("$. {}", linear_noun(&dense)),
+            Err(_) => "$. ''".to_string(),
+        },
+        (_, []) => atoms(arr),
+        (_, [1]) => format!(",{}", atoms(arr)),
+        (JArray::CharArray(..), [_]) => atoms(arr),
+        (JArray::BoxArray(_), [0]) => "0$a:".to_string(),
+        (_, [0]) => "i.0".to_string(),
+        (_, [_]) => atoms(arr),
+        (_, shape) => {
+            let shape = shape.iter().join(" ");
+            match arr.tally() {
+                0 => format!("{shape}$0"),
+                n => format!("{shape}${}", atoms(&arr.reshape(vec![n]).expect("ravel"))),
+            }
+        }
+    }
+}
@@ -7,7 +7,7 @@ use itertools::Itertools;
 use log::info;
 
 use crate::foreign::files::noun_to_fs_path;
-use crate::{feed, Ctx, EvalOutput, HasEmpty, JArray, JError, Word};
+use crate::{feed, Ctx, ErrorSite, EvalOutput, HasEmpty, JArray, JError, Word};
 
 pub fn f_load_script(ctx: &mut Ctx, k: i64, y: &JArray) -> Result<JArray> {
     let [src, err, display]: [char; 3] = format!("{k:03}")
@@ -45,9 +45,13 @@ pub fn f_load_script(ctx: &mut Ctx, k: i64, y: &JArray) -> Result<JArray> {
     for (off, line) in script.split('\n').enumerate() {
         match feed(line, ctx) {
             Ok(word) => last = word,
-            Err(e) if break_on_error => {
-                return Err(e).with_context(|| anyhow!("on line {} of {path:?}", off + 1))
+            Err(mut e) if break_on_error => {
+                if let Some(site) = e.downcast_mut::<ErrorSite>() {
+                    site.in_script(&path.display().to_string(), off + 1);
+                }
+                return Err(e).with_context(|| anyhow!("on line {} of {path:?}", off + 1));
             }
+            Err(e) if matches!(JError::extract(&e), Some(JError::Exit(_))) => return Err(e),
             Err(e) => {
                 info!("ignoring error during script load, as requested: {e:?}")
             }
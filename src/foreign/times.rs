@@ -13,7 +13,7 @@ pub fn f_time_sentence(ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<
         _ => Err(JError::DomainError).context("x IntArray, y CharArray please"),
     }?;
     match y {
-        JArray::CharArray(y) => {
+        JArray::CharArray(y, _) => {
             Ok(JArray::FloatArray(ArcArray::from_shape_vec(
                 if let Some(x) = x { x.shape() } else { &[1] },
                 counts
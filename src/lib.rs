@@ -22,7 +22,7 @@ pub use empty::HasEmpty;
 
 // public API
 pub use crate::ctx::Ctx;
-pub use crate::error::JError;
+pub use crate::error::{ErrorSite, JError};
 pub use crate::eval::{eval, feed, EvalOutput};
 
 // e.g. cli syntax highlighting
@@ -47,6 +47,7 @@ use modifiers::ModifierImpl;
 use verbs::VerbImpl;
 
 fn primitive_verbs(sentence: &str) -> Option<VerbImpl> {
+    use modifiers::v_terminate_fold;
     use verbs::*;
     fn p(
         name: &'static str,
@@ -69,6 +70,14 @@ fn primitive_verbs(sentence: &str) -> Option<VerbImpl> {
         })
     }
 
+    // primitives which need to see the `Ctx`, e.g. for the symbol table
+    fn with_ctx(name: &'static str, biv: BivalentOwnedF, ranks: (Rank, DyadRank)) -> VerbImpl {
+        VerbImpl::Partial(PartialImpl {
+            imp: BivalentOwned { biv, ranks },
+            def: Box::new(PartialDef::Primitive(name)),
+        })
+    }
+
     let not_impl = |op| {
         p(
             op,
@@ -83,32 +92,32 @@ fn primitive_verbs(sentence: &str) -> Option<VerbImpl> {
         "=" => p("=", v_self_classify, v_equal, rank!(_ 0 0), None),
         "<" => p("<", v_box, v_less_than, rank!(_ 0 0), ">"),
         "<." => p("<.", v_floor, v_lesser_of_min, rank!(0 0 0), None),
-        "<:" => p("<:", v_decrement, v_less_or_equal, rank!(0 0 0), None),
+        "<:" => p("<:", v_decrement, v_less_or_equal, rank!(0 0 0), ">:"),
         ">" => p(">", v_open, v_larger_than, rank!(0 0 0), "<"),
         ">." => p(">.", v_ceiling, v_larger_of_max, rank!(0 0 0), None),
-        ">:" => p(">:", v_increment, v_larger_or_equal, rank!(0 0 0), None),
+        ">:" => p(">:", v_increment, v_larger_or_equal, rank!(0 0 0), "<:"),
 
-        "+" => p("+", v_conjugate, v_plus, rank!(0 0 0), "-"),
-        "+." => p("+.", v_real_imaginary, v_gcd_or, rank!(0 0 0), None),
-        "+:" => p("+:", v_double, v_not_or, rank!(0 0 0), None),
+        "+" => p("+", v_conjugate, v_plus, rank!(0 0 0), "+"),
+        "+." => p("+.", v_real_imaginary, v_gcd_or, rank!(0 0 0), "j./\"1"),
+        "+:" => p("+:", v_double, v_not_or, rank!(0 0 0), "-:"),
         "*" => p("*", v_signum, v_times, rank!(0 0 0), None),
         "*." => p("*.", v_length_angle, v_lcm_and, rank!(0 0 0), None),
         "*:" => p("*:", v_square, v_not_and, rank!(0 0 0), "%:"),
-        "-" => p("-", v_negate, v_minus, rank!(0 0 0), None),
-        "-." => p("-.", v_not, v_less, rank!(0 _ _), None),
-        "-:" => p("-:", v_halve, v_match, rank!(0 _ _), None),
-        "%" => p("%", v_reciprocal, v_divide, rank!(0 0 0), None),
-        "%." => p("%.", v_matrix_inverse, v_matrix_divide, rank!(2 _ 2), None),
+        "-" => p("-", v_negate, v_minus, rank!(0 0 0), "-"),
+        "-." => p("-.", v_not, v_less, rank!(0 _ _), "-."),
+        "-:" => p("-:", v_halve, v_match, rank!(0 _ _), "+:"),
+        "%" => p("%", v_reciprocal, v_divide, rank!(0 0 0), "%"),
+        "%." => p("%.", v_matrix_inverse, v_matrix_divide, rank!(2 _ 2), "%."),
         "%:" => p("%:", v_square_root, v_root, rank!(0 0 0), "*:"),
 
-        "^" => p("^", v_exponential, v_power, rank!(0 0 0), None),
+        "^" => p("^", v_exponential, v_power, rank!(0 0 0), "^."),
         "^." => p("^.", v_natural_log, v_logarithm, rank!(0 0 0), "^"),
         "$" => p("$", v_shape_of, v_shape, rank!(_ 1 _), None),
         "~." => p("~.", v_nub, v_not_exist_dyad, rank!(_ _ _), None),
         "~:" => p("~:", v_nub_sieve, v_not_equal, rank!(_ 0 0), None),
         "|" => p("|", v_magnitude, v_residue, rank!(0 0 0), None),
-        "|." => p("|.", v_reverse, v_rotate_shift, rank!(_ _ _), None),
-        "|:" => p("|:", v_transpose, v_transpose_dyad, rank!(_ _ _), None),
+        "|." => p("|.", v_reverse, v_rotate_shift, rank!(_ _ _), "|."),
+        "|:" => p("|:", v_transpose, v_transpose_dyad, rank!(_ _ _), "|:"),
 
         "," => p(",", v_ravel, v_append, rank!(_ _ _), None),
         ",." => p(",.", v_ravel_items, v_stitch, rank!(_ _ _), None),
@@ -117,14 +126,14 @@ fn primitive_verbs(sentence: &str) -> Option<VerbImpl> {
         ";:" => p(";:", v_words, v_sequential_machine, rank!(1 _ _), None),
 
         "#" => p("#", v_tally, v_copy, rank!(_ 1 _), None),
-        "#." => p("#.", v_base_, v_base, rank!(1 1 1), None),
-        "#:" => p("#:", v_antibase_, v_antibase, rank!(_ 1 0), None),
+        "#." => p("#.", v_base_, v_base, rank!(1 1 1), "#:"),
+        "#:" => p("#:", v_antibase_, v_antibase, rank!(_ 1 0), "#."),
         "!" => p("!", v_factorial, v_out_of, rank!(0 0 0), None),
-        "/:" => p("/:", v_grade_up, v_sort_up, rank!(_ _ _), None),
+        "/:" => p("/:", v_grade_up, v_sort_up, rank!(_ _ _), "/:"),
         "\\:" => p("\\:", v_grade_down, v_sort_down, rank!(_ _ _), None),
 
-        "[" => p("[", v_same, v_left, rank!(_ _ _), None),
-        "]" => p("]", v_same, v_right, rank!(_ _ _), None),
+        "[" => p("[", v_same, v_left, rank!(_ _ _), "["),
+        "]" => p("]", v_same, v_right, rank!(_ _ _), "]"),
         "{" => p("{", v_catalogue, v_from, rank!(1 0 _), None),
         "{." => p("{.", v_head, v_take, rank!(_ 1 _), None),
         "{:" => p("{:", v_tail, v_not_implemented_dyad, rank!(_ _ _), None),
@@ -132,29 +141,33 @@ fn primitive_verbs(sentence: &str) -> Option<VerbImpl> {
         "{::" => p("{::", v_map, v_fetch, rank!(_ 1 _), None),
         "}." => p("}.", v_behead, v_drop, rank!(_ 1 _), None),
 
-        "\"." => p("\".", v_do, v_numbers, rank!(1 _ _), None),
-        "\":" => p("\":", v_default_format, v_format, rank!(_ 1 _), None),
+        "\"." => p("\".", v_do, v_numbers, rank!(1 _ _), "\":"),
+        "\":" => p("\":", v_default_format, v_format, rank!(_ 1 _), "\"."),
         "?" => p("?", v_roll, v_deal, rank!(0 0 0), None),
         "?." => p("?.", v_roll, v_deal_fixed_seed, rank!(_ 0 0), None),
 
         "A." => p("A.", v_anagram_index, v_anagram, rank!(1 0 _), None),
-        "C." => p("C.", v_cycledirect, v_permute, rank!(1 1 _), None),
+        "C." => p("C.", v_cycledirect, v_permute, rank!(1 1 _), "C."),
         "e." => p("e.", v_raze_in, v_member_in, rank!(_ _ _), None),
 
         "i." => p("i.", v_integers, v_index_of, rank!(1 _ _), None),
         "i:" => p("i:", v_steps, v_index_of_last, rank!(0 _ _), None),
         "I." => p("I.", v_indices, v_interval_index, rank!(1 _ _), None),
-        "j." => p("j.", v_imaginary, v_complex, rank!(0 0 0), None),
-        "o." => p("o.", v_pi_times, v_circle_function, rank!(0 0 0), None),
+        "j." => p("j.", v_imaginary, v_complex, rank!(0 0 0), "%&0j1"),
+        "o." => p("o.", v_pi_times, v_circle_function, rank!(0 0 0), "%&(o. 1)"),
         "p." => p("p.", v_roots, v_polynomial, rank!(1 1 0), None),
         "p.." => p("p..", v_poly_deriv, v_poly_integral, rank!(1 0 1), None),
 
-        "q:" => p("q:", v_prime_factors, v_prime_exponents, rank!(0 0 0), None),
+        "q:" => p("q:", v_prime_factors, v_prime_exponents, rank!(0 0 0), "*/"),
         "r." => p("r.", v_angle, v_polar, rank!(0 0 0), None),
         "x:" => p("x:", v_extend_precision, v_num_denom, rank!(_ _ _), None),
 
-        "$." => not_impl("$."),
-        "$:" => not_impl("$:"),
+        "$." => p("$.", v_sparse, v_sparse_functions, rank!(_ _ _), "0&$."),
+        "$:" => with_ctx(
+            "$:",
+            BivalentOwned::from_bivalent(v_self_reference),
+            rank!(_ _ _),
+        ),
         ".:" => not_impl(".:"),
         ".." => not_impl(".."),
         "[:" => VerbImpl::Cap,
@@ -167,11 +180,15 @@ fn primitive_verbs(sentence: &str) -> Option<VerbImpl> {
             None,
         ),
         "L." => p("L.", v_levels, v_not_exist_dyad, rank!(_ _ _), None),
-        "p:" => not_impl("p:"),
-        "s:" => not_impl("s:"),
+        "p:" => p("p:", v_primes, v_prime_functions, rank!(0 0 0), "_1&p:"),
+        "s:" => with_ctx("s:", BivalentOwned::from_bivalent(v_symbol), rank!(_ _ _)),
         "T." => not_impl("T."),
-        "u:" => not_impl("u:"),
-        "Z:" => not_impl("Z:"),
+        "u:" => p("u:", v_unicode, v_unicode_conversions, rank!(_ _ _), "3&u:"),
+        "Z:" => with_ctx(
+            "Z:",
+            BivalentOwned::from_bivalent(v_terminate_fold),
+            rank!(_ _ _),
+        ),
         "u." => not_impl("u."),
         "v." => not_impl("v."),
 
@@ -193,8 +210,11 @@ fn primitive_adverbs(sentence: &str) -> Option<ModifierImpl> {
         "]:" => adverb("]:", a_not_implemented),
         "}" => adverb("}", a_curlyrt),
         "b." => adverb("b.", a_bdot),
-        "f." => adverb("f.", a_not_implemented),
-        "M." => adverb("M.", a_not_implemented),
+        "f." => ModifierImpl::WordyAdverb(WordyAdverb {
+            name: "f.",
+            f: a_fix,
+        }),
+        "M." => adverb("M.", a_memo),
         _ => return None,
     })
 }
@@ -222,7 +242,9 @@ pub fn primitive_nouns(sentence: &str) -> Option<Word> {
             // ]
             // .concat();
             let ascii_ints: Vec<u8> = (0..=255u8).collect();
-            char_array(ascii_ints.iter().map(|i| *i as char).collect::<String>())
+            Word::Noun(JArray::from_list(
+                ascii_ints.iter().map(|i| *i as char).collect::<Vec<_>>(),
+            ))
         }
         // TODO declare a: properly instead of the scan hack
         "a:" => scan_eval("<0$0").unwrap(),
@@ -236,13 +258,16 @@ fn primitive_conjunctions(sentence: &str) -> Option<ModifierImpl> {
     // https://code.jsoftware.com/wiki/NuVoc
     Some(match sentence {
         "^:" => conj("^:", c_hatco),
-        "." => conj(".", c_not_implemented),
+        "." => conj(".", c_dot),
         ":" => ModifierImpl::Cor,
-        ":." => conj(":.", c_not_implemented),
+        ":." => conj(":.", c_obverse),
         "::" => conj("::", c_assign_adverse),
         ";." => conj(";.", c_cut),
-        "!." => conj("!.", c_not_implemented),
-        "!:" => conj("!:", c_foreign),
+        "!." => conj("!.", c_fit),
+        "!:" => ModifierImpl::WordyConjunction(WordyConjunction {
+            name: "!:",
+            f: c_foreign,
+        }),
         "[." => conj("[.", c_not_implemented),
         "]." => conj("].", c_not_implemented),
         "\"" => conj("\"", c_quote),
@@ -250,7 +275,10 @@ fn primitive_conjunctions(sentence: &str) -> Option<ModifierImpl> {
             name: "`",
             f: c_tie,
         }),
-        "`:" => conj("`:", c_not_implemented),
+        "`:" => ModifierImpl::WordyConjunction(WordyConjunction {
+            name: "`:",
+            f: c_evoke,
+        }),
         "@" => conj("@", c_atop),
         "@." => ModifierImpl::WordyConjunction(WordyConjunction {
             name: "@.",
@@ -264,15 +292,15 @@ fn primitive_conjunctions(sentence: &str) -> Option<ModifierImpl> {
         "d." => conj("d.", c_not_implemented),
         "D." => conj("D.", c_not_implemented),
         "D:" => conj("D:", c_not_implemented),
-        "F." => conj("F.", c_not_implemented),
-        "F.." => conj("F..", c_not_implemented),
-        "F.:" => conj("F.:", c_not_implemented),
-        "F:" => conj("F:", c_not_implemented),
-        "F:." => conj("F:.", c_not_implemented),
-        "F::" => conj("F::", c_not_implemented),
+        "F." => conj("F.", c_fold),
+        "F.." => conj("F..", c_fold_forward),
+        "F.:" => conj("F.:", c_fold_reverse),
+        "F:" => conj("F:", c_fold_multiple),
+        "F:." => conj("F:.", c_fold_multiple_forward),
+        "F::" => conj("F::", c_fold_multiple_reverse),
         "H." => conj("H.", c_not_implemented),
-        "L:" => conj("L:", c_not_implemented),
-        "S:" => conj("S:", c_not_implemented),
+        "L:" => conj("L:", c_level),
+        "S:" => conj("S:", c_spread),
         "t." => conj("t.", c_not_implemented),
         _ => return None,
     })
@@ -1,13 +1,16 @@
+use std::collections::HashMap;
 use std::fmt;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
 
 use crate::cells::fill_promote_reshape;
 use crate::eval::VerbNoun;
+use crate::foreign::binary::{self, Layout};
 use crate::modifiers::do_atop;
-use crate::verbs::{v_self_classify, BivalentOwned, VerbImpl};
-use crate::{primitive_verbs, rank, Ctx, JArray, JError};
+use crate::verbs::{v_self_classify, BivalentOwned, PartialDef, Rank, VerbImpl};
+use crate::{primitive_verbs, rank, Ctx, JArray, JError, Word};
 
 pub type AdverbFn = fn(&mut Ctx, &VerbNoun) -> Result<BivalentOwned>;
 
@@ -29,6 +32,24 @@ impl fmt::Debug for SimpleAdverb {
     }
 }
 
+#[derive(Clone)]
+pub struct WordyAdverb {
+    pub name: &'static str,
+    pub f: fn(&mut Ctx, &Word) -> Result<Word>,
+}
+
+impl PartialEq for WordyAdverb {
+    fn eq(&self, other: &Self) -> bool {
+        self.name.eq(other.name)
+    }
+}
+
+impl fmt::Debug for WordyAdverb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "WordyAdverb({:?})", self.name)
+    }
+}
+
 pub fn a_not_implemented(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
     let u = u.clone();
     let biv = BivalentOwned::from_bivalent(move |_ctx, _x, _y| {
@@ -74,7 +95,7 @@ pub fn a_slash(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
         if let Some(x) = x {
             return a_table(ctx, &u, x, y);
         }
-        y.outer_iter()
+        y.outer_iter()?
             .rev()
             .map(Ok)
             // Reverse to force right to left execution.
@@ -95,8 +116,8 @@ pub fn a_slash(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
 
 fn a_table(ctx: &mut Ctx, u: &VerbImpl, x: &JArray, y: &JArray) -> Result<JArray> {
     let mut items = Vec::new();
-    for x in x.outer_iter() {
-        for y in y.outer_iter() {
+    for x in x.outer_iter()? {
+        for y in y.outer_iter()? {
             items.push(u.exec(ctx, Some(&x.to_owned()), &y.to_owned())?);
         }
     }
@@ -135,7 +156,7 @@ pub fn a_backslash(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
     let u = u.clone();
     let biv = BivalentOwned::from_bivalent(move |ctx, x, y| match x {
         None => {
-            let y = y.outer_iter().collect_vec();
+            let y = y.outer_iter()?.collect_vec();
             let mut piece = Vec::new();
             for i in 1..=y.len() {
                 let chunk = &y[..i];
@@ -164,11 +185,11 @@ pub fn a_backslash(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
 
             let size = usize::try_from(x.abs())?;
             if x < 0 {
-                for chunk in y.outer_iter().collect_vec().chunks(size) {
+                for chunk in y.outer_iter()?.collect_vec().chunks(size) {
                     f(chunk)?;
                 }
             } else {
-                for chunk in y.outer_iter().collect_vec().windows(size) {
+                for chunk in y.outer_iter()?.collect_vec().windows(size) {
                     f(chunk)?;
                 }
             }
@@ -190,7 +211,7 @@ pub fn a_suffix_outfix(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
     let u = u.clone();
     let biv = BivalentOwned::from_bivalent(move |ctx, x, y| match x {
         None => {
-            let y = y.outer_iter().collect_vec();
+            let y = y.outer_iter()?.collect_vec();
             let mut piece = Vec::new();
             for i in 0..y.len() {
                 piece.push(u.exec(
@@ -238,14 +259,19 @@ fn build_curlrt(u: &JArray) -> Result<BivalentOwned> {
         return Err(JError::LengthError).context("u can't be empty");
     }
 
-    let u = u.approx_usize_list()?;
-    let biv = BivalentOwned::from_bivalent(move |_ctx, x, y| match x {
-        Some(x) => {
+    // boxed indices are only supported into sparse arrays, by exec_sparse
+    let u = match u {
+        JArray::BoxArray(_) => None,
+        _ => Some(u.approx_usize_list()?),
+    };
+    let biv = BivalentOwned::from_bivalent(move |_ctx, x, y| match (x, &u) {
+        (Some(_), None) => Err(JError::NonceError).context("amending at boxed indices"),
+        (Some(x), Some(u)) => {
             if x.is_empty() {
                 return Err(JError::LengthError).context("x cannot be empty");
             }
-            let x = x.outer_iter().collect_vec();
-            let mut y = y.outer_iter().collect_vec();
+            let x = x.outer_iter()?.collect_vec();
+            let mut y = y.outer_iter()?.collect_vec();
 
             for (i, u) in u.iter().enumerate() {
                 *y.get_mut(*u)
@@ -255,7 +281,7 @@ fn build_curlrt(u: &JArray) -> Result<BivalentOwned> {
 
             JArray::from_fill_promote(y)
         }
-        None => Err(JError::NonceError).context("monadic }"),
+        (None, _) => Err(JError::NonceError).context("monadic }"),
     });
 
     Ok(BivalentOwned {
@@ -282,3 +308,137 @@ pub fn a_bdot(_ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
         Verb(_) => Err(JError::NonceError).with_context(|| anyhow!("b. verb info for {u:?}")),
     }
 }
+
+/// the most results one `u M.` remembers; its cache is emptied when it fills up
+const MEMO_CAPACITY: usize = 10_000;
+
+/// arguments with more atoms than this aren't remembered
+const MEMO_ATOMS: usize = 256;
+
+/// the encoded x (if any) and y
+type MemoKey = (Option<Vec<u8>>, Vec<u8>);
+
+pub fn a_memo(ctx: &mut Ctx, u: &VerbNoun) -> Result<BivalentOwned> {
+    use VerbNoun::*;
+    let Verb(u) = u else {
+        return Err(JError::DomainError).context("verb for M.'s u");
+    };
+    let ranks = match u.to_verb(ctx.eval()) {
+        Ok(v) => (
+            v.monad_rank().unwrap_or(Rank::infinite()),
+            v.dyad_rank()
+                .unwrap_or((Rank::infinite(), Rank::infinite())),
+        ),
+        Err(_) => rank!(_ _ _),
+    };
+    let u = u.clone();
+    let cache: Arc<Mutex<HashMap<MemoKey, JArray>>> = Arc::default();
+    let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
+        let u = u.to_verb(ctx.eval())?;
+        let key = match x {
+            None => memo_bytes(y).map(|y| (None, y)),
+            Some(x) => memo_bytes(x).zip(memo_bytes(y)).map(|(x, y)| (Some(x), y)),
+        };
+        let Some(key) = key else {
+            return u.exec(ctx, x, y);
+        };
+        let hit = cache.lock().expect("unpoisoned").get(&key).cloned();
+        if let Some(hit) = hit {
+            return Ok(hit);
+        }
+        // u may well call this again, so the cache mustn't be locked while it runs
+        let result = u.exec(ctx, x, y)?;
+        let mut cache = cache.lock().expect("unpoisoned");
+        if cache.len() >= MEMO_CAPACITY {
+            cache.clear();
+        }
+        cache.insert(key, result.clone());
+        Ok(result)
+    });
+    Ok(BivalentOwned { biv, ranks })
+}
+
+/// an argument as a cache key, if it's small enough to be worth remembering
+fn memo_bytes(arr: &JArray) -> Option<Vec<u8>> {
+    if arr.shape().iter().product::<usize>() > MEMO_ATOMS {
+        return None;
+    }
+    binary::encode(arr, Layout::NATIVE).ok()
+}
+
+/// f. (adverb)
+pub fn a_fix(ctx: &mut Ctx, u: &Word) -> Result<Word> {
+    match fix_word(ctx, u, &mut Vec::new())? {
+        v @ Word::Verb(_) => Ok(v),
+        _ => Err(JError::DomainError).with_context(|| anyhow!("f. takes a verb, not {u:?}")),
+    }
+}
+
+/// `word`, with the names in it replaced by their definitions; `fixing` is the names being
+/// expanded, outermost first
+fn fix_word(ctx: &mut Ctx, word: &Word, fixing: &mut Vec<String>) -> Result<Word> {
+    match word {
+        Word::Name(name) => fix_name(ctx, name, fixing),
+        Word::Verb(v) => Ok(Word::Verb(fix_verb(ctx, v, fixing)?)),
+        _ => Ok(word.clone()),
+    }
+}
+
+fn fix_name(ctx: &mut Ctx, name: &str, fixing: &mut Vec<String>) -> Result<Word> {
+    if let Some(depth) = fixing.iter().position(|n| n == name) {
+        // recursion back to the whole verb is `$:`; anything else is left to run by name
+        return Ok(match depth {
+            0 => Word::Verb(primitive_verbs("$:").expect("$: is a primitive")),
+            _ => Word::Name(name.to_string()),
+        });
+    }
+    let word = ctx
+        .eval()
+        .locales
+        .lookup(name)?
+        .cloned()
+        .ok_or(JError::ValueError)
+        .with_context(|| anyhow!("{name:?} isn't defined"))?;
+    fixing.push(name.to_string());
+    let fixed = fix_word(ctx, &word, fixing);
+    fixing.pop();
+    fixed
+}
+
+fn fix_verb(ctx: &mut Ctx, v: &VerbImpl, fixing: &mut Vec<String>) -> Result<VerbImpl> {
+    let mut fix = |ctx: &mut Ctx, word: &Word| fix_word(ctx, word, fixing);
+    Ok(match v {
+        VerbImpl::Fork { f, g, h } => VerbImpl::Fork {
+            f: Box::new(fix(ctx, f)?),
+            g: Box::new(fix(ctx, g)?),
+            h: Box::new(fix(ctx, h)?),
+        },
+        VerbImpl::Hook { l, r } => VerbImpl::Hook {
+            l: Box::new(fix(ctx, l)?),
+            r: Box::new(fix(ctx, r)?),
+        },
+        // the modifier is applied again, to the fixed operands
+        VerbImpl::Partial(p) => match &*p.def {
+            PartialDef::Adverb(a, u) => {
+                let u = fix(ctx, &u.clone().into())?;
+                fixed_verb(a.form_adverb(ctx, &u)?)?
+            }
+            PartialDef::Conjunction(u, c, v) => {
+                let u = fix(ctx, &u.clone().into())?;
+                let v = fix(ctx, &v.clone().into())?;
+                fixed_verb(c.form_conjunction(ctx, &u, &v)?)?
+            }
+            // explicit definitions look their names up as they run
+            PartialDef::Cor(..) | PartialDef::Primitive(_) => v.clone(),
+        },
+        VerbImpl::Primitive(_) | VerbImpl::Cap | VerbImpl::Number(_) => v.clone(),
+    })
+}
+
+fn fixed_verb((farcical, word): (bool, Word)) -> Result<VerbImpl> {
+    match (farcical, word) {
+        (false, Word::Verb(v)) => Ok(v),
+        (_, word) => Err(JError::DomainError)
+            .with_context(|| anyhow!("a fixed modifier made {word:?}, not a verb")),
+    }
+}
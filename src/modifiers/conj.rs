@@ -5,16 +5,18 @@ use std::sync::Arc;
 use anyhow::{anyhow, ensure, Context, Error, Result};
 use itertools::Itertools;
 use ndarray::prelude::*;
+use ndarray::Slice;
 
 use crate::arrays::BoxArray;
 use crate::cells::{apply_cells, fill_promote_reshape, monad_cells};
 use crate::eval::{create_def, resolve_controls, MaybeVerb, VerbNoun};
-use crate::foreign::foreign;
-use crate::scan::str_to_primitive;
+use crate::foreign::{f_define, foreign};
+use crate::modifiers::{ModifierImpl, WordyAdverb};
 use crate::verbs::{
-    append_nd, exec_dyad, exec_monad, BivalentOwned, PartialDef, PartialImpl, Rank, VerbImpl,
+    append_nd, exec_dyad, exec_monad, BivalentOwned, PartialDef, PartialImpl, PrimitiveImpl, Rank,
+    VerbImpl,
 };
-use crate::{arr0d, generate_cells, primitive_conjunctions, rank, Ctx};
+use crate::{arr0d, generate_cells, primitive_adverbs, primitive_conjunctions, rank, Ctx};
 use crate::{HasEmpty, JArray, JError, Word};
 
 #[derive(Clone)]
@@ -147,7 +149,7 @@ pub fn c_hatco(ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwne
                     // x u^:(v0`v1`v2)y <==> (x v0 y)u^:(x v1 y) (x v2 y)
                     let b = b
                         .iter()
-                        .map(|item| untie(ctx, item))
+                        .map(|item| Word::from_boxed_ar(ctx, item))
                         .collect::<Result<Vec<_>>>()?;
                     let (v0, v1, v2) = match &b[..] {
                         [Word::Verb(v1), Word::Verb(v2)] => (None, v1, v2),
@@ -220,13 +222,13 @@ fn do_hatco(
                         t = u.exec(ctx, x, &t)?;
                     }
                 } else {
-                    let u_i = u.obverse().unwrap();
+                    // x u^:_1 y is x&u^:_1 y
+                    let u_i = match x {
+                        None => u.obverse(ctx)?,
+                        Some(x) => bond(ctx, x, u)?.obverse(ctx)?,
+                    };
                     for _ in 0..(*i).abs() {
-                        if let Some(ja) = x {
-                            t = u_i.exec(ctx, Some(&t), ja)?;
-                        } else {
-                            t = u_i.exec(ctx, x, &t)?;
-                        }
+                        t = u_i.exec(ctx, None, &t)?;
                     }
                 }
                 Ok(t)
@@ -235,6 +237,15 @@ fn do_hatco(
     ))
 }
 
+/// `x&u`, the monad which x u^:n y repeats
+fn bond(ctx: &mut Ctx, x: &JArray, u: &VerbImpl) -> Result<VerbImpl> {
+    let bondo = primitive_conjunctions("&").expect("static conjunction");
+    match bondo.form_conjunction(ctx, &Word::Noun(x.clone()), &Word::Verb(u.clone()))? {
+        (_, Word::Verb(v)) => Ok(v),
+        (_, w) => Err(JError::DomainError).with_context(|| anyhow!("x&u made {w:?}")),
+    }
+}
+
 pub fn c_quote(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
     let VerbNoun::Noun(n) = v else {
         return Err(JError::DomainError).context("rank conjugation's arg must be a noun");
@@ -332,7 +343,7 @@ pub fn c_agenda(ctx: &mut Ctx, u: &Word, v: &Word) -> Result<Word> {
     }
 
     match v {
-        Noun(v) => do_agenda(ctx, u, v.approx_usize_one().context("agenda's v")?),
+        Noun(v) => do_agenda(ctx, u, v.approx_i64_one().context("agenda's v")?),
         Verb(v) => {
             let u = u.clone();
             let v = v.clone();
@@ -343,19 +354,20 @@ pub fn c_agenda(ctx: &mut Ctx, u: &Word, v: &Word) -> Result<Word> {
                 VerbNoun::Verb(MaybeVerb::Verb(v.clone())),
             ));
 
+            // the index is picked for each cell v applies to
+            let ranks = match (v.monad_rank(), v.dyad_rank()) {
+                (Some(m), Some((l, r))) => (m, (l, r)),
+                _ => rank!(_ _ _),
+            };
             let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
                 let v = v.exec(ctx, x, y)?;
-                match do_agenda(ctx, &u, v.approx_usize_one()?)? {
+                match do_agenda(ctx, &u, v.approx_i64_one()?)? {
                     Verb(a) => a.exec(ctx, x, y),
                     _ => Err(JError::DomainError).context("untied a non-verb, which is banned"),
                 }
             });
             Ok(Verb(VerbImpl::Partial(PartialImpl {
-                imp: BivalentOwned {
-                    biv,
-                    // supposedly depends on the rank of v
-                    ranks: rank!(_ _ _),
-                },
+                imp: BivalentOwned { biv, ranks },
                 def,
             })))
         }
@@ -363,105 +375,102 @@ pub fn c_agenda(ctx: &mut Ctx, u: &Word, v: &Word) -> Result<Word> {
     }
 }
 
-fn do_agenda(ctx: &mut Ctx, u: &BoxArray, v: usize) -> Result<Word> {
-    let verb = u
-        .iter()
-        .nth(v)
+fn do_agenda(ctx: &mut Ctx, u: &BoxArray, v: i64) -> Result<Word> {
+    let ar = usize::try_from(v)
+        .ok()
+        .or_else(|| u.len().checked_sub(usize::try_from(v.unsigned_abs()).ok()?))
+        .and_then(|v| u.iter().nth(v))
         .ok_or(JError::IndexError)
         .context("gerund out of bounds")?;
 
-    untie(ctx, verb)
+    Word::from_boxed_ar(ctx, ar)
 }
 
-fn untie(ctx: &mut Ctx, verb: &JArray) -> Result<Word> {
-    Ok(match verb {
-        JArray::BoxArray(b) => {
-            let op = match &b[0] {
-                JArray::CharArray(arr) if arr.shape().len() <= 1 => arr.iter().collect::<String>(),
-                _ => {
-                    return Err(JError::NonceError)
-                        .with_context(|| anyhow!("unrecognised 'op' type in box array: {b:?}"));
-                }
-            };
-
-            // https://code.jsoftware.com/wiki/Vocabulary/Foreigns#m5
-            match op.as_ref() {
-                "0" => {
-                    if b.shape() != [2] {
-                        return Err(JError::DomainError)
-                            .with_context(|| anyhow!("noun but non-noun shaped box: {b:?}"));
-                    }
-                    Word::Noun(b[1].clone())
-                }
-                "2" => {
-                    return Err(JError::NonceError)
-                        .with_context(|| anyhow!("unimplemented un-tie op: executed adverb"));
-                }
-                "3" => {
-                    if b.shape() != [2] {
-                        return Err(JError::DomainError)
-                            .with_context(|| anyhow!("fork but non-fork shaped box: {b:?}"));
-                    }
-                    let r = &b[1];
-                    let r = match r {
-                        JArray::BoxArray(b) if b.shape() == [3] => b,
-                        _ => {
-                            return Err(JError::DomainError)
-                                .with_context(|| anyhow!("fork but non-fork shaped r: {r:?}"));
-                        }
-                    };
-                    Word::Verb(VerbImpl::Fork {
-                        f: Box::new(untie(ctx, &r[0])?),
-                        g: Box::new(untie(ctx, &r[1])?),
-                        h: Box::new(untie(ctx, &r[2])?),
-                    })
-                }
-                "4" => {
-                    return Err(JError::NonceError)
-                        .with_context(|| anyhow!("unimplemented un-tie op: modifier train"));
-                }
-                _ => match str_to_primitive(&op)? {
-                    Some(Word::Conjunction(c)) => {
-                        if b.shape() != [2] {
-                            return Err(JError::DomainError).with_context(|| {
-                                anyhow!("conjunction but non-conjunction shaped box: {b:?}")
-                            });
-                        }
-
-                        let JArray::BoxArray(r) = &b[1] else {
-                            return Err(JError::DomainError).with_context(|| {
-                                anyhow!("conjunction argument should be a box, not {:?}", &b[1])
-                            });
-                        };
-
-                        if r.shape() != [2] {
-                            return Err(JError::DomainError).with_context(|| {
-                                anyhow!("conjunction but non-conjunction shaped argument: {b:?}")
-                            });
-                        }
+/// `: (conjunction)
+pub fn c_evoke(ctx: &mut Ctx, m: &Word, n: &Word) -> Result<Word> {
+    let (Word::Noun(JArray::BoxArray(m)), Word::Noun(n)) = (m, n) else {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("`: takes a gerund and a mode, not {m:?} and {n:?}"));
+    };
+    let mode = n.approx_i64_one().context("`:'s mode")?;
+    let words = m
+        .iter()
+        .map(|ar| Word::from_boxed_ar(ctx, ar))
+        .collect::<Result<Vec<_>>>()?;
+    if words.is_empty() {
+        return Err(JError::DomainError).context("`: of an empty gerund");
+    }
+    match mode {
+        0 | 3 => {}
+        6 => return Ok(evoke_train(words)),
+        _ => return Err(JError::DomainError).with_context(|| anyhow!("`: has no mode {mode}")),
+    }
+    let verbs = words
+        .iter()
+        .map(|w| w.when_verb())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(JError::DomainError)
+        .context("`:0 and `:3 take a gerund of verbs")?;
+    if mode == 3 && verbs.len() == 1 {
+        let insert = primitive_adverbs("/").expect("static adverb");
+        return insert.form_adverb(ctx, &words[0]).map(|(_, w)| w);
+    }
 
-                        let u = untie(ctx, &r[0])?;
-                        let v = untie(ctx, &r[1])?;
-                        let (farcical, conj) = c.form_conjunction(ctx, &u, &v)?;
-                        ensure!(!farcical);
-                        conj
-                    }
-                    other => {
-                        return Err(JError::NonceError).with_context(|| {
-                            anyhow!("can't un-tie primitive {other:?} for {op:?}")
-                        });
-                    }
-                },
+    let biv = match mode {
+        0 => BivalentOwned::from_bivalent(move |ctx, x, y| {
+            let results = verbs
+                .iter()
+                .map(|v| v.exec(ctx, x, y))
+                .collect::<Result<Vec<_>>>()?;
+            JArray::from_fill_promote(results)
+        }),
+        _ => BivalentOwned::from_bivalent(move |ctx, x, y| {
+            if x.is_some() {
+                return Err(JError::DomainError).context("a gerund insert has no dyad");
             }
-        }
-        JArray::CharArray(verb) if verb.shape().len() <= 1 => {
-            let name = verb.iter().collect::<String>();
-            str_to_primitive(&name)?
-                .ok_or(JError::NonceError)
-                .with_context(|| anyhow!("unable to un-tie char list {name:?}"))?
-        }
-        _ => return Err(JError::NonceError).with_context(|| anyhow!("unable to un-tie {verb:?}")),
-    })
+            let mut items = y.outer_iter()?.collect_vec();
+            let mut acc = items
+                .pop()
+                .ok_or(JError::DomainError)
+                .context("a gerund insert of nothing")?;
+            // item k and the result so far are the arguments to verb k, cycling through them
+            for (k, item) in items.iter().enumerate().rev() {
+                acc = verbs[k % verbs.len()].exec(ctx, Some(item), &acc)?;
+            }
+            Ok(acc)
+        }),
+    };
+    let def = Box::new(PartialDef::Conjunction(
+        VerbNoun::Noun(JArray::from(m.clone())),
+        primitive_conjunctions("`:").expect("static conjunction"),
+        VerbNoun::Noun(n.clone()),
+    ));
+    Ok(Word::Verb(VerbImpl::Partial(PartialImpl {
+        imp: BivalentOwned {
+            biv,
+            ranks: rank!(_ _ _),
+        },
+        def,
+    })))
+}
+
+/// the words as a train: forks from the right, and a hook if there's one left over
+fn evoke_train(mut words: Vec<Word>) -> Word {
+    let mut train = words.pop().expect("non-empty");
+    while let Some(g) = words.pop() {
+        train = match words.pop() {
+            Some(f) => Word::Verb(VerbImpl::Fork {
+                f: Box::new(f),
+                g: Box::new(g),
+                h: Box::new(train),
+            }),
+            None => Word::Verb(VerbImpl::Hook {
+                l: Box::new(g),
+                r: Box::new(train),
+            }),
+        };
+    }
+    train
 }
 
 // https://code.jsoftware.com/wiki/Vocabulary/at#/media/File:Funcomp.png
@@ -537,16 +546,23 @@ pub fn c_cor(_ctx: &mut Ctx, n: &Word, m: &Word) -> Result<(bool, Word)> {
         Word::Noun(arr) if arr.approx_i64_one().ok() == Some(0) => {
             Ok((true, Word::Noun(JArray::from(arr0d(n)))))
         }
-        Word::Noun(CharArray(jcode)) if jcode.shape().len() <= 1 => {
+        Word::Noun(CharArray(jcode, width)) if jcode.shape().len() <= 2 => {
             let n = match n {
-                0 => return Ok((false, Word::Noun(CharArray(jcode.clone())))),
+                0 => return Ok((false, Word::Noun(CharArray(jcode.clone(), *width)))),
                 1 => 'a',
                 2 => 'c',
                 3 => 'm',
                 4 => 'd',
                 _ => return Err(JError::NonceError).context("unsupported cor mode"),
             };
-            let jcode = jcode.iter().collect::<String>();
+            // a table has a line per row, as the ar of a definition does
+            let jcode = match jcode.shape() {
+                [_, _] => jcode
+                    .outer_iter()
+                    .map(|row| row.iter().collect::<String>())
+                    .join("\n"),
+                _ => jcode.iter().collect::<String>(),
+            };
             let mut words = crate::scan(&jcode)?;
             if !resolve_controls(&mut words)? {
                 return Err(JError::SyntaxError).context("unable to resolve controls in cor");
@@ -621,7 +637,7 @@ pub fn c_cut(_ctx: &mut Ctx, n: &VerbNoun, m: &VerbNoun) -> Result<BivalentOwned
     };
 
     let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
-        let parts = y.outer_iter().collect_vec();
+        let parts = y.outer_iter()?.collect_vec();
         ensure!(!parts.is_empty());
 
         let frets: Vec<usize> = match x {
@@ -736,15 +752,27 @@ mod test_cut {
     }
 }
 
-pub fn c_foreign(_ctx: &mut Ctx, l: &VerbNoun, r: &VerbNoun) -> Result<BivalentOwned> {
-    match (l, r) {
-        (VerbNoun::Noun(l), VerbNoun::Noun(r)) => {
-            let l = l.approx_i64_one().context("foreign's left")?;
-            let r = r.approx_i64_one().context("foreign's right")?;
-            Ok(foreign(l, r)?)
-        }
-        _ => Err(JError::NonceError).context("unsupported foreign syntax"),
+pub fn c_foreign(_ctx: &mut Ctx, l: &Word, r: &Word) -> Result<Word> {
+    let (Word::Noun(l), Word::Noun(r)) = (l, r) else {
+        return Err(JError::NonceError).context("unsupported foreign syntax");
+    };
+    let major = l.approx_i64_one().context("foreign's left")?;
+    let minor = r.approx_i64_one().context("foreign's right")?;
+    // what 5!:0 makes depends on its ar, so it's an adverb
+    if (major, minor) == (5, 0) {
+        return Ok(Word::Adverb(ModifierImpl::WordyAdverb(WordyAdverb {
+            name: "5!:0",
+            f: f_define,
+        })));
     }
+    Ok(Word::Verb(VerbImpl::Partial(PartialImpl {
+        imp: foreign(major, minor)?,
+        def: Box::new(PartialDef::Conjunction(
+            VerbNoun::Noun(l.clone()),
+            primitive_conjunctions("!:").expect("static conjunction"),
+            VerbNoun::Noun(r.clone()),
+        )),
+    })))
 }
 
 pub fn c_bondo(_ctx: &mut Ctx, n: &VerbNoun, m: &VerbNoun) -> Result<BivalentOwned> {
@@ -753,7 +781,7 @@ pub fn c_bondo(_ctx: &mut Ctx, n: &VerbNoun, m: &VerbNoun) -> Result<BivalentOwn
     // TODO: some of these are presumably obviously monads or dyads
     let biv = match (n.clone(), m.clone()) {
         (Verb(n), Noun(m)) => BivalentOwned::from_bivalent(move |ctx, _x, y| {
-            n.exec(ctx, Some(&m), &y.clone()).context("monad bondo VN")
+            n.exec(ctx, Some(y), &m).context("monad bondo VN")
         }),
         (Noun(n), Verb(m)) => BivalentOwned::from_bivalent(move |ctx, _x, y| {
             m.exec(ctx, Some(&n), y).context("monad bondo NV")
@@ -790,10 +818,7 @@ pub fn c_under(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwn
     let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
         let u = u.to_verb(ctx.eval())?;
         let v = v.to_verb(ctx.eval())?;
-        let vi = v
-            .obverse()
-            .ok_or(JError::NonceError)
-            .context("lacking obverse")?;
+        let vi = v.obverse(ctx).context("under's v")?;
         match x {
             None => do_under_monad(ctx, &u, &v, &vi, y),
             Some(x) => do_under_dyad(ctx, x, u, v, vi, y),
@@ -805,6 +830,25 @@ pub fn c_under(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwn
     })
 }
 
+/// :. (conjunction)
+pub fn c_obverse(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
+    let (VerbNoun::Verb(u), VerbNoun::Verb(_)) = (u, v) else {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("obverse takes two verbs, not {u:?} and {v:?}"));
+    };
+    // u :. v is u, until it's undone; v is picked up from the definition by `obverse`
+    let ranks = match u {
+        MaybeVerb::Verb(u) => match (u.monad_rank(), u.dyad_rank()) {
+            (Some(m), Some((l, r))) => (m, (l, r)),
+            _ => rank!(_ _ _),
+        },
+        MaybeVerb::Name(_) => rank!(_ _ _),
+    };
+    let u = u.clone();
+    let biv = BivalentOwned::from_bivalent(move |ctx, x, y| u.to_verb(ctx.eval())?.exec(ctx, x, y));
+    Ok(BivalentOwned { biv, ranks })
+}
+
 fn do_under_monad(
     ctx: &mut Ctx,
     u: &VerbImpl,
@@ -812,7 +856,9 @@ fn do_under_monad(
     vi: &VerbImpl,
     y: &JArray,
 ) -> Result<JArray, Error> {
-    let (cells, frame) = monad_cells(y, rank!(0))?;
+    // u is applied to each cell v is
+    let rank = v.monad_rank().unwrap_or_else(Rank::infinite);
+    let (cells, frame) = monad_cells(y, rank)?;
     let mut parts = Vec::new();
     for y in cells {
         let v = v.exec(ctx, None, &y).context("under dual v")?;
@@ -850,3 +896,242 @@ fn do_under_dyad(
     )?;
     JArray::from_fill_promote(parts)?.reshape(frame)
 }
+
+// https://code.jsoftware.com/wiki/Vocabulary/dot
+pub fn c_dot(ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
+    use VerbNoun::*;
+    let (Verb(u), Verb(v)) = (u, v) else {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("expected to verb . verb, not {u:?} . {v:?}"));
+    };
+
+    // x u . v y <-> x u@(v"(1+lv,_)) y
+    let lv = v
+        .to_verb(ctx.eval())
+        .ok()
+        .and_then(|v| v.dyad_rank())
+        .map(|(lv, _)| lv)
+        .unwrap_or_else(|| Rank::new(0));
+    let left = match lv.usize() {
+        Some(lv) => Rank::new_checked(u8::try_from(lv + 1)?)?,
+        None => Rank::infinite(),
+    };
+
+    let u = u.clone();
+    let v = v.clone();
+    let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
+        let u = u.to_verb(ctx.eval())?;
+        let v = v.to_verb(ctx.eval())?;
+        match x {
+            Some(x) => do_inner_product(ctx, &u, &v, x, y),
+            None => do_determinant(ctx, &u, &v, y),
+        }
+    });
+    Ok(BivalentOwned {
+        biv,
+        ranks: (Rank::new(2), (left, Rank::infinite())),
+    })
+}
+
+fn do_inner_product(
+    ctx: &mut Ctx,
+    u: &VerbImpl,
+    v: &VerbImpl,
+    x: &JArray,
+    y: &JArray,
+) -> Result<JArray> {
+    if is_sum_times(u, v) {
+        if let Some(r) = sum_times(x, y) {
+            return Ok(r);
+        }
+    }
+    let r = v.exec(ctx, Some(x), y).context("right half of c_dot")?;
+    u.exec(ctx, None, &r).context("left half of c_dot")
+}
+
+/// the primitive `p` if `u` is `p/`
+fn inserted(u: &VerbImpl) -> Option<&PrimitiveImpl> {
+    let VerbImpl::Partial(PartialImpl { def, .. }) = u else {
+        return None;
+    };
+    match &**def {
+        PartialDef::Adverb(a, VerbNoun::Verb(MaybeVerb::Verb(VerbImpl::Primitive(p))))
+            if a.name() == "/" =>
+        {
+            Some(p)
+        }
+        _ => None,
+    }
+}
+
+/// whether `u . v` is `(name)/ . *`
+fn is_insert_times(u: &VerbImpl, v: &VerbImpl, name: &str) -> bool {
+    matches!(v, VerbImpl::Primitive(times) if times.name == "*")
+        && inserted(u).is_some_and(|p| p.name == name)
+}
+
+fn is_sum_times(u: &VerbImpl, v: &VerbImpl) -> bool {
+    is_insert_times(u, v, "+")
+}
+
+/// `x +/ . * y` for a list `x` and plain numbers, without going through the atoms one by one
+fn sum_times(x: &JArray, y: &JArray) -> Option<JArray> {
+    if x.shape().len() != 1 || y.shape().first() != Some(&x.shape()[0]) {
+        return None;
+    }
+    let out = IxDyn(&y.shape()[1..]);
+    match (x, y) {
+        (JArray::FloatArray(_), _) | (_, JArray::FloatArray(_)) => {
+            let x = x.to_f64()?;
+            let y = y.to_f64()?;
+            let mut acc = ArrayD::<f64>::zeros(out);
+            for (x, y) in x.iter().zip(y.outer_iter()) {
+                acc.scaled_add(*x, &y);
+            }
+            Some(JArray::FloatArray(acc.into_shared()))
+        }
+        (
+            JArray::IntArray(_) | JArray::BoolArray(_),
+            JArray::IntArray(_) | JArray::BoolArray(_),
+        ) => {
+            let x = x.to_i64()?;
+            let y = y.to_i64()?;
+            let mut acc = ArrayD::<i64>::zeros(out);
+            for (x, y) in x.iter().zip(y.outer_iter()) {
+                for (a, y) in acc.iter_mut().zip(y.iter()) {
+                    // overflow would make floats, so leave that to the general case
+                    *a = a.checked_add(x.checked_mul(*y)?)?;
+                }
+            }
+            Some(JArray::IntArray(acc.into_shared()))
+        }
+        _ => None,
+    }
+}
+
+/// expansion by minors along the first column:
+/// `DET=: 2 : 'v/@,`({."1 u . v $:@minors)@.(0<{:@$) @ ,.'"2`
+fn do_determinant(ctx: &mut Ctx, u: &VerbImpl, v: &VerbImpl, y: &JArray) -> Result<JArray> {
+    let y = match y.shape().len() {
+        0 => y.reshape(vec![1, 1])?,
+        1 => y.reshape(vec![y.shape()[0], 1])?,
+        _ => y.clone(),
+    };
+    let (rows, cols) = (y.shape()[0], y.shape()[1]);
+    if cols == 0 {
+        return insert_identity(v);
+    }
+    if rows == 0 {
+        // no minors, so `u` is applied to an empty list
+        return match inserted(u) {
+            Some(p) => insert_identity(&VerbImpl::Primitive(*p)),
+            None => u
+                .exec(ctx, None, &JArray::empty())
+                .context("left half of c_dot"),
+        };
+    }
+    if rows == cols && is_insert_times(u, v, "-") {
+        if let Some(r) = determinant(&y) {
+            return Ok(r);
+        }
+    }
+
+    let first = y.select(Axis(1), &[0])?.reshape(vec![rows])?;
+    let minors = (0..rows)
+        .map(|i| {
+            let others = (0..rows).filter(|&r| r != i).collect_vec();
+            let minor = y
+                .select(Axis(0), &others)?
+                .slice_axis(Axis(1), Slice::from(1..))?;
+            do_determinant(ctx, u, v, &minor)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let minors = JArray::from_fill_promote(minors)?;
+    do_inner_product(ctx, u, v, &first, &minors)
+}
+
+/// `-/ . * y` for a square matrix of plain numbers, by elimination rather than expansion
+fn determinant(y: &JArray) -> Option<JArray> {
+    if let Some(y) = y.to_i64() {
+        let y = y.into_dimensionality::<Ix2>().ok()?.to_owned();
+        if let Some(r) = bareiss(y) {
+            return Some(JArray::from(arr0d(r)));
+        }
+    }
+    let y = y.to_f64()?.into_dimensionality::<Ix2>().ok()?.to_owned();
+    Some(JArray::from(arr0d(gaussian(y))))
+}
+
+/// the exact determinant of an integer matrix, unless it overflows
+fn bareiss(mut m: Array2<i64>) -> Option<i64> {
+    let n = m.nrows();
+    let (mut sign, mut prev) = (1i64, 1i128);
+    for k in 0..n.saturating_sub(1) {
+        if m[[k, k]] == 0 {
+            let Some(p) = (k + 1..n).find(|&i| m[[i, k]] != 0) else {
+                return Some(0);
+            };
+            swap_rows(&mut m, k, p);
+            sign = -sign;
+        }
+        for i in k + 1..n {
+            for j in k + 1..n {
+                let (a, b) = (i128::from(m[[i, j]]), i128::from(m[[k, k]]));
+                let (c, d) = (i128::from(m[[i, k]]), i128::from(m[[k, j]]));
+                m[[i, j]] =
+                    i64::try_from(a.checked_mul(b)?.checked_sub(c.checked_mul(d)?)? / prev).ok()?;
+            }
+        }
+        prev = i128::from(m[[k, k]]);
+    }
+    m[[n - 1, n - 1]].checked_mul(sign)
+}
+
+/// the determinant of a float matrix, by Gaussian elimination with partial pivoting
+fn gaussian(mut m: Array2<f64>) -> f64 {
+    let n = m.nrows();
+    let mut det = 1.;
+    for k in 0..n {
+        let p = (k..n)
+            .max_by(|&a, &b| m[[a, k]].abs().total_cmp(&m[[b, k]].abs()))
+            .expect("non-empty");
+        if m[[p, k]] == 0. {
+            return 0.;
+        }
+        if p != k {
+            swap_rows(&mut m, k, p);
+            det = -det;
+        }
+        det *= m[[k, k]];
+        for i in k + 1..n {
+            let f = m[[i, k]] / m[[k, k]];
+            for j in k + 1..n {
+                m[[i, j]] -= f * m[[k, j]];
+            }
+        }
+    }
+    det
+}
+
+fn swap_rows<T: Clone>(m: &mut Array2<T>, a: usize, b: usize) {
+    for j in 0..m.ncols() {
+        m.swap([a, j], [b, j]);
+    }
+}
+
+/// `v/ ''`, which is all the empty expansion needs
+fn insert_identity(v: &VerbImpl) -> Result<JArray> {
+    let VerbImpl::Primitive(p) = v else {
+        return Err(JError::DomainError).context("no identity element for a derived verb");
+    };
+    Ok(match p.name {
+        "+" | "-" | "+." | "~:" | "|" | ">" | "<" => JArray::from(arr0d(0u8)),
+        "*" | "%" | "*." | "=" | "^" | "!" | "<:" | ">:" => JArray::from(arr0d(1u8)),
+        "<." => JArray::from(arr0d(f64::INFINITY)),
+        ">." => JArray::from(arr0d(f64::NEG_INFINITY)),
+        other => {
+            return Err(JError::DomainError)
+                .with_context(|| anyhow!("no identity element for {other}"))
+        }
+    })
+}
@@ -0,0 +1,82 @@
+//! Fit, !., which customises a primitive
+//! https://code.jsoftware.com/wiki/Vocabulary/bangdot
+//!
+//! n is the tolerance of a comparison, the atom a verb pads with, the significant digits of a
+//! format, or the step of a stope. The other valence of u is left alone.
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::eval::VerbNoun;
+use crate::number::tolerance_of;
+use crate::verbs::*;
+use crate::{Ctx, JArray, JError};
+
+/// !. (conjunction)
+pub fn c_fit(ctx: &mut Ctx, u: &VerbNoun, n: &VerbNoun) -> Result<BivalentOwned> {
+    let (VerbNoun::Verb(u), VerbNoun::Noun(n)) = (u, n) else {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("fit takes a verb and a noun, not {u:?} and {n:?}"));
+    };
+    let VerbImpl::Primitive(u) = u.to_verb(ctx.eval())? else {
+        return Err(JError::NonceError).with_context(|| anyhow!("fit only takes primitives"));
+    };
+    let (monad, dyad) = (u.monad.f, u.dyad.f);
+    let biv = match u.name {
+        _ if u.tolerant_monad().is_some() || u.tolerant_dyad().is_some() => {
+            let tolerance = tolerance_of(n)?;
+            let (tm, td) = (u.tolerant_monad(), u.tolerant_dyad());
+            BivalentOwned::from_bivalent(move |_ctx, x, y| match (x, tm, td) {
+                (None, Some(tm), _) => tm(y, tolerance),
+                (None, None, _) => monad(y),
+                (Some(x), _, Some(td)) => td(x, y, tolerance),
+                (Some(x), _, None) => dyad(x, y),
+            })
+        }
+        "{." | "|." | "$" => {
+            let fill = fill(n)?;
+            let pad = match u.name {
+                "{." => take,
+                "|." => rotate_shift,
+                _ => shape,
+            };
+            BivalentOwned::from_bivalent(move |_ctx, x, y| match x {
+                None => monad(y),
+                Some(x) => pad(x, y, Some(&fill)),
+            })
+        }
+        "\":" => {
+            let digits = n
+                .approx_usize_one()
+                .context("the precision of \": is a number of digits")?;
+            BivalentOwned::from_bivalent(move |_ctx, x, y| match x {
+                None => format_precision(y, digits),
+                Some(x) => dyad(x, y),
+            })
+        }
+        "^" => {
+            let step = n
+                .single_math_num()
+                .ok_or(JError::DomainError)
+                .context("the step of a stope is a number")?;
+            BivalentOwned::from_bivalent(move |_ctx, x, y| match x {
+                None => monad(y),
+                Some(x) => stope(x, y, &step),
+            })
+        }
+        _ => {
+            return Err(JError::NonceError)
+                .with_context(|| anyhow!("!. doesn't customise {}", u.name))
+        }
+    };
+    Ok(BivalentOwned {
+        biv,
+        ranks: (u.monad.rank, u.dyad.rank),
+    })
+}
+
+fn fill(n: &JArray) -> Result<JArray> {
+    match n.shape() {
+        [] => Ok(n.clone()),
+        _ => Err(JError::DomainError).with_context(|| anyhow!("fill is an atom, not {n:?}")),
+    }
+}
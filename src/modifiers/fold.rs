@@ -0,0 +1,221 @@
+//! The folds, F. F.. F.: F: F:. F::, and Z:, which steers them from inside u or v
+//! https://code.jsoftware.com/wiki/Vocabulary/fcap
+//!
+//! Each step is `item v acc`, or `x v acc` for the forms without a direction, and u makes the
+//! step's result from the new accumulator. Single folds keep only the last result.
+
+use std::iter;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::eval::VerbNoun;
+use crate::verbs::{BivalentOwned, VerbImpl};
+use crate::{rank, Ctx, HasEmpty, JArray, JError};
+
+/// what `Z:` has asked of the innermost running fold
+#[derive(Debug, Default)]
+pub struct FoldControl {
+    iterations: usize,
+    /// `_3 Z: n`: the most iterations allowed
+    limit: Option<usize>,
+    /// `_2 Z: 1`: end the fold, dropping this iteration
+    quit: bool,
+    /// `_1 Z: 1`: drop this iteration, leaving the accumulator alone
+    skip: bool,
+    /// `0 Z: 1`: don't keep this iteration's result
+    omit: bool,
+    /// `1 Z: 1`: end the fold after this iteration
+    stop: bool,
+}
+
+impl FoldControl {
+    fn next_iteration(&mut self) -> Result<()> {
+        self.iterations += 1;
+        self.check_limit()?;
+        self.quit = false;
+        self.skip = false;
+        self.omit = false;
+        self.stop = false;
+        Ok(())
+    }
+
+    fn check_limit(&self) -> Result<()> {
+        match self.limit {
+            Some(limit) if self.iterations > limit => {
+                Err(JError::FoldLimit).with_context(|| anyhow!("more than {limit} iterations"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+enum Direction {
+    Forward,
+    Reverse,
+    /// repeatedly on y, until `Z:` says otherwise
+    Neither,
+}
+
+/// F. (conjunction)
+pub fn c_fold(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
+    fold(u, v, Direction::Neither, false)
+}
+
+/// F.. (conjunction)
+pub fn c_fold_forward(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
+    fold(u, v, Direction::Forward, false)
+}
+
+/// F.: (conjunction)
+pub fn c_fold_reverse(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
+    fold(u, v, Direction::Reverse, false)
+}
+
+/// F: (conjunction)
+pub fn c_fold_multiple(_ctx: &mut Ctx, u: &VerbNoun, v: &VerbNoun) -> Result<BivalentOwned> {
+    fold(u, v, Direction::Neither, true)
+}
+
+/// F:. (conjunction)
+pub fn c_fold_multiple_forward(
+    _ctx: &mut Ctx,
+    u: &VerbNoun,
+    v: &VerbNoun,
+) -> Result<BivalentOwned> {
+    fold(u, v, Direction::Forward, true)
+}
+
+/// F:: (conjunction)
+pub fn c_fold_multiple_reverse(
+    _ctx: &mut Ctx,
+    u: &VerbNoun,
+    v: &VerbNoun,
+) -> Result<BivalentOwned> {
+    fold(u, v, Direction::Reverse, true)
+}
+
+fn fold(u: &VerbNoun, v: &VerbNoun, direction: Direction, multiple: bool) -> Result<BivalentOwned> {
+    let (VerbNoun::Verb(u), VerbNoun::Verb(v)) = (u, v) else {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("folds take two verbs, not {u:?} and {v:?}"));
+    };
+    let u = u.clone();
+    let v = v.clone();
+    let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
+        let u = u.to_verb(ctx.eval())?;
+        let v = v.to_verb(ctx.eval())?;
+        ctx.folds.push(FoldControl::default());
+        let results = run_fold(ctx, &u, &v, direction, x, y);
+        ctx.folds.pop().expect("pushed above");
+        let mut results = results?;
+        if multiple {
+            return match results.is_empty() {
+                true => Ok(JArray::empty()),
+                false => JArray::from_fill_promote(results),
+            };
+        }
+        results
+            .pop()
+            .ok_or(JError::DomainError)
+            .context("the fold kept no results")
+    });
+    Ok(BivalentOwned {
+        biv,
+        ranks: rank!(_ _ _),
+    })
+}
+
+/// every result kept by the fold, in order
+fn run_fold(
+    ctx: &mut Ctx,
+    u: &VerbImpl,
+    v: &VerbImpl,
+    direction: Direction,
+    x: Option<&JArray>,
+    y: &JArray,
+) -> Result<Vec<JArray>> {
+    let (mut acc, lefts): (JArray, Box<dyn Iterator<Item = Option<JArray>>>) = match direction {
+        Direction::Neither => (y.clone(), Box::new(iter::repeat(x.cloned()))),
+        Direction::Forward | Direction::Reverse => {
+            let mut items = y.outer_iter()?.collect::<Vec<_>>();
+            if let Direction::Reverse = direction {
+                items.reverse();
+            }
+            let mut items = items.into_iter();
+            // without an x, the first item starts the accumulator off
+            let acc = match x.cloned().or_else(|| items.next()) {
+                Some(acc) => acc,
+                None => return Ok(Vec::new()),
+            };
+            (acc, Box::new(items.map(Some)))
+        }
+    };
+
+    let mut results = Vec::new();
+    for left in lefts {
+        control(ctx)?.next_iteration()?;
+        let next = v.exec(ctx, left.as_ref(), &acc).context("fold v")?;
+        let c = control(ctx)?;
+        if c.quit {
+            break;
+        }
+        if c.skip {
+            continue;
+        }
+        let result = u.exec(ctx, None, &next).context("fold u")?;
+        let c = control(ctx)?;
+        if c.quit {
+            break;
+        }
+        if c.skip {
+            continue;
+        }
+        acc = next;
+        if !c.omit {
+            results.push(result);
+        }
+        if c.stop {
+            break;
+        }
+    }
+    Ok(results)
+}
+
+fn control(ctx: &mut Ctx) -> Result<&mut FoldControl> {
+    ctx.folds
+        .last_mut()
+        .ok_or(JError::DomainError)
+        .context("Z: outside of a fold")
+}
+
+/// Z: (dyad)
+pub fn v_terminate_fold(ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<JArray> {
+    let x = x
+        .ok_or(JError::DomainError)
+        .context("Z: has no monad")?
+        .approx_i64_one()
+        .context("Z: takes an integer x")?;
+    if !(-3..=1).contains(&x) {
+        return Err(JError::DomainError).with_context(|| anyhow!("Z: has no mode {x}"));
+    }
+    let c = control(ctx)?;
+    if x == -3 {
+        let limit = y
+            .approx_usize_one()
+            .context("_3 Z: takes an iteration count")?;
+        c.limit = Some(limit);
+        c.check_limit()?;
+        return Ok(JArray::empty());
+    }
+    if y.approx_i64_one().context("Z: takes a boolean y")? == 0 {
+        return Ok(JArray::empty());
+    }
+    match x {
+        -2 => c.quit = true,
+        -1 => c.skip = true,
+        0 => c.omit = true,
+        _ => c.stop = true,
+    }
+    Ok(JArray::empty())
+}
@@ -0,0 +1,205 @@
+//! Level, L:, and spread, S:, which apply u inside boxes
+//! https://code.jsoftware.com/wiki/Vocabulary/lcapco
+//! https://code.jsoftware.com/wiki/Vocabulary/scapco
+//!
+//! u is applied to the first boxes (going in from the top) whose `L.` is at most the level; a
+//! negative level is counted from the top level of the argument instead. L: puts the results back
+//! where they came from, S: lists them.
+
+use anyhow::{anyhow, Context, Result};
+use ndarray::prelude::*;
+
+use crate::arrays::ArcArrayD;
+use crate::eval::{MaybeVerb, VerbNoun};
+use crate::verbs::{levels_of, BivalentOwned, VerbImpl};
+use crate::{rank, Ctx, HasEmpty, JArray, JError};
+
+/// L: (conjunction)
+pub fn c_level(_ctx: &mut Ctx, u: &VerbNoun, n: &VerbNoun) -> Result<BivalentOwned> {
+    let (u, levels) = verb_levels(u, n)?;
+    let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
+        let u = u.to_verb(ctx.eval())?;
+        match x {
+            None => at_level(ctx, &u, resolve(levels[0], y), y),
+            Some(x) => {
+                let levels = (resolve(levels[1], x), resolve(levels[2], y));
+                at_levels(ctx, &u, levels, x, y)
+            }
+        }
+    });
+    Ok(BivalentOwned {
+        biv,
+        ranks: rank!(_ _ _),
+    })
+}
+
+/// S: (conjunction)
+pub fn c_spread(_ctx: &mut Ctx, u: &VerbNoun, n: &VerbNoun) -> Result<BivalentOwned> {
+    let (u, levels) = verb_levels(u, n)?;
+    let biv = BivalentOwned::from_bivalent(move |ctx, x, y| {
+        let u = u.to_verb(ctx.eval())?;
+        let mut results = Vec::new();
+        match x {
+            None => spread(ctx, &u, resolve(levels[0], y), y, &mut results)?,
+            Some(x) => {
+                let levels = (resolve(levels[1], x), resolve(levels[2], y));
+                spread_dyad(ctx, &u, levels, x, y, &mut results)?
+            }
+        }
+        match results.is_empty() {
+            true => Ok(JArray::empty()),
+            false => JArray::from_fill_promote(results),
+        }
+    });
+    Ok(BivalentOwned {
+        biv,
+        ranks: rank!(_ _ _),
+    })
+}
+
+/// u, and the monad, left and right levels, as for rank
+fn verb_levels(u: &VerbNoun, n: &VerbNoun) -> Result<(MaybeVerb, [i64; 3])> {
+    let (VerbNoun::Verb(u), VerbNoun::Noun(n)) = (u, n) else {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("levels take a verb and a noun, not {u:?} and {n:?}"));
+    };
+    let n = n
+        .approx()
+        .ok_or(JError::DomainError)
+        .context("levels must be numbers")?
+        .iter()
+        .map(|&l| match l {
+            l if l == f32::INFINITY => Ok(i64::MAX),
+            l if l == f32::NEG_INFINITY => Ok(i64::MIN),
+            l if l.fract() == 0.0 => Ok(l as i64),
+            l => Err(JError::DomainError).with_context(|| anyhow!("{l} isn't a level")),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let levels = match n[..] {
+        [only] => [only, only, only],
+        [l, r] => [r, l, r],
+        [m, l, r] => [m, l, r],
+        _ => return Err(JError::LengthError).context("levels are a list of 1-3 numbers"),
+    };
+    Ok((u.clone(), levels))
+}
+
+/// a level for `y`, made absolute
+fn resolve(level: i64, y: &JArray) -> usize {
+    match usize::try_from(level) {
+        Ok(level) => level,
+        Err(_) => {
+            let depth = i64::try_from(levels_of(y)).unwrap_or(i64::MAX);
+            usize::try_from(depth.saturating_add(level)).unwrap_or(0)
+        }
+    }
+}
+
+fn too_deep(y: &JArray, level: usize) -> bool {
+    levels_of(y) > level
+}
+
+fn at_level(ctx: &mut Ctx, u: &VerbImpl, level: usize, y: &JArray) -> Result<JArray> {
+    match y {
+        JArray::BoxArray(b) if too_deep(y, level) => {
+            let results = b
+                .iter()
+                .map(|y| at_level(ctx, u, level, y))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(JArray::BoxArray(ArcArrayD::from_shape_vec(
+                b.shape(),
+                results,
+            )?))
+        }
+        _ => u.exec(ctx, None, y).context("u inside L:"),
+    }
+}
+
+fn at_levels(
+    ctx: &mut Ctx,
+    u: &VerbImpl,
+    levels: (usize, usize),
+    x: &JArray,
+    y: &JArray,
+) -> Result<JArray> {
+    let (deep_x, deep_y) = (too_deep(x, levels.0), too_deep(y, levels.1));
+    if !deep_x && !deep_y {
+        return u.exec(ctx, Some(x), y).context("u inside L:");
+    }
+    let (shape, pairs) = pair_up(x, deep_x, y, deep_y)?;
+    let results = pairs
+        .iter()
+        .map(|(x, y)| at_levels(ctx, u, levels, x, y))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(JArray::BoxArray(ArcArrayD::from_shape_vec(
+        IxDyn(&shape),
+        results,
+    )?))
+}
+
+fn spread(
+    ctx: &mut Ctx,
+    u: &VerbImpl,
+    level: usize,
+    y: &JArray,
+    results: &mut Vec<JArray>,
+) -> Result<()> {
+    match y {
+        JArray::BoxArray(b) if too_deep(y, level) => {
+            for y in b.iter() {
+                spread(ctx, u, level, y, results)?;
+            }
+        }
+        _ => results.push(u.exec(ctx, None, y).context("u inside S:")?),
+    }
+    Ok(())
+}
+
+fn spread_dyad(
+    ctx: &mut Ctx,
+    u: &VerbImpl,
+    levels: (usize, usize),
+    x: &JArray,
+    y: &JArray,
+    results: &mut Vec<JArray>,
+) -> Result<()> {
+    let (deep_x, deep_y) = (too_deep(x, levels.0), too_deep(y, levels.1));
+    if !deep_x && !deep_y {
+        results.push(u.exec(ctx, Some(x), y).context("u inside S:")?);
+        return Ok(());
+    }
+    let (_, pairs) = pair_up(x, deep_x, y, deep_y)?;
+    for (x, y) in pairs {
+        spread_dyad(ctx, u, levels, &x, &y, results)?;
+    }
+    Ok(())
+}
+
+/// the shape the pairs came in, and the pairs
+type Pairs = (Vec<usize>, Vec<(JArray, JArray)>);
+
+/// the contents of the boxes on the deep sides, with the other side repeated to match
+fn pair_up(x: &JArray, deep_x: bool, y: &JArray, deep_y: bool) -> Result<Pairs> {
+    let contents = |a: &JArray, deep: bool| match (a, deep) {
+        (JArray::BoxArray(b), true) => (b.shape().to_vec(), b.iter().cloned().collect()),
+        _ => (Vec::new(), vec![a.clone()]),
+    };
+    let (x_shape, xs): (_, Vec<_>) = contents(x, deep_x);
+    let (y_shape, ys): (_, Vec<_>) = contents(y, deep_y);
+    if x_shape == y_shape {
+        return Ok((x_shape, xs.into_iter().zip(ys).collect()));
+    }
+    if x_shape.is_empty() {
+        return Ok((
+            y_shape,
+            ys.into_iter().map(|y| (xs[0].clone(), y)).collect(),
+        ));
+    }
+    if y_shape.is_empty() {
+        return Ok((
+            x_shape,
+            xs.into_iter().map(|x| (x, ys[0].clone())).collect(),
+        ));
+    }
+    Err(JError::LengthError).with_context(|| anyhow!("boxes of shape {x_shape:?} and {y_shape:?}"))
+}
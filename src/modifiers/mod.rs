@@ -3,6 +3,9 @@
 
 mod adverb;
 mod conj;
+mod fit;
+mod fold;
+mod level;
 
 use anyhow::{anyhow, Context, Result};
 
@@ -11,11 +14,15 @@ use crate::{Ctx, JArray, JError, Word};
 use crate::verbs::{PartialDef, PartialImpl, VerbImpl};
 pub use adverb::*;
 pub use conj::*;
+pub use fit::*;
+pub use fold::*;
+pub use level::*;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum ModifierImpl {
     Adverb(SimpleAdverb),
     OwnedAdverb(OwnedAdverb),
+    WordyAdverb(WordyAdverb),
 
     Conjunction(SimpleConjunction),
     WordyConjunction(WordyConjunction),
@@ -93,6 +100,9 @@ impl ModifierImpl {
                 ModifierImpl::OwnedAdverb(a) => {
                     (a.f)(ctx, u).with_context(|| anyhow!("u: {u:?}"))?
                 }
+                ModifierImpl::WordyAdverb(a) => (a.f)(ctx, u)
+                    .context(a.name)
+                    .with_context(|| anyhow!("u: {u:?}"))?,
                 ModifierImpl::DerivedAdverb { c, vn } => {
                     return c
                         .form_conjunction(ctx, u, vn)
@@ -119,6 +129,7 @@ impl ModifierImpl {
         use ModifierImpl::*;
         match self {
             Adverb(a) => a.name.to_string(),
+            WordyAdverb(a) => a.name.to_string(),
             Conjunction(c) => c.name.to_string(),
             WordyConjunction(w) => w.name.to_string(),
             OwnedConjunction(_c) => format!("unrepresentable conjunction"),
@@ -133,7 +144,9 @@ impl ModifierImpl {
         use ModifierImpl::*;
         Ok(match self {
             Adverb(a) => JArray::from_string(a.name),
+            WordyAdverb(a) => JArray::from_string(a.name),
             Conjunction(c) => JArray::from_string(c.name),
+            WordyConjunction(c) => JArray::from_string(c.name),
             Cor => JArray::from_string(":"),
             _ => {
                 return Err(JError::NonceError)
@@ -1,5 +1,9 @@
 mod number;
+mod primes;
 mod promote;
+mod tolerance;
 
 pub use number::{float_is_int, Num};
+pub use primes::{factorise, is_prime, next_prime, nth_prime, prev_prime, prime_pi};
 pub use promote::{infer_kind_from_boxes, Promote};
+pub use tolerance::{arrays_match, elems_cmp, elems_equal, tolerance_of, DEFAULT_TOLERANCE};
@@ -326,7 +326,7 @@ fn flip<T>((b, a): (T, T)) -> (T, T) {
     (a, b)
 }
 
-fn promo(l: Num, r: Num) -> (Num, Num) {
+pub(super) fn promo(l: Num, r: Num) -> (Num, Num) {
     use Num::*;
 
     match (&l, &r) {
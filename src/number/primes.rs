@@ -0,0 +1,193 @@
+//! Number theory behind p: and q:
+//! Small questions are answered from a (cached) sieve; large numbers are tested with
+//! Miller-Rabin and factored with Pollard's rho.
+
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use num::{BigUint, Integer};
+use num_traits::{One, ToPrimitive, Zero};
+
+use crate::JError;
+
+/// the largest number the sieve will cover
+const SIEVE_LIMIT: u64 = 1 << 28;
+
+/// numbers below this are factored entirely by trial division
+const TRIAL_LIMIT: u64 = 1 << 10;
+
+/// Miller-Rabin is deterministic with these bases for every n below 3.3e24
+const WITNESSES: [u32; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+/// every prime below some bound, grown on demand
+static PRIMES: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+/// run `f` on a sieve which covers every prime below `bound`
+fn with_sieve<T>(bound: u64, f: impl FnOnce(&[u64]) -> T) -> Result<T> {
+    if bound > SIEVE_LIMIT {
+        return Err(JError::LimitError).context("too large for the prime sieve");
+    }
+    let mut primes = PRIMES.lock().expect("unpoisoned");
+    let covered = primes.last().map(|p| p + 1).unwrap_or(0);
+    if covered < bound {
+        *primes = sieve(bound.max(covered * 2).min(SIEVE_LIMIT));
+    }
+    Ok(f(&primes))
+}
+
+/// Eratosthenes, over the odd numbers
+fn sieve(bound: u64) -> Vec<u64> {
+    let half = (bound / 2) as usize;
+    let mut composite = vec![false; half];
+    let mut i = 1;
+    while (2 * i + 1) * (2 * i + 1) < bound as usize {
+        if !composite[i] {
+            let p = 2 * i + 1;
+            for multiple in (p * p / 2..half).step_by(p) {
+                composite[multiple] = true;
+            }
+        }
+        i += 1;
+    }
+    let odd = (1..half)
+        .filter(|&i| !composite[i])
+        .map(|i| 2 * i as u64 + 1);
+    (bound > 2).then_some(2).into_iter().chain(odd).collect()
+}
+
+/// `p: n`, counting from `p: 0` = 2
+pub fn nth_prime(n: u64) -> Result<u64> {
+    // p_n < n (ln n + ln ln n) for n >= 6, counting from one
+    let count = (n + 1) as f64;
+    let bound = if n < 6 {
+        16
+    } else {
+        (count * (count.ln() + count.ln().ln())) as u64 + 1
+    };
+    with_sieve(bound, |primes| primes[n as usize])
+}
+
+/// the number of primes below `n`
+pub fn prime_pi(n: u64) -> Result<u64> {
+    with_sieve(n, |primes| primes.partition_point(|&p| p < n) as u64)
+}
+
+pub fn is_prime(n: &BigUint) -> bool {
+    if let Some(small) = n.to_u64().filter(|&n| n < TRIAL_LIMIT * TRIAL_LIMIT) {
+        return small >= 2 && (2..).take_while(|d| d * d <= small).all(|d| small % d != 0);
+    }
+    if WITNESSES.iter().any(|&w| (n % w).is_zero()) {
+        return false;
+    }
+
+    let one = BigUint::one();
+    let n_less = n - &one;
+    let shift = n_less.trailing_zeros().expect("n is odd and large") as usize;
+    let d = &n_less >> shift;
+    WITNESSES.iter().all(|&w| {
+        let mut x = BigUint::from(w).modpow(&d, n);
+        if x == one || x == n_less {
+            return true;
+        }
+        for _ in 1..shift {
+            x = &x * &x % n;
+            if x == n_less {
+                return true;
+            }
+        }
+        false
+    })
+}
+
+/// the smallest prime greater than `n`
+pub fn next_prime(n: &BigUint) -> BigUint {
+    let mut candidate = n + 1u32;
+    while !is_prime(&candidate) {
+        candidate += 1u32;
+    }
+    candidate
+}
+
+/// the largest prime less than `n`, if there is one
+pub fn prev_prime(n: &BigUint) -> Option<BigUint> {
+    let mut candidate = n.clone();
+    while candidate > BigUint::from(2u32) {
+        candidate -= 1u32;
+        if is_prime(&candidate) {
+            return Some(candidate);
+        }
+    }
+    None
+}
+
+/// the prime factors of `n`, with repeats, in ascending order; none for 0 or 1
+pub fn factorise(n: &BigUint) -> Vec<BigUint> {
+    let mut factors = Vec::new();
+    let mut rest = n.clone();
+    if rest.is_zero() {
+        return factors;
+    }
+    for d in 2..TRIAL_LIMIT {
+        while (&rest % d).is_zero() {
+            factors.push(BigUint::from(d));
+            rest /= d;
+        }
+    }
+
+    let mut pending = vec![rest];
+    while let Some(n) = pending.pop() {
+        if n.is_one() {
+            continue;
+        }
+        if is_prime(&n) {
+            factors.push(n);
+            continue;
+        }
+        let d = rho(&n);
+        pending.push(&n / &d);
+        pending.push(d);
+    }
+    factors.sort();
+    factors
+}
+
+/// some non-trivial divisor of the (odd, composite) `n`, by Pollard's rho
+fn rho(n: &BigUint) -> BigUint {
+    // gcds are taken over a batch of steps, and the batch is replayed if it overshot
+    const BATCH: usize = 64;
+    for c in 1u32.. {
+        let step = |x: &BigUint| (x * x + c) % n;
+        let distance = |x: &BigUint, y: &BigUint| if x > y { x - y } else { y - x };
+        let (mut x, mut y) = (BigUint::from(2u32), BigUint::from(2u32));
+        loop {
+            let (saved_x, saved_y) = (x.clone(), y.clone());
+            let mut product = BigUint::one();
+            for _ in 0..BATCH {
+                x = step(&x);
+                y = step(&step(&y));
+                product = product * distance(&x, &y) % n;
+            }
+            let mut d = product.gcd(n);
+            if d.is_one() {
+                continue;
+            }
+            if &d == n {
+                (x, y) = (saved_x, saved_y);
+                for _ in 0..BATCH {
+                    x = step(&x);
+                    y = step(&step(&y));
+                    d = distance(&x, &y).gcd(n);
+                    if !d.is_one() {
+                        break;
+                    }
+                }
+            }
+            if &d != n {
+                return d;
+            }
+            // the cycle closed without a factor; start again with another polynomial
+            break;
+        }
+    }
+    unreachable!("every composite has a factor")
+}
@@ -5,13 +5,15 @@ use num::rational::BigRational;
 use num::BigInt;
 
 use super::Num;
-use crate::arrays::{Elem, JArray, JArrayKind};
+use crate::arrays::{Elem, JArray, JArrayKind, Symbol};
 
 pub fn infer_kind_from_boxes(parts: &[JArray]) -> JArrayKind {
     // priority table: https://code.jsoftware.com/wiki/Vocabulary/NumericPrecisions#Numeric_Precisions_in_J
     if parts.iter().any(|n| matches!(n, JArray::BoxArray(_))) {
         JArrayKind::Box
-    } else if parts.iter().any(|n| matches!(n, JArray::CharArray(_))) {
+    } else if parts.iter().any(|n| matches!(n, JArray::SymbolArray(_))) {
+        JArrayKind::Symbol
+    } else if parts.iter().any(|n| matches!(n, JArray::CharArray(..))) {
         JArrayKind::Char
     } else if parts.iter().any(|n| matches!(n, JArray::ComplexArray(_))) {
         JArrayKind::Complex
@@ -53,6 +55,17 @@ impl Promote for char {
     }
 }
 
+impl Promote for Symbol {
+    fn promote(value: Elem) -> Symbol {
+        use Num::*;
+        match value {
+            Elem::Symbol(s) => s,
+            Elem::Num(Bool(0)) => Symbol::default(),
+            _ => unreachable!("promotion inference error"),
+        }
+    }
+}
+
 impl Promote for Complex64 {
     fn promote(value: Elem) -> Complex64 {
         use Num::*;
@@ -0,0 +1,100 @@
+//! Tolerant comparison, which J uses for every primitive that compares numbers
+//! https://code.jsoftware.com/wiki/Essays/Tolerant_Comparison
+//!
+//! Two inexact numbers are equal if they differ by no more than the tolerance times the larger
+//! magnitude, so `0.1 + 0.2` is `0.3`. Exact numbers are only equal to themselves.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+
+use super::number::promo;
+use super::Num;
+use crate::arrays::Sparse;
+use crate::{Elem, JArray, JError};
+
+/// the tolerance J starts with, 2^-44
+pub const DEFAULT_TOLERANCE: f64 = 1. / (1u64 << 44) as f64;
+
+/// the largest tolerance J allows, 2^-34
+pub const MAX_TOLERANCE: f64 = 1. / (1u64 << 34) as f64;
+
+impl Num {
+    /// `==`, but inexact numbers within `tolerance` of the larger magnitude are equal
+    pub fn tolerant_eq(&self, other: &Num, tolerance: f64) -> bool {
+        use Num::*;
+        match promo(self.clone(), other.clone()) {
+            (Float(l), Float(r)) => l == r || (l - r).abs() <= tolerance * l.abs().max(r.abs()),
+            (Complex(l), Complex(r)) => {
+                l == r || (l - r).norm() <= tolerance * l.norm().max(r.norm())
+            }
+            (l, r) => l == r,
+        }
+    }
+
+    /// `partial_cmp`, with equality as in [`Num::tolerant_eq`]
+    pub fn tolerant_cmp(&self, other: &Num, tolerance: f64) -> Option<Ordering> {
+        match self.tolerant_eq(other, tolerance) {
+            true => Some(Ordering::Equal),
+            false => self.partial_cmp(other),
+        }
+    }
+}
+
+/// whether two atoms are equal, where numbers within `tolerance` of each other are
+pub fn elems_equal(x: &Elem, y: &Elem, tolerance: f64) -> bool {
+    match (x, y) {
+        (Elem::Num(x), Elem::Num(y)) => x.tolerant_eq(y, tolerance),
+        (Elem::Boxed(x), Elem::Boxed(y)) => arrays_match(x, y, tolerance),
+        _ => x == y,
+    }
+}
+
+/// the order of two atoms, where numbers within `tolerance` of each other are equal
+pub fn elems_cmp(x: &Elem, y: &Elem, tolerance: f64) -> Option<Ordering> {
+    match (x, y) {
+        (Elem::Num(x), Elem::Num(y)) => x.tolerant_cmp(y, tolerance),
+        _ => x.partial_cmp(y),
+    }
+}
+
+/// `-:`, the same shape and tolerantly equal atoms
+pub fn arrays_match(x: &JArray, y: &JArray, tolerance: f64) -> bool {
+    if let (JArray::SparseArray(s), _) | (_, JArray::SparseArray(s)) = (x, y) {
+        return x.shape() == y.shape() && sparse_match(x, y, s.fill(), tolerance);
+    }
+    if x.shape() != y.shape() {
+        return false;
+    }
+    let (Ok(x), Ok(y)) = (x.clone().into_elems(), y.clone().into_elems()) else {
+        return false;
+    };
+    x.iter().zip(&y).all(|(x, y)| elems_equal(x, y, tolerance))
+}
+
+/// `-:` where either side is sparse, comparing the stored atoms rather than making both dense
+fn sparse_match(x: &JArray, y: &JArray, fill: &JArray, tolerance: f64) -> bool {
+    let as_sparse = |a: &JArray| match a {
+        JArray::SparseArray(s) => Some(s.clone()),
+        _ => Sparse::from_dense(a, fill.clone()).ok().map(Arc::new),
+    };
+    match (as_sparse(x), as_sparse(y)) {
+        (Some(x), Some(y)) => x.all_atoms(&y, |l, r| arrays_match(l, r, tolerance)),
+        _ => false,
+    }
+}
+
+/// a noun given as a tolerance, by `!.` or `9!:19`
+pub fn tolerance_of(n: &JArray) -> Result<f64> {
+    let tolerance = n
+        .single_math_num()
+        .and_then(|t| t.approx_f64())
+        .ok_or(JError::DomainError)
+        .context("a tolerance is a number")?;
+    match (0. ..=MAX_TOLERANCE).contains(&tolerance) {
+        true => Ok(tolerance),
+        false => Err(JError::DomainError)
+            .with_context(|| anyhow!("{tolerance} isn't a tolerance between 0 and 2^_34")),
+    }
+}
@@ -13,7 +13,6 @@ use crate::{primitive_adverbs, primitive_conjunctions, primitive_nouns, primitiv
 use crate::verbs::VerbImpl;
 use litnum::scan_litnumarray;
 pub use litnum::scan_num_token;
-use JArray::*;
 use Word::*;
 
 type Pos = (usize, usize);
@@ -36,6 +35,7 @@ pub fn scan_with_locations(sentence: &str) -> Result<Vec<(Pos, Word)>> {
         );
         loc_off += line.len();
         words.push(((loc_off, loc_off), Word::NewLine));
+        loc_off += 1;
     }
     let _ = words.pop();
     Ok(words)
@@ -163,7 +163,7 @@ fn scan_litstring(sentence: &str) -> Result<(usize, Word)> {
     if s.len() == 1 {
         Ok((
             l,
-            Noun(CharArray(ArcArrayD::from_elem(
+            Noun(JArray::from_chars(ArcArrayD::from_elem(
                 IxDyn(&[]),
                 s.chars().next().unwrap(),
             ))),
@@ -183,11 +183,12 @@ fn scan_name(sentence: &str) -> Result<(usize, Word)> {
     let base: String = it
         .peeking_take_while(|c| matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '_'))
         .collect();
-    let suffix = it.peek().filter(|c| matches!(c, '.' | ':')).copied();
+    let suffix: String = it.peeking_take_while(|c| matches!(c, '.' | ':')).collect();
 
-    if let Some(suffix) = suffix {
-        if let Some(primitive) = str_to_primitive(&format!("{base}{suffix}"))? {
-            return Ok((base.len(), primitive));
+    // the longest inflection which makes a primitive, e.g. `F:.` rather than `F:`
+    for len in (1..=suffix.len()).rev() {
+        if let Some(primitive) = str_to_primitive(&format!("{base}{}", &suffix[..len]))? {
+            return Ok((base.len() + len - 1, primitive));
         }
     }
 
@@ -264,9 +265,12 @@ pub fn str_to_primitive(sentence: &str) -> Result<Option<Word>> {
             "end." => Word::End,
             "select." => Word::Select,
             "case." => Word::Case,
+            "fcase." => Word::FCase,
             "for." => Word::For(None),
             "while." => Word::While,
             "whilst." => Word::Whilst,
+            "break." => Word::Break,
+            "continue." => Word::Continue,
             "assert." => Word::Assert,
             "try." => Word::Try,
             "catch." => Word::Catch,
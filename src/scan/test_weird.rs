@@ -83,7 +83,7 @@ fn test_scan_atoms() -> Result<()> {
     let words = scan("'a'")?;
     assert_eq!(
         words,
-        [Word::Noun(JArray::CharArray(ArcArrayD::from_elem(
+        [Word::Noun(JArray::from_chars(ArcArrayD::from_elem(
             IxDyn(&[]),
             'a'
         )))]
@@ -158,7 +158,7 @@ fn test_scan_primitives() -> Result<()> {
         [
             primitive_nouns("a.").unwrap(),
             Word::static_verb("I."),
-            Word::Noun(JArray::CharArray(ArcArrayD::from_elem(IxDyn(&[]), 'A')))
+            Word::Noun(JArray::from_chars(ArcArrayD::from_elem(IxDyn(&[]), 'A')))
         ]
     );
     Ok(())
@@ -172,7 +172,7 @@ fn test_scan_primitives_not_spaced() -> Result<()> {
         [
             primitive_nouns("a.").unwrap(),
             Word::static_verb("I."),
-            Word::Noun(JArray::CharArray(ArcArrayD::from_elem(IxDyn(&[]), 'A')))
+            Word::Noun(JArray::from_chars(ArcArrayD::from_elem(IxDyn(&[]), 'A')))
         ]
     );
     Ok(())
@@ -1,5 +1,4 @@
-use crate::test_impls::jsoft_binary;
-use crate::test_impls::jsoft_binary::parse_hex;
+use crate::foreign::binary;
 use crate::JArray;
 use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
@@ -36,7 +35,7 @@ fn capture(expr: impl AsRef<str>) -> Result<Run> {
         // most of the values are like "e800000000000000". toml lists are very verbose.
         // strip the trailing zeros and just join them with a comma
         .map(|x| {
-            if parse_hex(x).is_ok() {
+            if x.len() == 16 && binary::from_hex(x).is_ok() {
                 Ok(x)
             } else {
                 Err(anyhow!("invalid value in j output, {x:?}"))
@@ -117,13 +116,17 @@ impl Lookup for HashMap<String, (JArray, String)> {
 
 impl Run {
     pub fn parse_encoded(&self) -> Result<JArray> {
-        jsoft_binary::decode(
+        binary::decode(&self.encoded_bytes()?)
+    }
+
+    pub fn encoded_bytes(&self) -> Result<Vec<u8>> {
+        binary::from_hex(
             &self
                 .encoded
                 .replace('\n', "")
                 .split(',')
-                .map(|s| jsoft_binary::parse_hex(&format!("{s:0<16}")))
-                .collect::<Result<Vec<u64>>>()?,
+                .map(|s| format!("{s:0<16}"))
+                .join(""),
         )
     }
 }
@@ -142,3 +145,34 @@ fn insert_debug(expr: impl AsRef<str>) -> Result<String> {
     *last_line = format!("3!:3 ] {last_line}");
     Ok(content.join("\n"))
 }
+
+#[test]
+fn floats() {
+    let f = |hex: &str| binary::decode(&binary::from_hex(hex).unwrap()).unwrap();
+    let header = "e300000000000000080000000000000001000000000000000000000000000000";
+    assert_eq!(
+        JArray::from(crate::arr0d(-1.5)),
+        f(&format!("{header}000000000000f8bf"))
+    );
+    assert_eq!(
+        JArray::from(crate::arr0d(0.15)),
+        f(&format!("{header}333333333333c33f"))
+    );
+}
+
+#[test]
+fn reencode_snippets() -> Result<()> {
+    let runs = RunList::open(include_str!("../../tests/snippets.toml"))?;
+    for run in runs.runs {
+        let bytes = run.encoded_bytes()?;
+        let arr = binary::decode(&bytes).with_context(|| anyhow!("{:?}", run.expr))?;
+        let again = binary::encode(&arr, binary::Layout::from_mode(3)?)?;
+        assert_eq!(
+            binary::to_hex(&bytes, binary::Layout::from_mode(3)?),
+            binary::to_hex(&again, binary::Layout::from_mode(3)?),
+            "{:?}",
+            run.expr
+        );
+    }
+    Ok(())
+}
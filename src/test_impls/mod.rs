@@ -1,4 +1,3 @@
-mod jsoft_binary;
 mod jsoft_runs;
 
 use std::fs;
@@ -4,10 +4,11 @@ use anyhow::{anyhow, bail, Context, Result};
 
 use super::ranks::Rank;
 use crate::cells::{apply_cells, fill_promote_reshape, generate_cells, monad_apply, monad_cells};
+use crate::eval::MaybeVerb;
 use crate::number::float_is_int;
 use crate::verbs::primitive::PrimitiveImpl;
-use crate::verbs::{DyadRank, PartialDef, PartialImpl};
-use crate::{arr0ad, primitive_verbs, Ctx, JArray, JError, Num, Word};
+use crate::verbs::{exec_sparse, DyadRank, PartialDef, PartialImpl};
+use crate::{arr0ad, Ctx, JArray, JError, Num, Word};
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum VerbImpl {
@@ -87,23 +88,86 @@ pub fn exec_dyad(
     fill_promote_reshape(r)
 }
 
+/// run `f` with `verb` as what `$:` means
+pub fn with_self<T>(
+    ctx: &mut Ctx,
+    verb: &VerbImpl,
+    f: impl FnOnce(&mut Ctx) -> Result<T>,
+) -> Result<T> {
+    ctx.selves.push(verb.clone());
+    let result = f(ctx);
+    ctx.selves.pop();
+    result
+}
+
+/// run `f` one verb deeper, counting towards the `Ctx::stack_limit`
+fn nested<T>(ctx: &mut Ctx, f: impl FnOnce(&mut Ctx) -> Result<T>) -> Result<T> {
+    if ctx.depth >= ctx.stack_limit {
+        return Err(JError::StackError)
+            .with_context(|| anyhow!("more than {} verbs deep", ctx.stack_limit));
+    }
+    ctx.depth += 1;
+    let result = f(ctx);
+    ctx.depth -= 1;
+    result
+}
+
+/// $: (monad/dyad)
+pub fn v_self_reference(ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<JArray> {
+    let verb = ctx
+        .selves
+        .last()
+        .cloned()
+        .ok_or(JError::StackError)
+        .context("$: outside of a verb refers to itself")?;
+    with_self(ctx, &verb, |ctx| verb.exec(ctx, x, y))
+}
+
 impl VerbImpl {
     pub fn exec(&self, ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<JArray> {
         fill_promote_reshape(self.partial_exec(ctx, x, y)?)
     }
 
+    /// exec, for a whole verb phrase in a sentence, which is what `$:` inside it refers to
+    pub fn exec_phrase(&self, ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<JArray> {
+        if self.is_self_reference() {
+            return self.exec(ctx, x, y);
+        }
+        with_self(ctx, self, |ctx| self.exec(ctx, x, y))
+    }
+
+    fn is_self_reference(&self) -> bool {
+        matches!(self, VerbImpl::Partial(p) if matches!(*p.def, PartialDef::Primitive("$:")))
+    }
+
     pub fn partial_exec(
         &self,
         ctx: &mut Ctx,
         x: Option<&JArray>,
         y: &JArray,
     ) -> Result<VerbResult> {
+        let takes_sparse = matches!(self, VerbImpl::Partial(p) if p.takes_sparse());
+        if (x.is_some_and(JArray::is_sparse) || y.is_sparse()) && !takes_sparse {
+            if let Some(result) = exec_sparse(self, ctx.tolerance, x, y) {
+                return Ok((Vec::new(), vec![result?]));
+            }
+            let x = x.map(JArray::to_dense).transpose()?;
+            return self.partial_exec(ctx, x.as_ref(), &y.to_dense()?);
+        }
+        match self {
+            VerbImpl::Primitive(imp) => partial_exec_primitive(imp, ctx.tolerance, x, y),
+            _ => nested(ctx, |ctx| self.compound_exec(ctx, x, y)),
+        }
+    }
+
+    /// partial_exec for everything but the primitives, which can't run other verbs
+    fn compound_exec(&self, ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<VerbResult> {
         use Word::*;
         match self {
-            VerbImpl::Primitive(imp) => partial_exec_primitive(imp, x, y),
+            VerbImpl::Primitive(imp) => partial_exec_primitive(imp, ctx.tolerance, x, y),
             VerbImpl::Partial(p) => {
                 let biv = &p.imp.biv;
-                match x {
+                let run = |ctx: &mut Ctx| match x {
                     None => exec_monad_inner(|y| biv(ctx, None, y), p.imp.ranks.0, y)
                         .with_context(|| anyhow!("y: {y:?}"))
                         .with_context(|| anyhow!("monadic partial {:?}", p.name())),
@@ -111,6 +175,11 @@ impl VerbImpl {
                         .with_context(|| anyhow!("x: {x:?}"))
                         .with_context(|| anyhow!("y: {y:?}"))
                         .with_context(|| anyhow!("dyadic partial: {}", p.name())),
+                };
+                match *p.def {
+                    // an explicit definition is what `$:` means inside it, however it was reached
+                    PartialDef::Cor(..) => with_self(ctx, self, run),
+                    _ => run(ctx),
                 }
             }
             VerbImpl::Fork { f, g, h } => match (f.deref(), g.deref(), h.deref()) {
@@ -121,11 +190,12 @@ impl VerbImpl {
                     match (f, h) {
                         (VerbImpl::Primitive(f), VerbImpl::Primitive(h)) => {
                             // f and h are primitives so execute in parallel (no global assignments to worry about)
+                            let t = ctx.tolerance;
                             crossbeam::scope(|s| {
-                                let thread_l =
-                                    s.spawn(|_| exec_primitive(f, x, y).context("fork impl (f)"));
-                                let thread_r =
-                                    s.spawn(|_| exec_primitive(h, x, y).context("fork impl (h)"));
+                                let thread_l = s
+                                    .spawn(|_| exec_primitive(f, t, x, y).context("fork impl (f)"));
+                                let thread_r = s
+                                    .spawn(|_| exec_primitive(h, t, x, y).context("fork impl (h)"));
 
                                 let nx = thread_l.join().expect("thread_l panic")?;
                                 let ny = thread_r.join().expect("thread_r panic")?;
@@ -136,9 +206,9 @@ impl VerbImpl {
                         _ => {
                             let f = match f {
                                 VerbImpl::Cap => None,
-                                _ => Some(f.exec(ctx, x, y).context("fork impl (f)").unwrap()),
+                                _ => Some(f.exec(ctx, x, y).context("fork impl (f)")?),
                             };
-                            let ny = h.exec(ctx, x, y).context("fork impl (h)").unwrap();
+                            let ny = h.exec(ctx, x, y).context("fork impl (h)")?;
 
                             g.partial_exec(ctx, f.as_ref(), &ny)
                                 .context("fork impl (g)")
@@ -151,6 +221,21 @@ impl VerbImpl {
                     let ny = h.exec(ctx, x, y)?;
                     g.partial_exec(ctx, Some(m), &ny)
                 }
+                // names are looked up each time the fork runs
+                (f, g, h) if [f, g, h].iter().any(|w| matches!(w, Name(_))) => {
+                    let resolve = |w: &Word| -> Result<Box<Word>> {
+                        Ok(Box::new(match w.when_verb() {
+                            Some(v @ MaybeVerb::Name(_)) => Verb(v.to_verb(ctx.eval())?),
+                            _ => w.clone(),
+                        }))
+                    };
+                    let fork = VerbImpl::Fork {
+                        f: resolve(f)?,
+                        g: resolve(g)?,
+                        h: resolve(h)?,
+                    };
+                    fork.partial_exec(ctx, x, y)
+                }
                 _ => panic!("invalid Fork {:?}", self),
             },
             VerbImpl::Hook { l, r } => match (l.when_verb(), r.when_verb()) {
@@ -191,11 +276,9 @@ impl VerbImpl {
         }
     }
 
-    pub fn obverse(&self) -> Option<VerbImpl> {
-        match self {
-            VerbImpl::Primitive(imp) => imp.inverse.and_then(primitive_verbs),
-            _ => None,
-        }
+    /// the verb which undoes this one, for `^:_1` and `&.`
+    pub fn obverse(&self, ctx: &mut Ctx) -> Result<VerbImpl> {
+        super::obverse::obverse(ctx, self)
     }
 
     pub fn token(&self) -> Option<&str> {
@@ -228,12 +311,14 @@ impl VerbImpl {
         use VerbImpl::*;
         Ok(match self {
             Primitive(imp) => JArray::from_string(imp.name),
-            // TODO: invalid for inf, negatives
             Number(i) => JArray::from_string(match float_is_int(*i) {
                 Some(i) if i >= 0 => format!("{i}:"),
-                Some(i) => format!("_{i}:"),
-                None => return Err(JError::NonceError).context("super lazy about infinity"),
+                Some(i) => format!("_{}:", i.abs()),
+                None if *i == f64::INFINITY => "_:".to_string(),
+                None if *i == f64::NEG_INFINITY => "__:".to_string(),
+                None => return Err(JError::DomainError).with_context(|| anyhow!("{i}: has no ar")),
             }),
+            Cap => JArray::from_string("[:"),
             Partial(PartialImpl { def, .. }) => match &**def {
                 PartialDef::Adverb(a, u) => {
                     JArray::from_list(vec![a.boxed_ar()?, JArray::from_list(vec![u.boxed_ar()?])])
@@ -242,6 +327,7 @@ impl VerbImpl {
                     c.boxed_ar()?,
                     JArray::from_list(vec![u.boxed_ar()?, v.boxed_ar()?]),
                 ]),
+                PartialDef::Primitive(name) => JArray::from_string(name),
                 PartialDef::Cor(n, def) => JArray::from_list([
                     JArray::from_string(":"),
                     JArray::from_list([
@@ -259,10 +345,6 @@ impl VerbImpl {
                 JArray::from_string("3"),
                 JArray::from_list([f.boxed_ar()?, g.boxed_ar()?, h.boxed_ar()?]),
             ]),
-            _ => {
-                return Err(JError::NonceError)
-                    .with_context(|| anyhow!("can't VerbImpl::boxed_ar {self:?}"))
-            }
         })
     }
 }
@@ -276,22 +358,35 @@ pub fn stringify(def: &[Word]) -> String {
     ret
 }
 
-fn exec_primitive(imp: &PrimitiveImpl, x: Option<&JArray>, y: &JArray) -> Result<JArray> {
-    fill_promote_reshape(partial_exec_primitive(imp, x, y)?)
+fn exec_primitive(
+    imp: &PrimitiveImpl,
+    tolerance: f64,
+    x: Option<&JArray>,
+    y: &JArray,
+) -> Result<JArray> {
+    fill_promote_reshape(partial_exec_primitive(imp, tolerance, x, y)?)
 }
 
+/// run a primitive, comparing with `tolerance` if it compares at all
 fn partial_exec_primitive(
     imp: &PrimitiveImpl,
+    tolerance: f64,
     x: Option<&JArray>,
     y: &JArray,
 ) -> Result<VerbResult> {
     match x {
-        None => exec_monad_inner(imp.monad.f, imp.monad.rank, y)
-            .with_context(|| anyhow!("y: {y:?}"))
-            .with_context(|| anyhow!("monadic {:?}", imp.name)),
-        Some(x) => exec_dyad_inner(imp.dyad.f, imp.dyad.rank, x, y)
-            .with_context(|| anyhow!("x: {x:?}"))
-            .with_context(|| anyhow!("y: {y:?}"))
-            .with_context(|| anyhow!("dyadic {:?}", imp.name)),
+        None => match imp.tolerant_monad() {
+            Some(f) => exec_monad_inner(|y| f(y, tolerance), imp.monad.rank, y),
+            None => exec_monad_inner(imp.monad.f, imp.monad.rank, y),
+        }
+        .with_context(|| anyhow!("y: {y:?}"))
+        .with_context(|| anyhow!("monadic {:?}", imp.name)),
+        Some(x) => match imp.tolerant_dyad() {
+            Some(f) => exec_dyad_inner(|x, y| f(x, y, tolerance), imp.dyad.rank, x, y),
+            None => exec_dyad_inner(imp.dyad.f, imp.dyad.rank, x, y),
+        }
+        .with_context(|| anyhow!("x: {x:?}"))
+        .with_context(|| anyhow!("y: {y:?}"))
+        .with_context(|| anyhow!("dyadic {:?}", imp.name)),
     }
 }
@@ -5,7 +5,7 @@
 use std::cmp::Ordering;
 
 use crate::arrays::IntoVec;
-use crate::number::Num;
+use crate::number::{elems_cmp, elems_equal, Num, DEFAULT_TOLERANCE};
 use crate::{JArray, JError};
 
 use anyhow::{Context, Result};
@@ -19,12 +19,20 @@ use super::maff::*;
 
 /// = (dyad)
 pub fn v_equal(x: &JArray, y: &JArray) -> Result<JArray> {
-    d00eb(x, y, |x, y| x == y)
+    equal(x, y, DEFAULT_TOLERANCE)
+}
+
+pub fn equal(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    d00eb(x, y, |x, y| elems_equal(&x, &y, tolerance))
 }
 
 /// < (dyad)
 pub fn v_less_than(x: &JArray, y: &JArray) -> Result<JArray> {
-    d00erb(x, y, |x, y| match x.partial_cmp(&y) {
+    less_than(x, y, DEFAULT_TOLERANCE)
+}
+
+pub fn less_than(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    d00erb(x, y, |x, y| match elems_cmp(&x, &y, tolerance) {
         Some(Ordering::Less) => Ok(true),
         None => Err(JError::DomainError).context("non-comparable number"),
         _ => Ok(false),
@@ -33,6 +41,11 @@ pub fn v_less_than(x: &JArray, y: &JArray) -> Result<JArray> {
 
 /// <. (monad)
 pub fn v_floor(y: &JArray) -> Result<JArray> {
+    floor(y, DEFAULT_TOLERANCE)
+}
+
+/// `<.`, where a float within `tolerance` of an integer is that integer
+pub fn floor(y: &JArray, tolerance: f64) -> Result<JArray> {
     use Num::*;
     m0nrn(y, |y| {
         Ok(match y {
@@ -40,7 +53,7 @@ pub fn v_floor(y: &JArray) -> Result<JArray> {
             Int(x) => Int(x),
             ExtInt(x) => ExtInt(x),
             Rational(x) => Rational(x.floor()),
-            Float(x) => Num::float_or_int(x.floor()),
+            Float(x) => Num::float_or_int(snap(x, tolerance).unwrap_or(x.floor())),
             Complex(_) => {
                 return Err(JError::NonceError)
                     .context("floor of a complex number is a complex subject")
@@ -48,6 +61,15 @@ pub fn v_floor(y: &JArray) -> Result<JArray> {
         })
     })
 }
+
+/// the integer tolerantly equal to `x`, if there is one
+fn snap(x: f64, tolerance: f64) -> Option<f64> {
+    let round = x.round();
+    Num::Float(round)
+        .tolerant_eq(&Num::Float(x), tolerance)
+        .then_some(round)
+}
+
 /// <. (dyad)
 pub fn v_lesser_of_min(x: &JArray, y: &JArray) -> Result<JArray> {
     d00ere(x, y, |x, y| match x.partial_cmp(&y) {
@@ -64,7 +86,11 @@ pub fn v_decrement(y: &JArray) -> Result<JArray> {
 
 /// <: (dyad)
 pub fn v_less_or_equal(x: &JArray, y: &JArray) -> Result<JArray> {
-    d00erb(x, y, |x, y| match x.partial_cmp(&y) {
+    less_or_equal(x, y, DEFAULT_TOLERANCE)
+}
+
+pub fn less_or_equal(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    d00erb(x, y, |x, y| match elems_cmp(&x, &y, tolerance) {
         Some(Ordering::Less) => Ok(true),
         Some(Ordering::Equal) => Ok(true),
         None => Err(JError::DomainError).context("non-comparable number"),
@@ -74,7 +100,11 @@ pub fn v_less_or_equal(x: &JArray, y: &JArray) -> Result<JArray> {
 
 /// > (dyad)
 pub fn v_larger_than(x: &JArray, y: &JArray) -> Result<JArray> {
-    d00erb(x, y, |x, y| match x.partial_cmp(&y) {
+    larger_than(x, y, DEFAULT_TOLERANCE)
+}
+
+pub fn larger_than(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    d00erb(x, y, |x, y| match elems_cmp(&x, &y, tolerance) {
         Some(Ordering::Greater) => Ok(true),
         None => Err(JError::DomainError).context("non-comparable number"),
         _ => Ok(false),
@@ -83,6 +113,11 @@ pub fn v_larger_than(x: &JArray, y: &JArray) -> Result<JArray> {
 
 /// >. (monad)
 pub fn v_ceiling(y: &JArray) -> Result<JArray> {
+    ceiling(y, DEFAULT_TOLERANCE)
+}
+
+/// `>.`, where a float within `tolerance` of an integer is that integer
+pub fn ceiling(y: &JArray, tolerance: f64) -> Result<JArray> {
     use Num::*;
     m0nrn(y, |y| {
         Ok(match y {
@@ -90,7 +125,7 @@ pub fn v_ceiling(y: &JArray) -> Result<JArray> {
             Int(x) => Int(x),
             ExtInt(x) => ExtInt(x),
             Rational(x) => Rational(x.ceil()),
-            Float(x) => Num::float_or_int(x.ceil()),
+            Float(x) => Num::float_or_int(snap(x, tolerance).unwrap_or(x.ceil())),
             Complex(_) => {
                 return Err(JError::NonceError)
                     .context("ceil of a complex number is a complex subject")
@@ -115,7 +150,11 @@ pub fn v_increment(y: &JArray) -> Result<JArray> {
 
 /// >: (dyad)
 pub fn v_larger_or_equal(x: &JArray, y: &JArray) -> Result<JArray> {
-    d00erb(x, y, |x, y| match x.partial_cmp(&y) {
+    larger_or_equal(x, y, DEFAULT_TOLERANCE)
+}
+
+pub fn larger_or_equal(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    d00erb(x, y, |x, y| match elems_cmp(&x, &y, tolerance) {
         Some(Ordering::Greater) => Ok(true),
         Some(Ordering::Equal) => Ok(true),
         None => Err(JError::DomainError).context("non-comparable number"),
@@ -265,16 +304,6 @@ pub fn v_divide(x: &JArray, y: &JArray) -> Result<JArray> {
     d00nrn(x, y, |x, y| Ok(x / y))
 }
 
-/// %. (monad)
-pub fn v_matrix_inverse(_y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
-}
-
-/// %. (dyad)
-pub fn v_matrix_divide(_x: &JArray, _y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
-}
-
 /// %: (monad)
 pub fn v_square_root(y: &JArray) -> Result<JArray> {
     use Num::*;
@@ -292,9 +321,14 @@ pub fn v_square_root(y: &JArray) -> Result<JArray> {
 }
 
 /// %: (dyad)
-pub fn v_root(_x: &JArray, _y: &JArray) -> Result<JArray> {
-    // weird promotion rules here; 2 %: 16 is 4. (float), 2x %: 16 is 4x (extended)
-    Err(JError::NonceError.into())
+pub fn v_root(x: &JArray, y: &JArray) -> Result<JArray> {
+    // TODO: weird promotion rules here; 2 %: 16 is 4. (float), 2x %: 16 is 4x (extended)
+    d00nrn(x, y, |x, y| match (x.approx_f64(), y.approx_f64()) {
+        (Some(x), Some(y)) if y >= 0. => Ok(Num::Float(y.powf(x.recip()))),
+        // odd roots of negative numbers are real
+        (Some(x), Some(y)) if x.rem_euclid(2.) == 1. => Ok(Num::Float(-(-y).powf(x.recip()))),
+        _ => Err(JError::NonceError).context("roots of negative numbers are complex"),
+    })
 }
 
 /// ^ (monad)
@@ -319,6 +353,20 @@ pub fn v_power(x: &JArray, y: &JArray) -> Result<JArray> {
     })
 }
 
+/// `x ^!.s y`, the stope: y factors, starting at x, each s more than the last
+pub fn stope(x: &JArray, y: &JArray, s: &Num) -> Result<JArray> {
+    d00nrn(x, y, |x, y| {
+        let n = y
+            .value_i64()
+            .filter(|n| *n >= 0)
+            .ok_or(JError::DomainError)
+            .context("a stope has a whole number of factors")?;
+        Ok((0..n).fold(Num::one(), |acc, k| {
+            acc * (x.clone() + Num::Int(k) * s.clone())
+        }))
+    })
+}
+
 /// ^. (monad)
 pub fn v_natural_log(y: &JArray) -> Result<JArray> {
     m0nrn(y, |y| {
@@ -339,7 +387,11 @@ pub fn v_logarithm(x: &JArray, y: &JArray) -> Result<JArray> {
 
 /// ~: (dyad)
 pub fn v_not_equal(x: &JArray, y: &JArray) -> Result<JArray> {
-    d00eb(x, y, |x, y| x != y)
+    not_equal(x, y, DEFAULT_TOLERANCE)
+}
+
+pub fn not_equal(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    d00eb(x, y, |x, y| !elems_equal(&x, &y, tolerance))
 }
 
 /// | (monad)
@@ -404,16 +456,6 @@ pub fn v_deal_fixed_seed(_x: &JArray, _y: &JArray) -> Result<JArray> {
     Err(JError::NonceError.into())
 }
 
-/// q: (monad)
-pub fn v_prime_factors(_y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
-}
-
-/// q: (dyad)
-pub fn v_prime_exponents(_x: &JArray, _y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
-}
-
 /// r. (monad)
 pub fn v_angle(_y: &JArray) -> Result<JArray> {
     Err(JError::NonceError.into())
@@ -0,0 +1,209 @@
+//! %. (matrix inverse and matrix divide)
+//! Inexact numbers are solved by QR decomposition, which also gives least squares fits for
+//! overdetermined systems. Rationals and extended integers are solved exactly, by elimination.
+
+use std::ops::{Add, Mul};
+
+use anyhow::{Context, Result};
+use ndarray::prelude::*;
+use num::complex::Complex64;
+use num::BigRational;
+use num_traits::{One, Zero};
+
+use crate::{arr0d, JArray, JError};
+
+/// how small (relative to the largest element) a pivot can be before the matrix is singular
+const SINGULAR: f64 = 1e-14;
+
+/// %. (monad)
+pub fn v_matrix_inverse(y: &JArray) -> Result<JArray> {
+    // the inverse is whatever divides the identity matrix
+    let identity = match y.shape().first() {
+        None => JArray::from(arr0d(1u8)),
+        Some(&n) => Array2::from_shape_fn((n, n), |(i, j)| u8::from(i == j))
+            .into_dyn()
+            .into(),
+    };
+    v_matrix_divide(&identity, y)
+}
+
+/// %. (dyad)
+pub fn v_matrix_divide(x: &JArray, y: &JArray) -> Result<JArray> {
+    // scalars and lists act as a single column; x is split into a row for each row of y
+    let (rows, cols) = match y.shape() {
+        [] => (1, 1),
+        [m] => (*m, 1),
+        [m, n] => (*m, *n),
+        _ => return Err(JError::RankError).context("matrix divide needs a table"),
+    };
+    if rows < cols {
+        return Err(JError::DomainError).context("matrix divide needs at least as many rows");
+    }
+    let x_rest = match (y.shape(), x.shape()) {
+        ([], rest) => rest,
+        (_, []) => &[],
+        (_, [m, rest @ ..]) if *m == rows => rest,
+        _ => return Err(JError::LengthError).context("x and y must have the same number of rows"),
+    };
+    let width = x_rest.iter().product::<usize>();
+    let out_shape = y
+        .shape()
+        .iter()
+        .skip(1)
+        .chain(x_rest)
+        .copied()
+        .collect::<Vec<_>>();
+
+    Ok(match Field::of(x).max(Field::of(y)) {
+        Field::Unsupported => {
+            return Err(JError::DomainError).context("matrix divide needs numbers")
+        }
+        Field::Exact => {
+            let a = matrix(y.to_rat().expect("exact"), (rows, cols))?;
+            let b = matrix(x.to_rat().expect("exact"), (rows, width))?;
+            exact_solve(a, b)?.into_shape(out_shape)?.into()
+        }
+        field => {
+            let a = matrix(y.to_c64().expect("numeric"), (rows, cols))?;
+            let b = matrix(x.to_c64().expect("numeric"), (rows, width))?;
+            let solved = qr_solve(a, b)?.into_shape(out_shape)?;
+            match field {
+                Field::Complex => solved.into(),
+                _ => solved.mapv(|v| v.re).into(),
+            }
+        }
+    })
+}
+
+/// what sort of arithmetic the solution needs; the larger of x's and y's wins
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Field {
+    /// integers go along with anything else, but are solved as reals on their own
+    Integral,
+    Exact,
+    Real,
+    Complex,
+    Unsupported,
+}
+
+impl Field {
+    fn of(arr: &JArray) -> Field {
+        use JArray::*;
+        match arr {
+            ExtIntArray(_) | RationalArray(_) => Field::Exact,
+            BoolArray(_) | IntArray(_) => Field::Integral,
+            FloatArray(_) => Field::Real,
+            ComplexArray(_) => Field::Complex,
+            CharArray(..) | SymbolArray(_) | BoxArray(_) | SparseArray(_) => Field::Unsupported,
+        }
+    }
+}
+
+fn matrix<T: Clone>(arr: CowArray<T, IxDyn>, shape: (usize, usize)) -> Result<Array2<T>> {
+    Ok(arr.into_owned().into_shape(shape)?)
+}
+
+/// solve a x = b, in the least squares sense if a is tall, by Householder reflections
+fn qr_solve(mut a: Array2<Complex64>, mut b: Array2<Complex64>) -> Result<Array2<Complex64>> {
+    let cols = a.ncols();
+    let scale = a.iter().map(|v| v.norm()).fold(0., f64::max);
+    for k in 0..cols {
+        // reflect column k's tail onto its diagonal, leaving alpha there
+        let norm = a
+            .slice(s![k.., k])
+            .iter()
+            .map(|v| v.norm_sqr())
+            .sum::<f64>()
+            .sqrt();
+        if norm <= scale * SINGULAR {
+            return Err(JError::DomainError).context("singular matrix");
+        }
+        let head = a[(k, k)];
+        let phase = if head.is_zero() {
+            Complex64::one()
+        } else {
+            head / head.norm()
+        };
+        let mut v = a.slice(s![k.., k]).to_owned();
+        v[0] += phase * norm;
+        let v_norm = v.iter().map(|v| v.norm_sqr()).sum::<f64>().sqrt();
+        v.mapv_inplace(|v| v / v_norm);
+        reflect(&v, a.slice_mut(s![k.., k..]));
+        reflect(&v, b.slice_mut(s![k.., ..]));
+    }
+
+    // a is now upper triangular (in its first `cols` rows); back substitute
+    let mut x = Array2::zeros((cols, b.ncols()));
+    for i in (0..cols).rev() {
+        for c in 0..b.ncols() {
+            let known = (i + 1..cols)
+                .map(|j| a[(i, j)] * x[(j, c)])
+                .sum::<Complex64>();
+            x[(i, c)] = (b[(i, c)] - known) / a[(i, i)];
+        }
+    }
+    Ok(x)
+}
+
+/// apply the reflection `I - 2 v v*` to every column of m
+fn reflect(v: &Array1<Complex64>, mut m: ArrayViewMut2<Complex64>) {
+    for mut col in m.columns_mut() {
+        let dot = v
+            .iter()
+            .zip(col.iter())
+            .map(|(v, c)| v.conj() * c)
+            .sum::<Complex64>();
+        col.zip_mut_with(v, |c, v| *c -= v * dot * 2.);
+    }
+}
+
+/// solve a x = b exactly, through the normal equations if a is tall
+fn exact_solve(a: Array2<BigRational>, b: Array2<BigRational>) -> Result<Array2<BigRational>> {
+    let (mut a, mut b) = if a.is_square() {
+        (a, b)
+    } else {
+        let at = a.t();
+        (mat_mul(at, a.view()), mat_mul(at, b.view()))
+    };
+
+    // Gauss-Jordan elimination, leaving the identity in a and the answer in b
+    let n = a.nrows();
+    for k in 0..n {
+        let Some(p) = (k..n).find(|&r| !a[(r, k)].is_zero()) else {
+            return Err(JError::DomainError).context("singular matrix");
+        };
+        for c in 0..n {
+            a.swap((k, c), (p, c));
+        }
+        for c in 0..b.ncols() {
+            b.swap((k, c), (p, c));
+        }
+
+        let pivot = a[(k, k)].clone();
+        a.row_mut(k).mapv_inplace(|v| v / &pivot);
+        b.row_mut(k).mapv_inplace(|v| v / &pivot);
+        let (a_k, b_k) = (a.row(k).to_owned(), b.row(k).to_owned());
+        for r in (0..n).filter(|&r| r != k) {
+            let factor = a[(r, k)].clone();
+            if factor.is_zero() {
+                continue;
+            }
+            a.row_mut(r).zip_mut_with(&a_k, |v, p| *v -= &factor * p);
+            b.row_mut(r).zip_mut_with(&b_k, |v, p| *v -= &factor * p);
+        }
+    }
+    Ok(b)
+}
+
+fn mat_mul<T>(a: ArrayView2<T>, b: ArrayView2<T>) -> Array2<T>
+where
+    T: Clone + Zero + Add<Output = T>,
+    for<'t> &'t T: Mul<&'t T, Output = T>,
+{
+    Array2::from_shape_fn((a.nrows(), b.ncols()), |(i, j)| {
+        a.row(i)
+            .iter()
+            .zip(b.column(j))
+            .fold(T::zero(), |acc, (l, r)| acc + l * r)
+    })
+}
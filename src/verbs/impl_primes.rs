@@ -0,0 +1,191 @@
+//! p: and q:, over the prime machinery in `number::primes`
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use num::{BigInt, BigUint};
+use num_traits::{FromPrimitive, One, Signed, ToPrimitive, Zero};
+
+use crate::number::{factorise, is_prime, next_prime, nth_prime, prev_prime, prime_pi, Num};
+use crate::{JArray, JError};
+
+use super::maff::*;
+
+/// p: (monad)
+pub fn v_primes(y: &JArray) -> Result<JArray> {
+    m0nrn(y, |y| {
+        let n = integer(&y)
+            .and_then(|n| n.to_u64())
+            .ok_or(JError::DomainError)
+            .context("p: needs a non-negative integer")?;
+        Ok(int_like(&y, nth_prime(n)?.into()))
+    })
+}
+
+/// p: (dyad)
+pub fn v_prime_functions(x: &JArray, y: &JArray) -> Result<JArray> {
+    let mode = x
+        .approx_i64_one()
+        .context("p: needs an integer mode for x")?;
+    let y_num = y
+        .single_math_num()
+        .ok_or(JError::DomainError)
+        .context("p: needs a number for y")?;
+    Ok(match mode {
+        // previous prime
+        -4 => {
+            let below = natural(&ceil(&y_num)?).and_then(|n| prev_prime(&n));
+            int_like(
+                &y_num,
+                below
+                    .ok_or(JError::DomainError)
+                    .context("no smaller prime")?,
+            )
+        }
+        // the number of primes below y
+        -1 => {
+            let n = ceil(&y_num)?.max(BigInt::zero()).to_u64();
+            Num::Int(prime_pi(n.ok_or(JError::LimitError)?)? as i64)
+        }
+        0 | 1 => {
+            let prime = integer(&y_num)
+                .and_then(|n| natural(&n))
+                .is_some_and(|n| is_prime(&n));
+            Num::bool(prime == (mode == 1))
+        }
+        2 => return prime_table(&y_num),
+        3 => return v_prime_factors(y),
+        // next prime
+        4 => {
+            let floor = natural(&floor(&y_num)?).unwrap_or_default();
+            int_like(&y_num, next_prime(&floor))
+        }
+        // totient
+        5 => {
+            let n = integer(&y_num)
+                .and_then(|n| natural(&n))
+                .ok_or(JError::DomainError)
+                .context("totient needs a non-negative integer")?;
+            let totient = factorise(&n)
+                .into_iter()
+                .dedup_with_count()
+                .map(|(count, p)| (&p - 1u32) * p.pow(count as u32 - 1))
+                .fold(BigUint::one(), |acc, t| acc * t);
+            int_like(&y_num, if n.is_zero() { n } else { totient })
+        }
+        _ => return Err(JError::DomainError).with_context(|| anyhow!("p: has no mode {mode}")),
+    }
+    .into())
+}
+
+/// q: (monad)
+pub fn v_prime_factors(y: &JArray) -> Result<JArray> {
+    let y = y
+        .single_math_num()
+        .ok_or(JError::DomainError)
+        .context("q: needs a number")?;
+    Ok(int_list(&y, factorise(&positive(&y)?)))
+}
+
+/// q: (dyad)
+pub fn v_prime_exponents(x: &JArray, y: &JArray) -> Result<JArray> {
+    let x = x
+        .single_math_num()
+        .ok_or(JError::DomainError)
+        .context("q: needs a number for x")?;
+    let y = y
+        .single_math_num()
+        .ok_or(JError::DomainError)
+        .context("q: needs a number for y")?;
+    let factors = factorise(&positive(&y)?);
+
+    // the exponents of the first `count` primes
+    let count = match x.approx_f64() {
+        Some(f) if f == f64::NEG_INFINITY => return prime_table(&y),
+        Some(f) if f == f64::INFINITY => match factors.last() {
+            Some(largest) => prime_pi(largest.to_u64().ok_or(JError::LimitError)? + 1)?,
+            None => 0,
+        },
+        _ => x
+            .value_i64()
+            .and_then(|x| u64::try_from(x).ok())
+            .ok_or(JError::DomainError)
+            .context("q: needs a non-negative integer or infinity for x")?,
+    };
+    let exponents = (0..count)
+        .map(|i| {
+            let p = BigUint::from(nth_prime(i)?);
+            Ok(factors.iter().filter(|&f| f == &p).count() as i64)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(JArray::from_list(exponents))
+}
+
+/// `2 p: y`: the distinct prime factors of y, over their exponents
+fn prime_table(y: &Num) -> Result<JArray> {
+    let (exponents, primes): (Vec<_>, Vec<_>) = factorise(&positive(y)?)
+        .into_iter()
+        .dedup_with_count()
+        .map(|(count, p)| (count as i64, p))
+        .unzip();
+    JArray::from_fill_promote([int_list(y, primes), JArray::from_list(exponents)])
+}
+
+/// an integer argument, however it's stored; None for fractions
+fn integer(y: &Num) -> Option<BigInt> {
+    match y {
+        Num::ExtInt(i) => Some(i.clone()),
+        Num::Rational(r) if r.is_integer() => Some(r.numer().clone()),
+        other => other.value_i64().map(BigInt::from),
+    }
+}
+
+fn natural(n: &BigInt) -> Option<BigUint> {
+    n.to_biguint()
+}
+
+fn positive(y: &Num) -> Result<BigUint> {
+    integer(y)
+        .filter(|n| n.is_positive())
+        .and_then(|n| natural(&n))
+        .ok_or(JError::DomainError)
+        .context("factorisation needs a positive integer")
+}
+
+fn floor(y: &Num) -> Result<BigInt> {
+    match y {
+        Num::Rational(r) => Ok(r.floor().to_integer()),
+        Num::Float(f) => BigInt::from_f64(f.floor())
+            .ok_or(JError::DomainError)
+            .context("needs a finite number"),
+        other => integer(other)
+            .ok_or(JError::DomainError)
+            .context("needs a real number"),
+    }
+}
+
+fn ceil(y: &Num) -> Result<BigInt> {
+    let floor = floor(y)?;
+    Ok(match integer(y) {
+        Some(_) => floor,
+        None => floor + 1,
+    })
+}
+
+/// `v`, extended if `y` was, or if it has to be
+fn int_like(y: &Num, v: BigUint) -> Num {
+    let v = BigInt::from(v);
+    match (y, v.to_i64()) {
+        (Num::ExtInt(_) | Num::Rational(_), _) | (_, None) => Num::ExtInt(v),
+        (_, Some(i)) => Num::Int(i),
+    }
+}
+
+fn int_list(y: &Num, vs: Vec<BigUint>) -> JArray {
+    let small = vs.iter().map(|v| v.to_i64()).collect::<Option<Vec<_>>>();
+    match (y, small) {
+        (Num::ExtInt(_) | Num::Rational(_), _) | (_, None) => {
+            JArray::from_list(vs.into_iter().map(BigInt::from).collect_vec())
+        }
+        (_, Some(small)) => JArray::from_list(small),
+    }
+}
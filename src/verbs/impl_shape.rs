@@ -11,11 +11,13 @@ use ndarray::prelude::*;
 use ndarray::{concatenate, Axis, Slice};
 
 use crate::arrays::{len_of_0, ArcArrayD, IntoVec};
+use crate::cells::fill_promote_reshape_with;
 use crate::number::Num;
 use crate::{arr0ad, impl_array, impl_homo, JArray, JError};
 
 pub fn reshape(shape: &[usize], arr: &JArray) -> Result<JArray> {
-    impl_array!(arr, |arr| reshape_nd(&shape, arr).map(|x| x.into()))
+    impl_array!(arr, |arr| reshape_nd(&shape, arr).map(JArray::from))
+        .map(|r| r.keep_char_width(arr))
 }
 
 pub fn reshape_nd<T>(shape: &[usize], arr: &ArcArrayD<T>) -> Result<ArcArrayD<T>>
@@ -63,8 +65,39 @@ pub fn v_transpose(y: &JArray) -> Result<JArray> {
 }
 
 /// |: (dyad) (1, _)
-pub fn v_transpose_dyad(_x: &JArray, _y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError).context("transpose dyad")
+pub fn v_transpose_dyad(x: &JArray, y: &JArray) -> Result<JArray> {
+    if matches!(x, JArray::BoxArray(_)) {
+        return Err(JError::NonceError).context("transpose dyad with boxed axes");
+    }
+    let rank = y.shape().len();
+    let moved = x
+        .approx_i64_list()
+        .context("transpose takes a list of axes")?
+        .into_iter()
+        .map(|a| {
+            let axis = if a < 0 { a + rank as i64 } else { a };
+            usize::try_from(axis)
+                .ok()
+                .filter(|&axis| axis < rank)
+                .ok_or(JError::IndexError)
+                .with_context(|| anyhow!("no axis {a} in an array of rank {rank}"))
+        })
+        .collect::<Result<Vec<usize>>>()?;
+    if !moved.iter().all_unique() {
+        return Err(JError::IndexError).context("an axis is repeated");
+    }
+    // the axes in x move to the end, in the order given
+    let perm = (0..rank)
+        .filter(|a| !moved.contains(a))
+        .chain(moved.iter().copied())
+        .collect_vec();
+    match y {
+        JArray::SparseArray(s) => Ok(s.permute(&perm)?.into()),
+        _ => impl_array!(y, |a: &ArcArrayD<_>| Ok(a
+            .clone()
+            .permuted_axes(IxDyn(&perm))
+            .into())),
+    }
 }
 
 /// $ (monad)
@@ -79,17 +112,31 @@ pub fn v_shape_of(y: &JArray) -> Result<JArray> {
 
 /// $ (dyad) (1 _)
 pub fn v_shape(x: &JArray, y: &JArray) -> Result<JArray> {
+    shape(x, y, None)
+}
+
+/// `$`, padding with the atom `fill` once y runs out, rather than starting y again
+pub fn shape(x: &JArray, y: &JArray, fill: Option<&JArray>) -> Result<JArray> {
     let x = x.approx_i64_list().context("reshape takes an int list")?;
     if x.iter().product::<i64>() < 0 {
-        Err(JError::DomainError).context("cannot reshape to negative shapes")
-    } else {
-        debug!("v_shape: x: {x:?}, y: {y}");
-        reshape(
-            &x.iter()
-                .map(|x| Ok(usize::try_from(*x)?))
-                .collect::<Result<Vec<_>>>()?,
-            y,
-        )
+        return Err(JError::DomainError).context("cannot reshape to negative shapes");
+    }
+    debug!("v_shape: x: {x:?}, y: {y}");
+    let x = x
+        .iter()
+        .map(|x| Ok(usize::try_from(*x)?))
+        .collect::<Result<Vec<_>>>()?;
+    let wanted = x.iter().product::<usize>();
+    match fill {
+        Some(fill) if wanted > 0 => {
+            let y = y.clone().atom_to_singleton();
+            let items = y
+                .outer_iter()?
+                .chain(iter::repeat(fill.clone()))
+                .take(wanted);
+            fill_promote_reshape_with((x, items.collect()), Some(fill))
+        }
+        _ => reshape(&x, y),
     }
 }
 
@@ -124,17 +171,25 @@ pub fn v_append(x: &JArray, y: &JArray) -> Result<JArray> {
     let x = x.rank_extend(target_rank);
     let y = y.rank_extend(target_rank);
 
-    JArray::from_fill_promote(x.outer_iter().chain(y.outer_iter()))
+    let items = x.outer_iter()?.chain(y.outer_iter()?).collect_vec();
+    JArray::from_fill_promote(items)
 }
 
 /// ,. (dyad)
 pub fn v_stitch(x: &JArray, y: &JArray) -> Result<JArray> {
-    let items = x
-        .outer_iter()
-        .zip(y.outer_iter())
-        .map(|(x, y)| v_append(&x, &y))
-        .collect::<Result<Vec<_>>>()?;
-    JArray::from_fill_promote(items)
+    // an atom is stitched onto every item of the other side
+    let items: Result<Vec<_>> = match (x.shape().is_empty(), y.shape().is_empty()) {
+        (true, true) => return v_append(x, y),
+        (true, false) => y.outer_iter()?.map(|y| v_append(x, &y)).collect(),
+        (false, true) => x.outer_iter()?.map(|x| v_append(&x, y)).collect(),
+        (false, false) if x.len_of_0() == y.len_of_0() => x
+            .outer_iter()?
+            .zip(y.outer_iter()?)
+            .map(|(x, y)| v_append(&x, &y))
+            .collect(),
+        _ => return Err(JError::LengthError).context("stitch needs the same number of items"),
+    };
+    JArray::from_fill_promote(items?)
 }
 
 /// ; (dyad) (_, _)
@@ -205,8 +260,9 @@ pub fn v_copy(x: &JArray, y: &JArray) -> Result<JArray> {
                     .with_context(|| anyhow!("push: {y:?})"))?;
             }
         }
-        Ok(output.into())
+        Ok(JArray::from(output))
     })
+    .map(|r| r.keep_char_width(y))
 }
 
 /// {. (monad)
@@ -223,6 +279,11 @@ pub fn v_head(y: &JArray) -> Result<JArray> {
 
 /// {. (dyad)
 pub fn v_take(x: &JArray, y: &JArray) -> Result<JArray> {
+    take(x, y, None)
+}
+
+/// `{.`, padding an overtake with the atom `fill`, rather than with zeros
+pub fn take(x: &JArray, y: &JArray, fill: Option<&JArray>) -> Result<JArray> {
     assert!(
         x.shape().len() <= 1,
         "agreement guarantee x: {:?}",
@@ -242,7 +303,7 @@ pub fn v_take(x: &JArray, y: &JArray) -> Result<JArray> {
 
     let x = x[0];
     Ok(match x.cmp(&0) {
-        Ordering::Equal => y.create_cleared(),
+        Ordering::Equal => y.create_cleared()?,
         Ordering::Less => {
             // negative x (take from right)
             let x = usize::try_from(x.abs())
@@ -250,17 +311,12 @@ pub fn v_take(x: &JArray, y: &JArray) -> Result<JArray> {
                 .context("offset doesn't fit in memory")?;
             let y_len_zero = y.len_of_0();
 
-            if x == 1 {
-                match y.shape() {
-                    [] => y.reshape(vec![x])?,
-                    _ => y.select(Axis(0), &((y_len_zero - x)..y_len_zero).collect_vec()),
-                }
-            } else {
-                if x <= y_len_zero {
-                    y.select(Axis(0), &((y_len_zero - x)..y_len_zero).collect_vec())
-                } else {
-                    return Err(JError::NonceError).context("negative overtake");
+            match y.shape() {
+                [] if x == 1 => y.reshape(vec![x])?,
+                _ if x <= y_len_zero => {
+                    y.select(Axis(0), &((y_len_zero - x)..y_len_zero).collect_vec())?
                 }
+                _ => overtake(y, x, fill, true)?,
             }
         }
         Ordering::Greater => {
@@ -270,27 +326,35 @@ pub fn v_take(x: &JArray, y: &JArray) -> Result<JArray> {
 
             if x == 1 {
                 match (y.is_empty(), y.shape()) {
-                    (true, _) => JArray::atomic_zero(),
+                    (true, _) => fill.cloned().unwrap_or_else(JArray::atomic_zero),
                     (false, []) => y.reshape(vec![x])?,
                     _ => y.slice_axis(Axis(0), Slice::from(..1usize))?,
                 }
             } else {
                 let y_len_zero = y.len_of_0();
                 if x <= y_len_zero {
-                    y.select(Axis(0), &(0..x).collect_vec())
+                    y.select(Axis(0), &(0..x).collect_vec())?
                 } else {
-                    JArray::from_fill_promote(
-                        y.outer_iter()
-                            // we can't use empty() here as its rank is higher than arr0, which matters
-                            .chain(iter::repeat(JArray::atomic_zero()))
-                            .take(x),
-                    )?
+                    overtake(y, x, fill, false)?
                 }
             }
         }
     })
 }
 
+/// y's items, padded out to x of them, at the front if `from_end`
+fn overtake(y: &JArray, x: usize, fill: Option<&JArray>, from_end: bool) -> Result<JArray> {
+    let items = y.outer_iter()?.collect_vec();
+    // we can't use empty() here as its rank is higher than arr0, which matters
+    let pad = fill.cloned().unwrap_or_else(JArray::atomic_zero);
+    let pad = iter::repeat_n(pad, x.saturating_sub(items.len()));
+    let items = match from_end {
+        true => pad.chain(items).collect_vec(),
+        false => items.into_iter().chain(pad).collect_vec(),
+    };
+    fill_promote_reshape_with((vec![items.len()], items), fill)
+}
+
 /// {: (monad)
 pub fn v_tail(y: &JArray) -> Result<JArray> {
     let a = v_take(&JArray::from(Num::from(-1i64)), y)?;
@@ -329,7 +393,7 @@ pub fn v_fetch(x: &JArray, y: &JArray) -> Result<JArray> {
     for x in x {
         // let JArray::BoxArray(next) = here else { return Err(JError::NonceError).context("boxed component"); };
         let next = here
-            .outer_iter()
+            .outer_iter()?
             .nth(x)
             .ok_or(JError::IndexError)
             .context("path component past end")?;
@@ -350,10 +414,10 @@ pub fn v_behead(y: &JArray) -> Result<JArray> {
         return Ok(y.clone());
     }
 
-    impl_array!(y, |arr: &ArcArrayD<_>| Ok(arr
-        .slice_axis(Axis(0), Slice::from(1isize..))
-        .to_shared()
-        .into()))
+    impl_array!(y, |arr: &ArcArrayD<_>| Ok(JArray::from(
+        arr.slice_axis(Axis(0), Slice::from(1isize..)).to_shared()
+    )))
+    .map(|r| r.keep_char_width(y))
 }
 /// }. (dyad)
 pub fn v_drop(x: &JArray, y: &JArray) -> Result<JArray> {
@@ -364,7 +428,7 @@ pub fn v_drop(x: &JArray, y: &JArray) -> Result<JArray> {
     }
 
     match x {
-        CharArray(_) => Err(JError::DomainError.into()),
+        CharArray(..) => Err(JError::DomainError.into()),
         RationalArray(_) => Err(JError::DomainError.into()),
         FloatArray(_) => Err(JError::DomainError.into()),
         ComplexArray(_) => Err(JError::DomainError.into()),
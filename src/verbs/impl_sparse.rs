@@ -0,0 +1,514 @@
+//! $., and the verbs which work on sparse arrays without making them dense
+//! https://www.jsoftware.com/help/dictionary/d211.htm
+//!
+//! Everything not handled by [`exec_sparse`] sees dense copies of its arguments.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use itertools::{EitherOrBoth, Itertools};
+use ndarray::prelude::*;
+
+use crate::arrays::{ArcArrayD, Sparse};
+use crate::eval::{MaybeVerb, VerbNoun};
+use crate::modifiers::ModifierImpl;
+use crate::verbs::primitive::PrimitiveImpl;
+use crate::verbs::{
+    exec_dyad, exec_monad, match_, v_plus, v_shape, v_shape_of, v_tally, v_times, v_transpose,
+    v_transpose_dyad, PartialDef, Rank, VerbImpl,
+};
+use crate::{arr0ad, arr0d, HasEmpty, JArray, JError};
+
+/// $. (monad)
+pub fn v_sparse(y: &JArray) -> Result<JArray> {
+    match y {
+        JArray::SparseArray(_) => Ok(y.clone()),
+        _ => Ok(Sparse::from_dense(y, default_fill(y))?.into()),
+    }
+}
+
+/// $. (dyad)
+pub fn v_sparse_functions(x: &JArray, y: &JArray) -> Result<JArray> {
+    // (2;a) and (3;e) set what 2 and 3 report
+    let (mode, arg) = match x.when_box() {
+        Some(b) if b.len() == 2 => {
+            let mut b = b.iter().cloned();
+            let mode = b.next().expect("len == 2");
+            (mode, b.next())
+        }
+        _ => (x.clone(), None),
+    };
+    let mode = mode
+        .approx_i64_one()
+        .context("$. needs an integer mode for x")?;
+    match (mode, arg) {
+        (0, None) => match y {
+            JArray::SparseArray(s) => s.to_dense(),
+            _ => v_sparse(y),
+        },
+        (1, None) => new_sparse(y),
+        (2, None) => Ok(JArray::from_list(
+            (0..sparse(y)?.shape().len() as i64).collect_vec(),
+        )),
+        (2, Some(axes)) => {
+            let s = sparse(y)?;
+            check_all_axes(&axes, s.shape().len())?;
+            Ok(y.clone())
+        }
+        (3, None) => Ok(sparse(y)?.fill().clone()),
+        (3, Some(fill)) => {
+            let s = sparse(y)?;
+            if &fill == s.fill() {
+                return Ok(y.clone());
+            }
+            Ok(Sparse::from_dense(&s.to_dense()?, fill)?.into())
+        }
+        (4, None) => {
+            let s = sparse(y)?;
+            let table = s
+                .indices()
+                .iter()
+                .flatten()
+                .map(|&i| i as i64)
+                .collect_vec();
+            Ok(
+                ArcArrayD::from_shape_vec(IxDyn(&[s.indices().len(), s.shape().len()]), table)?
+                    .into(),
+            )
+        }
+        (5, None) => Ok(sparse(y)?.values().clone()),
+        (7, None) => Ok(JArray::from(arr0d(sparse(y)?.indices().len() as i64))),
+        // drop any stored atoms which are the sparse element anyway
+        (8, None) => {
+            let s = sparse(y)?;
+            let (indices, values): (Vec<_>, Vec<_>) = s
+                .entries()?
+                .filter(|(_, v)| v != s.fill())
+                .map(|(i, v)| (i.clone(), v))
+                .unzip();
+            let values = list_of(values, s.fill())?;
+            Ok(Sparse::new(s.shape().to_vec(), indices, values, s.fill().clone())?.into())
+        }
+        _ => Err(JError::DomainError).with_context(|| anyhow!("$. has no mode {x:?}")),
+    }
+}
+
+/// the result of `verb`, if it can be made without a dense copy of a sparse argument
+/// (comparing with `tolerance`)
+pub fn exec_sparse(
+    verb: &VerbImpl,
+    tolerance: f64,
+    x: Option<&JArray>,
+    y: &JArray,
+) -> Option<Result<JArray>> {
+    match verb {
+        VerbImpl::Primitive(imp) => exec_primitive(imp, tolerance, x, y),
+        VerbImpl::Partial(p) => match (&*p.def, x, y) {
+            // +/
+            (
+                PartialDef::Adverb(
+                    ModifierImpl::Adverb(a),
+                    VerbNoun::Verb(MaybeVerb::Verb(VerbImpl::Primitive(u))),
+                ),
+                None,
+                JArray::SparseArray(y),
+            ) if a.name == "/" && u.name == "+" && !y.shape().is_empty() => Some(sum(y)),
+            // x m} y
+            (
+                PartialDef::Adverb(ModifierImpl::Adverb(a), VerbNoun::Noun(m)),
+                Some(x),
+                JArray::SparseArray(y),
+            ) if a.name == "}" => amend(m, x, y),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn exec_primitive(
+    imp: &PrimitiveImpl,
+    tolerance: f64,
+    x: Option<&JArray>,
+    y: &JArray,
+) -> Option<Result<JArray>> {
+    match (imp.name, x) {
+        ("$.", None) => Some(v_sparse(y)),
+        ("$.", Some(x)) if !x.is_sparse() => Some(v_sparse_functions(x, y)),
+        ("$", None) => Some(v_shape_of(y)),
+        ("#", None) => Some(v_tally(y)),
+        ("-:", Some(x)) => Some(match_(x, y, tolerance)),
+        ("|:", None) => Some(v_transpose(y)),
+        ("|:", Some(x)) if !x.is_sparse() && x.shape().len() <= 1 => Some(v_transpose_dyad(x, y)),
+        (",", None) => Some(sparse(y).and_then(|s| Ok(s.ravel()?.into()))),
+        (",", Some(JArray::SparseArray(x))) => match y {
+            JArray::SparseArray(y) => append(x, y),
+            _ => None,
+        },
+        ("{", Some(x)) if !x.is_sparse() && !matches!(x, JArray::BoxArray(_)) => match y {
+            JArray::SparseArray(y) => from(x, y),
+            _ => None,
+        },
+        // a random number for the sparse element isn't a random number for every atom
+        ("?" | "?.", _) => None,
+        (_, None) if imp.monad.rank == Rank::new(0) => match y {
+            JArray::SparseArray(y) => match imp.tolerant_monad() {
+                Some(f) => atoms_monad(|y| f(y, tolerance), y),
+                None => atoms_monad(imp.monad.f, y),
+            },
+            _ => None,
+        },
+        (_, Some(x)) if imp.dyad.rank == (Rank::new(0), Rank::new(0)) => {
+            match imp.tolerant_dyad() {
+                Some(f) => atoms_dyad(|x, y| f(x, y, tolerance), x, y),
+                None => atoms_dyad(imp.dyad.f, x, y),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// f applied to the sparse element and to each stored atom
+fn atoms_monad(f: impl Fn(&JArray) -> Result<JArray>, y: &Sparse) -> Option<Result<JArray>> {
+    let fill = match f(y.fill()) {
+        Ok(fill) if fill.shape().is_empty() => fill,
+        Ok(_) => return None,
+        Err(e) => return Some(Err(e)),
+    };
+    Some((|| {
+        let values = match y.indices().len() {
+            0 => list_of(Vec::new(), &fill)?,
+            _ => exec_monad(&f, Rank::new(0), y.values())?,
+        };
+        Ok(Sparse::new(y.shape().to_vec(), y.indices().to_vec(), values, fill)?.into())
+    })())
+}
+
+/// f between atoms, where either side is sparse, and the other is an atom or the same shape
+fn atoms_dyad(
+    f: impl Fn(&JArray, &JArray) -> Result<JArray> + Copy,
+    x: &JArray,
+    y: &JArray,
+) -> Option<Result<JArray>> {
+    let rank = (Rank::new(0), Rank::new(0));
+    let (shape, fill, indices, xs, ys) = match (x, y) {
+        (JArray::SparseArray(x), y) if y.shape().is_empty() && !y.is_sparse() => (
+            x.shape(),
+            f(x.fill(), y),
+            x.indices().to_vec(),
+            x.values().clone(),
+            y.clone(),
+        ),
+        (x, JArray::SparseArray(y)) if x.shape().is_empty() && !x.is_sparse() => (
+            y.shape(),
+            f(x, y.fill()),
+            y.indices().to_vec(),
+            x.clone(),
+            y.values().clone(),
+        ),
+        _ => {
+            let (x, y) = as_sparse_pair(x, y)?;
+            let (xe, ye) = match (x.entries(), y.entries()) {
+                (Ok(xe), Ok(ye)) => (xe, ye),
+                (Err(e), _) | (_, Err(e)) => return Some(Err(e)),
+            };
+            let (indices, (xs, ys)): (Vec<_>, (Vec<_>, Vec<_>)) = xe
+                .merge_join_by(ye, |(l, _), (r, _)| l.cmp(r))
+                .map(|e| match e {
+                    EitherOrBoth::Both((i, l), (_, r)) => (i.clone(), (l, r)),
+                    EitherOrBoth::Left((i, l)) => (i.clone(), (l, y.fill().clone())),
+                    EitherOrBoth::Right((i, r)) => (i.clone(), (x.fill().clone(), r)),
+                })
+                .unzip();
+            let fill = f(x.fill(), y.fill());
+            let lists = list_of(xs, x.fill()).and_then(|xs| Ok((xs, list_of(ys, y.fill())?)));
+            let (xs, ys) = match lists {
+                Ok(lists) => lists,
+                Err(e) => return Some(Err(e)),
+            };
+            return finish_dyad(f, rank, x.shape(), fill, indices, &xs, &ys);
+        }
+    };
+    finish_dyad(f, rank, shape, fill, indices, &xs, &ys)
+}
+
+fn finish_dyad(
+    f: impl Fn(&JArray, &JArray) -> Result<JArray>,
+    rank: (Rank, Rank),
+    shape: &[usize],
+    fill: Result<JArray>,
+    indices: Vec<Vec<usize>>,
+    xs: &JArray,
+    ys: &JArray,
+) -> Option<Result<JArray>> {
+    let fill = match fill {
+        Ok(fill) if fill.shape().is_empty() => fill,
+        Ok(_) => return None,
+        Err(e) => return Some(Err(e)),
+    };
+    let values = match indices.len() {
+        0 => list_of(Vec::new(), &fill),
+        _ => exec_dyad(f, rank, xs, ys),
+    };
+    match values {
+        Ok(values) if values.shape() != [indices.len()] => None,
+        Ok(values) => Some(Sparse::new(shape.to_vec(), indices, values, fill).map(JArray::from)),
+        Err(e) => Some(Err(e)),
+    }
+}
+
+/// two sparse arrays of the same shape, when one of them was dense
+fn as_sparse_pair(x: &JArray, y: &JArray) -> Option<(Arc<Sparse>, Arc<Sparse>)> {
+    if x.shape() != y.shape() {
+        return None;
+    }
+    Some(match (x, y) {
+        (JArray::SparseArray(x), JArray::SparseArray(y)) => (x.clone(), y.clone()),
+        (JArray::SparseArray(x), y) => {
+            let y = Sparse::from_dense(y, x.fill().clone()).ok()?;
+            (x.clone(), Arc::new(y))
+        }
+        (x, JArray::SparseArray(y)) => {
+            let x = Sparse::from_dense(x, y.fill().clone()).ok()?;
+            (Arc::new(x), y.clone())
+        }
+        _ => return None,
+    })
+}
+
+/// `+/`, adding up the items; the sparse element is counted once for each missing atom
+fn sum(y: &Sparse) -> Result<JArray> {
+    let n = y.shape()[0];
+    let fill_times = |k: usize| v_times(y.fill(), &JArray::from(arr0d(k as i64)));
+
+    let mut groups: BTreeMap<&[usize], (JArray, usize)> = BTreeMap::new();
+    for (index, value) in y.entries()? {
+        match groups.get_mut(&index[1..]) {
+            Some((total, count)) => {
+                *total = v_plus(total, &value)?;
+                *count += 1;
+            }
+            None => {
+                groups.insert(&index[1..], (value, 1));
+            }
+        }
+    }
+    let fill = fill_times(n)?;
+    let (indices, values) = groups
+        .into_iter()
+        .map(|(index, (total, count))| {
+            Ok((index.to_vec(), v_plus(&total, &fill_times(n - count)?)?))
+        })
+        .collect::<Result<(Vec<_>, Vec<_>)>>()?;
+
+    if y.shape().len() == 1 {
+        return Ok(values.into_iter().next().unwrap_or(fill));
+    }
+    let values = list_of(values, &fill)?;
+    Ok(Sparse::new(y.shape()[1..].to_vec(), indices, values, fill)?.into())
+}
+
+/// `x { y` for integer x, selecting items
+fn from(x: &JArray, y: &Sparse) -> Option<Result<JArray>> {
+    if x.shape().len() > 1 || y.shape().is_empty() {
+        return None;
+    }
+    Some((|| {
+        let n = y.shape()[0];
+        let picks = x
+            .approx_i64_list()
+            .context("expecting integer indices")?
+            .into_iter()
+            .map(|i| index_into(i, n))
+            .collect::<Result<Vec<_>>>()?;
+
+        let atom = x.shape().is_empty();
+        let mut indices = Vec::new();
+        let mut positions = Vec::new();
+        for (k, &pick) in picks.iter().enumerate() {
+            // the stored atoms of item `pick` are contiguous, as the indices are sorted
+            let lo = y.indices().partition_point(|index| index[0] < pick);
+            let hi = y.indices().partition_point(|index| index[0] <= pick);
+            for p in lo..hi {
+                let rest = &y.indices()[p][1..];
+                indices.push(match atom {
+                    true => rest.to_vec(),
+                    false => [&[k], rest].concat(),
+                });
+                positions.push(p);
+            }
+        }
+        let values = y.values().select(Axis(0), &positions)?;
+
+        let shape = match atom {
+            true => y.shape()[1..].to_vec(),
+            false => [&[picks.len()], &y.shape()[1..]].concat(),
+        };
+        if shape.is_empty() {
+            return match positions.is_empty() {
+                true => Ok(y.fill().clone()),
+                false => Ok(values.select(Axis(0), &[0])?.reshape(IxDyn(&[]))?),
+            };
+        }
+        Ok(Sparse::new(shape, indices, values, y.fill().clone())?.into())
+    })())
+}
+
+/// `x m} y`, replacing the items of y at the integer indices m, or its atoms at the boxed ones
+fn amend(m: &JArray, x: &JArray, y: &Sparse) -> Option<Result<JArray>> {
+    if x.is_sparse() || y.shape().is_empty() {
+        return None;
+    }
+    Some((|| {
+        let prefixes = match m {
+            JArray::BoxArray(b) => b
+                .iter()
+                .map(|index| {
+                    let index = index
+                        .approx_i64_list()
+                        .context("expecting an integer index")?;
+                    if index.len() != y.shape().len() {
+                        return Err(JError::NonceError).with_context(|| {
+                            anyhow!("only whole indices of a sparse array can be amended")
+                        });
+                    }
+                    index
+                        .into_iter()
+                        .zip(y.shape())
+                        .map(|(i, &n)| index_into(i, n))
+                        .collect()
+                })
+                .collect::<Result<Vec<Vec<_>>>>()?,
+            _ => m
+                .approx_i64_list()
+                .context("expecting integer indices")?
+                .into_iter()
+                .map(|i| Ok(vec![index_into(i, y.shape()[0])?]))
+                .collect::<Result<_>>()?,
+        };
+        let Some(k) = prefixes.first().map(Vec::len) else {
+            return Ok(JArray::SparseArray(y.clone().into()));
+        };
+
+        // x, repeated to fill a cell for each index, as for dense arrays
+        let shape = [&[prefixes.len()], &y.shape()[k..]].concat();
+        if !shape.ends_with(x.shape()) {
+            return Err(JError::LengthError)
+                .with_context(|| anyhow!("{:?} doesn't fit cells of {shape:?}", x.shape()));
+        }
+        let shape = JArray::from_list(shape.iter().map(|&s| s as i64).collect_vec());
+        let cells = v_shape(&shape, &x.reshape(vec![x.tally()])?)?;
+        // the last cell for an index wins
+        let cells: BTreeMap<_, _> = prefixes.into_iter().zip(cells.outer_iter()?).collect();
+
+        let (mut indices, mut values): (Vec<_>, Vec<_>) = y
+            .entries()?
+            .filter(|(index, _)| !cells.contains_key(&index[..k]))
+            .map(|(index, value)| (index.clone(), value))
+            .unzip();
+        for (prefix, cell) in &cells {
+            for (index, value) in Sparse::from_dense(cell, y.fill().clone())?.entries()? {
+                indices.push([&prefix[..], index].concat());
+                values.push(value);
+            }
+        }
+        let values = list_of(values, y.fill())?;
+        Ok(Sparse::new(y.shape().to_vec(), indices, values, y.fill().clone())?.into())
+    })())
+}
+
+/// `i` as an index of an axis of length `n`, where negative indices count from the end
+fn index_into(i: i64, n: usize) -> Result<usize> {
+    let j = if i < 0 { i + n as i64 } else { i };
+    usize::try_from(j)
+        .ok()
+        .filter(|&j| j < n)
+        .ok_or(JError::IndexError)
+        .with_context(|| anyhow!("{i} isn't an index of {n} items"))
+}
+
+/// `x , y` for sparse arrays with the same items and sparse element
+fn append(x: &Sparse, y: &Sparse) -> Option<Result<JArray>> {
+    if x.shape().is_empty()
+        || x.shape().len() != y.shape().len()
+        || x.shape()[1..] != y.shape()[1..]
+        || x.fill() != y.fill()
+    {
+        return None;
+    }
+    let offset = x.shape()[0];
+    let mut shape = x.shape().to_vec();
+    shape[0] += y.shape()[0];
+    let indices = x
+        .indices()
+        .iter()
+        .cloned()
+        .chain(y.indices().iter().map(|index| {
+            let mut index = index.clone();
+            index[0] += offset;
+            index
+        }))
+        .collect_vec();
+    Some((|| {
+        let values = x.values().outer_iter()?.chain(y.values().outer_iter()?);
+        let values = list_of(values.collect(), x.fill())?;
+        Ok(Sparse::new(shape, indices, values, x.fill().clone())?.into())
+    })())
+}
+
+/// `1 $. sh` or `1 $. sh;a;e`: an array of nothing but the sparse element
+fn new_sparse(y: &JArray) -> Result<JArray> {
+    let parts = match y.when_box() {
+        Some(b) => b.iter().cloned().collect_vec(),
+        None => vec![y.clone()],
+    };
+    if parts.is_empty() || parts.len() > 3 {
+        return Err(JError::LengthError).context("1 $. takes sh or sh;a;e");
+    }
+    let shape = parts[0].approx_usize_list().context("expecting a shape")?;
+    if let Some(axes) = parts.get(1) {
+        check_all_axes(axes, shape.len())?;
+    }
+    let fill = parts.get(2).cloned().unwrap_or_else(JArray::atomic_zero);
+    Ok(Sparse::empty(shape, fill)?.into())
+}
+
+/// only arrays which are sparse on every axis are supported
+fn check_all_axes(axes: &JArray, rank: usize) -> Result<()> {
+    let axes = axes
+        .approx_i64_list()
+        .context("expecting a list of axes")?
+        .into_iter()
+        .map(|a| if a < 0 { a + rank as i64 } else { a })
+        .sorted()
+        .collect_vec();
+    if axes != (0..rank as i64).collect_vec() {
+        return Err(JError::NonceError)
+            .with_context(|| anyhow!("dense axes aren't supported, only sparse axes {axes:?}"));
+    }
+    Ok(())
+}
+
+fn sparse(y: &JArray) -> Result<&Sparse> {
+    match y {
+        JArray::SparseArray(s) => Ok(s),
+        _ => Err(JError::DomainError).context("expecting a sparse array"),
+    }
+}
+
+/// the usual sparse element for the kind of `y`
+fn default_fill(y: &JArray) -> JArray {
+    match y {
+        JArray::CharArray(..) => JArray::from(arr0d(' ')),
+        JArray::BoxArray(_) => JArray::BoxArray(arr0ad(JArray::empty())),
+        _ => JArray::atomic_zero(),
+    }
+}
+
+/// a list of atoms, which might be an empty list of the kind of `like`
+fn list_of(atoms: Vec<JArray>, like: &JArray) -> Result<JArray> {
+    match atoms.is_empty() {
+        true => like.rank_extend(1).select(Axis(0), &[]),
+        false => JArray::from_fill_promote(atoms),
+    }
+}
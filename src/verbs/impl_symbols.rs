@@ -0,0 +1,150 @@
+//! s:, which converts between strings and the symbols in the `Ctx`'s symbol table
+
+use anyhow::{anyhow, Context, Result};
+use itertools::Itertools;
+use ndarray::prelude::*;
+
+use crate::arrays::{ArcArrayD, Symbol};
+use crate::{arr0d, Ctx, JArray, JError};
+
+/// s: (monad and dyad)
+pub fn v_symbol(ctx: &mut Ctx, x: Option<&JArray>, y: &JArray) -> Result<JArray> {
+    let Some(x) = x else {
+        return match y {
+            JArray::SymbolArray(_) => Ok(y.clone()),
+            JArray::BoxArray(b) => {
+                let names = b.iter().map(string).collect::<Result<Vec<_>>>()?;
+                intern(ctx, y.shape(), names)
+            }
+            JArray::CharArray(..) if y.shape().len() >= 2 => from_table(ctx, y),
+            _ => from_separated(ctx, y, true),
+        };
+    };
+
+    let mode = x
+        .approx_i64_one()
+        .context("s: needs an integer mode for x")?;
+    match mode {
+        0 => query(ctx, y),
+        -1 => from_separated(ctx, y, true),
+        -2 => from_separated(ctx, y, false),
+        -3 => from_table(ctx, y),
+        1 => Ok(JArray::from_string(
+            symbols(y)?
+                .iter()
+                .map(|s| format!("`{}", s.as_str()))
+                .join(""),
+        )),
+        2 => Ok(JArray::from_string(
+            symbols(y)?
+                .iter()
+                .map(|s| format!("{}\0", s.as_str()))
+                .join(""),
+        )),
+        3 => {
+            // one row per symbol, padded with NULs
+            let syms = symbols(y)?;
+            let width = syms
+                .iter()
+                .map(|s| s.as_str().chars().count())
+                .max()
+                .unwrap_or(0);
+            let mut shape = y.shape().to_vec();
+            shape.push(width);
+            let chars = syms
+                .iter()
+                .flat_map(|s| s.as_str().chars().pad_using(width, |_| '\0'))
+                .collect();
+            Ok(ArrayD::from_shape_vec(shape, chars)?.into())
+        }
+        5 => Ok(symbols(y)?.map(|s| JArray::from_string(s.as_str())).into()),
+        6 => Ok(symbols(y)?.map(|s| ctx.symbols.index_of(s) as i64).into()),
+        -6 => {
+            let indexes = y.approx_usize_list().context("_6 s: needs indexes")?;
+            let found = indexes
+                .into_iter()
+                .map(|i| ctx.symbols.get(i).cloned())
+                .collect::<Option<Vec<_>>>()
+                .ok_or(JError::IndexError)
+                .context("not in the symbol table")?;
+            Ok(ArrayD::from_shape_vec(y.shape(), found)?.into())
+        }
+        _ => Err(JError::DomainError).with_context(|| anyhow!("s: has no mode {mode}")),
+    }
+}
+
+/// `0 s: y`, questions about the symbol table itself
+fn query(ctx: &Ctx, y: &JArray) -> Result<JArray> {
+    Ok(match y.approx_i64_one().context("0 s: needs an integer")? {
+        // the number of symbols
+        0 => JArray::from(arr0d(ctx.symbols.len() as i64)),
+        // the length of all their text
+        1 => JArray::from(arr0d(ctx.symbols.string_bytes() as i64)),
+        // a self-check, which can't fail
+        11 => JArray::from(arr0d(1u8)),
+        other => {
+            return Err(JError::NonceError).with_context(|| anyhow!("0 s: {other} is unsupported"))
+        }
+    })
+}
+
+/// `_1 s:` (with a leading separator) and `_2 s:` (with a trailing one)
+fn from_separated(ctx: &mut Ctx, y: &JArray, leading: bool) -> Result<JArray> {
+    let text = match y {
+        JArray::CharArray(a, _) if a.shape().len() <= 1 => a.iter().collect::<String>(),
+        _ => return Err(JError::DomainError).context("expecting a string of separated names"),
+    };
+    let separator = match leading {
+        true => text.chars().next(),
+        false => text.chars().last(),
+    };
+    let names = match separator {
+        None => vec![],
+        Some(sep) if leading => text[sep.len_utf8()..]
+            .split(sep)
+            .map(str::to_string)
+            .collect(),
+        Some(sep) => text[..text.len() - sep.len_utf8()]
+            .split(sep)
+            .map(str::to_string)
+            .collect(),
+    };
+    intern(ctx, &[names.len()], names)
+}
+
+/// each row of a table, without its trailing blanks (or NULs)
+fn from_table(ctx: &mut Ctx, y: &JArray) -> Result<JArray> {
+    let JArray::CharArray(a, _) = y else {
+        return Err(JError::DomainError).context("expecting a table of names");
+    };
+    if a.shape().len() < 2 {
+        return Err(JError::RankError).context("expecting a table of names");
+    }
+    let names = a
+        .rows()
+        .into_iter()
+        .map(|row| {
+            let name = row.iter().collect::<String>();
+            name.trim_end_matches([' ', '\0']).to_string()
+        })
+        .collect_vec();
+    intern(ctx, &a.shape()[..a.shape().len() - 1], names)
+}
+
+fn intern(ctx: &mut Ctx, shape: &[usize], names: Vec<String>) -> Result<JArray> {
+    let syms = names.iter().map(|n| ctx.symbols.intern(n)).collect();
+    Ok(ArrayD::from_shape_vec(shape, syms)?.into())
+}
+
+fn symbols(y: &JArray) -> Result<&ArcArrayD<Symbol>> {
+    match y {
+        JArray::SymbolArray(a) => Ok(a),
+        _ => Err(JError::DomainError).context("expecting symbols"),
+    }
+}
+
+fn string(b: &JArray) -> Result<String> {
+    b.when_string()
+        .ok_or(JError::DomainError)
+        .context("expecting boxed strings")
+}
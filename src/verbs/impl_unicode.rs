@@ -0,0 +1,146 @@
+//! u:, between code points and the UTF-8 and UTF-16 encodings of them
+//!
+//! Characters are stored as code points whatever their type (see [`CharWidth`]), so converting
+//! between literal and unicode is a question of which code points are allowed, and of the tag.
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::arrays::{ArcArrayD, CharWidth};
+use crate::{arr0d, JArray, JError};
+
+/// u: (monad)
+pub fn v_unicode(y: &JArray) -> Result<JArray> {
+    v_unicode_conversions(&JArray::from(arr0d(2i64)), y)
+}
+
+/// u: (dyad)
+pub fn v_unicode_conversions(x: &JArray, y: &JArray) -> Result<JArray> {
+    let mode = x
+        .approx_i64_one()
+        .context("u: needs an integer mode for x")?;
+    match mode {
+        // to literal, dropping everything above the low byte
+        1 => Ok(JArray::CharArray(
+            chars(y)?
+                .map(|&c| char::from(u32::from(c) as u8))
+                .into_shared(),
+            CharWidth::Literal,
+        )),
+        // to unicode; characters only need retagging
+        2 => match y.when_char() {
+            Some(c) if CharWidth::of_all(c.iter()) == CharWidth::Unicode4 => {
+                Err(JError::DomainError).context("characters beyond the BMP don't fit in unicode")
+            }
+            Some(_) => Ok(y.clone().with_char_width(CharWidth::Unicode)),
+            None => from_codes(y, CharWidth::Unicode),
+        },
+        3 => Ok(chars(y)?.map(|&c| i64::from(u32::from(c))).into()),
+        4 => from_codes(y, CharWidth::Unicode),
+        // to literal, refusing to drop anything
+        5 => {
+            let bytes = chars(y)?
+                .iter()
+                .map(|&c| match CharWidth::of(c) {
+                    CharWidth::Literal => Ok(c),
+                    _ => Err(JError::DomainError).with_context(|| anyhow!("{c:?} isn't a byte")),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok(JArray::CharArray(
+                ArcArrayD::from_shape_vec(y.shape(), bytes)?,
+                CharWidth::Literal,
+            ))
+        }
+        // little-endian byte pairs, as UTF-16
+        6 => {
+            let bytes = bytes(y)?;
+            if bytes.len() % 2 != 0 {
+                return Err(JError::LengthError).context("UTF-16 needs pairs of bytes");
+            }
+            let units = bytes.chunks(2).map(|p| u16::from_le_bytes([p[0], p[1]]));
+            let text = char::decode_utf16(units)
+                .collect::<Result<String, _>>()
+                .map_err(|_| JError::DomainError)
+                .context("unpaired surrogate")?;
+            Ok(JArray::from_string(text))
+        }
+        // from UTF-8; only literals are bytes, anything else has already been decoded
+        7 | 9 => {
+            let decoded = match y.char_width() {
+                Some(CharWidth::Literal) => JArray::from_string(
+                    String::from_utf8(bytes(y)?)
+                        .map_err(|_| JError::DomainError)
+                        .context("invalid UTF-8")?,
+                ),
+                _ => JArray::from_string(text(y)?.into_iter().collect::<String>()),
+            };
+            // ASCII stays literal; anything else is as wide as the mode asks
+            Ok(match (mode, decoded.char_width()) {
+                (9, Some(CharWidth::Unicode)) => decoded.with_char_width(CharWidth::Unicode4),
+                _ => decoded,
+            })
+        }
+        // to UTF-8
+        8 => {
+            let text = text(y)?.into_iter().collect::<String>();
+            let bytes = text.bytes().map(char::from).collect::<Vec<_>>();
+            Ok(JArray::from_list(bytes).with_char_width(CharWidth::Literal))
+        }
+        10 => from_codes(y, CharWidth::Unicode4),
+        // to UTF-16, which is a no-op unless there are surrogate pairs to make
+        11 => match CharWidth::of_all(chars(y)?.iter()) {
+            CharWidth::Unicode4 => {
+                Err(JError::NonceError).context("surrogates can't be stored as characters")
+            }
+            _ => Ok(y.clone().with_char_width(CharWidth::Unicode)),
+        },
+        _ => Err(JError::DomainError).with_context(|| anyhow!("u: has no mode {mode}")),
+    }
+}
+
+/// the characters for some code points, which must fit in `width`
+fn from_codes(y: &JArray, width: CharWidth) -> Result<JArray> {
+    let codes = y
+        .clone()
+        .into_nums()
+        .ok_or(JError::DomainError)
+        .context("expecting code points")?;
+    let chars = codes
+        .into_iter()
+        .map(|n| {
+            n.value_i64()
+                .and_then(|v| u32::try_from(v).ok())
+                .and_then(char::from_u32)
+                .filter(|&c| CharWidth::of(c) <= width)
+                .ok_or(JError::DomainError)
+                .with_context(|| anyhow!("{n:?} isn't a {width:?} code point"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    Ok(JArray::CharArray(
+        ArcArrayD::from_shape_vec(y.shape(), chars)?,
+        width,
+    ))
+}
+
+fn chars(y: &JArray) -> Result<&ArcArrayD<char>> {
+    y.when_char()
+        .ok_or(JError::DomainError)
+        .context("expecting characters")
+}
+
+/// the characters of a string (or a single character)
+fn text(y: &JArray) -> Result<Vec<char>> {
+    if y.shape().len() > 1 {
+        return Err(JError::RankError).context("expecting a string");
+    }
+    Ok(chars(y)?.iter().copied().collect())
+}
+
+/// a string of bytes, like the result of `8 u:`
+fn bytes(y: &JArray) -> Result<Vec<u8>> {
+    text(y)?
+        .into_iter()
+        .map(|c| u8::try_from(c).ok())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(JError::DomainError)
+        .context("expecting bytes")
+}
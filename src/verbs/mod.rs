@@ -1,15 +1,22 @@
 mod impl_impl;
 mod impl_maths;
+mod impl_matrix;
+mod impl_primes;
 mod impl_shape;
+mod impl_sparse;
+mod impl_symbols;
+mod impl_unicode;
 mod maff;
+mod obverse;
 mod partial;
 mod primitive;
 mod ranks;
 
 use std::collections::VecDeque;
-use std::iter::repeat;
+use std::iter::{self, repeat};
 
-use crate::number::Num;
+use crate::cells::fill_promote_reshape_with;
+use crate::number::{arrays_match, elems_equal, Num, DEFAULT_TOLERANCE};
 use crate::{
     arr0ad, arr0d, eval, impl_array, scan, scan_with_locations, ArcArrayD, Ctx, Elem, HasEmpty,
     JArray, JError, Word,
@@ -19,6 +26,7 @@ use anyhow::{anyhow, ensure, Context, Result};
 use itertools::Itertools;
 use ndarray::prelude::*;
 use ndarray::Axis;
+use num::complex::Complex64;
 use num::BigRational;
 use num_traits::FloatConst;
 use try_partialord::TrySort;
@@ -31,7 +39,12 @@ pub use ranks::{DyadRank, Rank};
 use crate::arrays::IntoVec;
 pub use impl_impl::*;
 pub use impl_maths::*;
+pub use impl_matrix::*;
+pub use impl_primes::*;
 pub use impl_shape::*;
+pub use impl_sparse::*;
+pub use impl_symbols::*;
+pub use impl_unicode::*;
 
 pub use partial::*;
 pub use primitive::*;
@@ -96,14 +109,19 @@ pub fn v_plot(y: &JArray) -> Result<JArray> {
 
 /// = (monad)
 pub fn v_self_classify(y: &JArray) -> Result<JArray> {
-    let candidates = y.outer_iter().collect_vec();
-    let nubs = nub(&candidates);
+    self_classify(y, DEFAULT_TOLERANCE)
+}
+
+/// `=`, with items as in [`nub_`]
+pub fn self_classify(y: &JArray, tolerance: f64) -> Result<JArray> {
+    let candidates = y.outer_iter()?.collect_vec();
+    let nubs = nub(&candidates, tolerance);
     let output_shape = [nubs.len(), candidates.len()];
     let mut output = Vec::with_capacity(output_shape[0] * output_shape[1]);
     for nub in &nubs {
         for cand in &candidates {
             let nub = &candidates[*nub];
-            output.push(if nub == cand { 1u8 } else { 0u8 });
+            output.push(arrays_match(nub, cand, tolerance) as u8);
         }
     }
 
@@ -114,25 +132,40 @@ pub fn v_self_classify(y: &JArray) -> Result<JArray> {
 
 /// -. (dyad)
 pub fn v_less(x: &JArray, y: &JArray) -> Result<JArray> {
+    less(x, y, DEFAULT_TOLERANCE)
+}
+
+/// `-.`, where items within `tolerance` of an item of y are removed
+pub fn less(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
     if x.shape().len() > 1 || y.shape().len() > 1 {
         return Err(JError::NonceError).context("only available for lists");
     }
-    let y = y.outer_iter().collect_vec();
-    JArray::from_fill_promote(x.outer_iter().filter(|x| !y.contains(x)))
+    let y = y.outer_iter()?.collect_vec();
+    JArray::from_fill_promote(
+        x.outer_iter()?
+            .filter(|x| !y.iter().any(|y| arrays_match(x, y, tolerance))),
+    )
 }
 
 /// -: (dyad)
 pub fn v_match(x: &JArray, y: &JArray) -> Result<JArray> {
-    Ok(JArray::BoolArray(arr0ad(if x == y { 1 } else { 0 })))
+    match_(x, y, DEFAULT_TOLERANCE)
+}
+
+/// `-:`, where atoms within `tolerance` of each other match
+pub fn match_(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    Ok(JArray::BoolArray(arr0ad(
+        arrays_match(x, y, tolerance) as u8
+    )))
 }
 
-fn nub(candidates: &[JArray]) -> Vec<usize> {
+fn nub(candidates: &[JArray], tolerance: f64) -> Vec<usize> {
     let mut included = Vec::new();
     'outer: for (i, test) in candidates.iter().enumerate() {
         // if we've already seen this value, don't add it to the `included` list,
         // by continuing out of the two loops
         for seen in &included {
-            if test == &candidates[*seen] {
+            if arrays_match(test, &candidates[*seen], tolerance) {
                 continue 'outer;
             }
         }
@@ -143,37 +176,69 @@ fn nub(candidates: &[JArray]) -> Vec<usize> {
 
 /// ~. (monad) (_)
 pub fn v_nub(y: &JArray) -> Result<JArray> {
+    nub_(y, DEFAULT_TOLERANCE)
+}
+
+/// `~.`, where items within `tolerance` of an earlier item are dropped
+pub fn nub_(y: &JArray, tolerance: f64) -> Result<JArray> {
     // truly awful; missing methods on JArrayCow / JArray which need adding; select, outer_iter()
-    // O(n²) 'cos of laziness around PartialEq; might be needed for tolerance
+    // O(n²) as tolerant equality can't be hashed
 
-    let candidates = y.outer_iter().collect_vec();
-    let included = nub(&candidates);
+    let candidates = y.outer_iter()?.collect_vec();
+    let included = nub(&candidates, tolerance);
 
-    Ok(y.select(Axis(0), &included))
+    y.select(Axis(0), &included)
 }
 
 /// ~: (monad)
 pub fn v_nub_sieve(y: &JArray) -> Result<JArray> {
-    j_monad_eval("(i.@# e. i.~)", y)
+    nub_sieve(y, DEFAULT_TOLERANCE)
+}
+
+/// `~:`, with items as in [`nub_`]
+pub fn nub_sieve(y: &JArray, tolerance: f64) -> Result<JArray> {
+    let candidates = y.outer_iter()?.collect_vec();
+    let included = nub(&candidates, tolerance);
+    Ok(JArray::from_list(
+        (0..candidates.len())
+            .map(|i| included.contains(&i) as u8)
+            .collect_vec(),
+    ))
 }
 
 /// |. (monad)
 pub fn v_reverse(y: &JArray) -> Result<JArray> {
-    let mut y = y.outer_iter().collect_vec();
+    let mut y = y.outer_iter()?.collect_vec();
     y.reverse();
     JArray::from_fill_promote(y.into_iter())
 }
 /// |. (dyad)
 pub fn v_rotate_shift(x: &JArray, y: &JArray) -> Result<JArray> {
+    rotate_shift(x, y, None)
+}
+
+/// `|.`, which shifts in the atom `fill` rather than rotating, if there is one
+pub fn rotate_shift(x: &JArray, y: &JArray, fill: Option<&JArray>) -> Result<JArray> {
     let x = x.approx_i64_one().context("rotate shift's x")?;
 
     if 0 == x {
         return Ok(y.clone());
     }
 
-    let mut y = y.outer_iter().collect::<VecDeque<_>>();
+    let mut y = y.outer_iter()?.collect::<VecDeque<_>>();
     let distance = usize::try_from(x.abs())?;
 
+    if let Some(fill) = fill {
+        let len = y.len();
+        let kept = len.saturating_sub(distance);
+        let pad = iter::repeat_n(fill.clone(), len - kept);
+        let y = match x < 0 {
+            true => pad.chain(y.into_iter().take(kept)).collect_vec(),
+            false => y.into_iter().skip(len - kept).chain(pad).collect_vec(),
+        };
+        return fill_promote_reshape_with((vec![len], y), Some(fill));
+    }
+
     // yes, this looks the wrong way around to me, too, but it's what it says
     if x < 0 {
         y.rotate_right(distance)
@@ -190,6 +255,7 @@ pub fn v_ravel(y: &JArray) -> Result<JArray> {
         // TODO: weird copy?
         Ok(JArray::from_list(arr.to_owned().into_raw_vec()))
     })
+    .map(|r| r.keep_char_width(y))
 }
 
 /// ,. (monad)
@@ -233,7 +299,7 @@ pub fn v_raze(y: &JArray) -> Result<JArray> {
                 if arr.shape().len() > 1 {
                     return Err(JError::NonceError).context("non-list inside a box");
                 }
-                parts.extend(arr.outer_iter());
+                parts.extend(arr.outer_iter()?);
             }
             JArray::from_fill_promote(parts)
         }
@@ -246,7 +312,7 @@ pub fn v_words(y: &JArray) -> Result<JArray> {
     if y.shape().len() > 1 {
         return Err(JError::NonceError).context("multi-line words");
     }
-    let JArray::CharArray(y) = y else { return Err(JError::DomainError).context("words only takes strings"); };
+    let JArray::CharArray(y, _) = y else { return Err(JError::DomainError).context("words only takes strings"); };
     let y = y.iter().collect::<String>();
     let items = scan_with_locations(&y)?;
     Ok(JArray::from_list(
@@ -267,31 +333,90 @@ pub fn v_tally(y: &JArray) -> Result<JArray> {
 }
 
 /// #. (monad)
-pub fn v_base_(_y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
+pub fn v_base_(y: &JArray) -> Result<JArray> {
+    v_base(&JArray::from(arr0d(2i64)), y)
 }
 /// #. (dyad)
-pub fn v_base(_x: &JArray, _y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
-
-    // https://code.jsoftware.com/wiki/PrimitivePrimitives
-    // raa   =. ,&< ($&.>~ ((-.@:] {"_1 [ |:@:,:  ({~ 1: <. i.&1)) (#&>)) )  ,&:<&:$
-    // rshp  =: ((&:>) /) (@: raa f.)
-    // base =: (+/@:* */\\.@:}.@:,&1)~ rshp
+pub fn v_base(x: &JArray, y: &JArray) -> Result<JArray> {
+    let (x, y) = (base_integers(x)?, base_integers(y)?);
+    let list = |a: &ArrayD<i64>| a.iter().copied().collect_vec();
+    let (x, y) = match (x.shape(), y.shape()) {
+        ([], [n]) => (vec![x[[]]; *n], list(&y)),
+        ([n], []) => (list(&x), vec![y[[]]; *n]),
+        (a, b) if a == b => (list(&x), list(&y)),
+        (a, b) => {
+            return Err(JError::LengthError)
+                .with_context(|| anyhow!("bases {a:?} for digits {b:?}"))
+        }
+    };
+    // Horner's rule; the first base is never used
+    let n = x.iter().zip(y).try_fold(0i64, |acc, (b, d)| {
+        acc.checked_mul(*b).and_then(|acc| acc.checked_add(d))
+    });
+    Ok(Num::from(n.ok_or(JError::LimitError).context("#. overflowed")?).into())
+}
 
-    // let s = "(+/@:* */\\.@:}.@:,&1)~ (rshp=:((&:>) /) (@: (raa=.,&< ($&.>~ ((-.@:] {\"_1 [ |:@:,:  ({~ 1: <. i.&1)) (#&>)) )  ,&:<&:$) f.))";
-    // let s = "4 : 'x ((+/@:* */\\.@:}.@:,&1)~ rshp) y [ (rshp =: ((&:>) /) (@: raa f.)) [ (raa =. ,&< ($&.>~ ((-.@:] {\"_1 [ |:@:,:  ({~ 1: <. i.&1)) (#&>)) )  ,&:<&:$)'";
-    // let s = "4 : 'x ((+/@:* */\\.@:}.@:,&1)~ rshp) y [ rshp =: (((&:>) /) (@: raa f.)) [ raa =. (,&< ($&.>~ ((-.@:] {\"_1 [ |:@:,:  ({~ 1: <. i.&1)) (#&>)) )  ,&:<&:$)'";
-    // j_dyad_eval(s, x, y)
+fn base_integers(y: &JArray) -> Result<ArrayD<i64>> {
+    Ok(y.to_i64()
+        .ok_or(JError::NonceError)
+        .context("#. and #: only take integers")?
+        .into_owned())
 }
 
 /// #: (monad)
-pub fn v_antibase_(_y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
+pub fn v_antibase_(y: &JArray) -> Result<JArray> {
+    antibase_enough(2, y)
+}
+
+/// `base&#.^:_1`, with enough digits for every atom; negative atoms are in base's complement
+pub fn antibase_enough(base: i64, y: &JArray) -> Result<JArray> {
+    if base < 2 {
+        return Err(JError::DomainError)
+            .with_context(|| anyhow!("no number of digits in base {base} is enough"));
+    }
+    let y = base_integers(y)?;
+    let width = |n: i64| {
+        let mut n = if n < 0 { !n } else { n };
+        let mut digits = 0;
+        while n > 0 {
+            n /= base;
+            digits += 1;
+        }
+        digits
+    };
+    let width = y.iter().map(|&n| width(n)).max().unwrap_or(0).max(1);
+    let digits = y
+        .iter()
+        .flat_map(|&n| antibase(&vec![base; width], n))
+        .collect_vec();
+    let shape = y.shape().iter().copied().chain([width]).collect_vec();
+    Ok(ArrayD::from_shape_vec(shape, digits)?.into())
 }
 /// #: (dyad)
-pub fn v_antibase(_x: &JArray, _y: &JArray) -> Result<JArray> {
-    Err(JError::NonceError.into())
+pub fn v_antibase(x: &JArray, y: &JArray) -> Result<JArray> {
+    let (x, y) = (base_integers(x)?, base_integers(y)?);
+    let digits = antibase(&x.iter().copied().collect_vec(), y[[]]);
+    Ok(ArrayD::from_shape_vec(x.shape(), digits)?.into())
+}
+
+/// the digits of n in the bases, most significant first, with the residue of x | y
+fn antibase(bases: &[i64], mut n: i64) -> Vec<i64> {
+    let mut digits = vec![0; bases.len()];
+    for (digit, &b) in digits.iter_mut().zip(bases).rev() {
+        if b == 0 {
+            // a zero base takes everything that's left
+            *digit = n;
+            n = 0;
+            continue;
+        }
+        let mut r = n % b;
+        if r != 0 && (r < 0) != (b < 0) {
+            r += b;
+        }
+        *digit = r;
+        n = (n - r) / b;
+    }
+    digits
 }
 
 /// /: (monad)
@@ -300,7 +425,12 @@ pub fn v_grade_up(y: &JArray) -> Result<JArray> {
         return Err(JError::NonceError).context("sort only implemented for (1d) lists");
     }
 
-    let mut y = y.clone().into_elems().into_iter().enumerate().collect_vec();
+    let mut y = y
+        .clone()
+        .into_elems()?
+        .into_iter()
+        .enumerate()
+        .collect_vec();
     y.try_sort_by_key(|(_, n)| Some(n.clone()))
         .map_err(|_| JError::NonceError)
         .context("sort only implemented for simple types")?;
@@ -317,11 +447,16 @@ pub fn v_sort_up(x: &JArray, y: &JArray) -> Result<JArray> {
         return Err(JError::NonceError).context("sort only implemented for (1d) lists");
     }
 
-    let mut y = y.clone().into_elems().into_iter().enumerate().collect_vec();
+    let mut y = y
+        .clone()
+        .into_elems()?
+        .into_iter()
+        .enumerate()
+        .collect_vec();
     y.try_sort_by_key(|(_, n)| Some(n.clone()))
         .map_err(|_| JError::NonceError)
         .context("sort only implemented for simple types")?;
-    let x = x.clone().into_elems();
+    let x = x.clone().into_elems()?;
     if x.len() < y.len() {
         return Err(JError::IndexError).context("need more xs than ys");
     }
@@ -343,11 +478,16 @@ pub fn v_sort_down(x: &JArray, y: &JArray) -> Result<JArray> {
         return Err(JError::NonceError).context("sort only implemented for (1d) lists");
     }
 
-    let mut y = y.clone().into_elems().into_iter().enumerate().collect_vec();
+    let mut y = y
+        .clone()
+        .into_elems()?
+        .into_iter()
+        .enumerate()
+        .collect_vec();
     y.try_sort_by_key(|(_, n)| Some(n.clone()))
         .map_err(|_| JError::NonceError)
         .context("sort only implemented for simple types")?;
-    let x = x.clone().into_elems();
+    let x = x.clone().into_elems()?;
     if x.len() < y.len() {
         return Err(JError::IndexError).context("need more xs than ys");
     }
@@ -387,7 +527,7 @@ pub fn v_catalogue(y: &JArray) -> Result<JArray> {
             // TODO: fancy iter zip thing
 
             let pools: Vec<JArray> = y
-                .rank_iter(0)
+                .rank_iter(0)?
                 .into_iter()
                 .map(|a| v_open(&a).unwrap())
                 .collect();
@@ -401,7 +541,7 @@ pub fn v_catalogue(y: &JArray) -> Result<JArray> {
             for p in pools {
                 let mut l: Vec<Vec<Elem>> = vec![vec![]];
                 for x in result.iter() {
-                    for y in p.clone().into_elems().iter() {
+                    for y in p.clone().into_elems()?.iter() {
                         l.append(&mut vec![vec![x.clone(), vec![y.clone()]].concat()]);
                     }
                 }
@@ -432,7 +572,7 @@ pub fn v_from(x: &JArray, y: &JArray) -> Result<JArray> {
     let x = x.approx_i64_one().context("from's x")?;
 
     if let Ok(x) = usize::try_from(x) {
-        let outer = y.outer_iter().collect_vec();
+        let outer = y.outer_iter()?.collect_vec();
         outer
             .get(x)
             .map(|cow| cow.to_owned())
@@ -451,7 +591,7 @@ pub fn v_map(_y: &JArray) -> Result<JArray> {
 /// ". (monad)
 pub fn v_do(y: &JArray) -> Result<JArray> {
     match y {
-        JArray::CharArray(jcode) if jcode.shape().len() <= 1 => {
+        JArray::CharArray(jcode, _) if jcode.shape().len() <= 1 => {
             let mut ctx = Ctx::root();
             let word = crate::eval(
                 crate::scan(&jcode.to_owned().into_raw_vec().iter().collect::<String>())?,
@@ -463,7 +603,7 @@ pub fn v_do(y: &JArray) -> Result<JArray> {
                 _ => JArray::empty(),
             })
         }
-        JArray::CharArray(_) => {
+        JArray::CharArray(..) => {
             return Err(JError::NonceError).context("unable to handle atomic or multi-line strings")
         }
         _ => Err(JError::DomainError).context("do() expects a string"),
@@ -492,11 +632,11 @@ pub fn v_numbers(x: &JArray, y: &JArray) -> Result<JArray> {
 
     match y.shape().len() {
         1 => {
-            let CharArray(arr) = y else { return Err(JError::DomainError).context("char array please") };
+            let CharArray(arr, _) = y else { return Err(JError::DomainError).context("char array please") };
             push_row(arr.view());
         }
         2 => {
-            let CharArray(arr) = y else { return Err(JError::DomainError).context("char array please") };
+            let CharArray(arr, _) = y else { return Err(JError::DomainError).context("char array please") };
             for line in arr.outer_iter() {
                 push_row(line);
             }
@@ -531,6 +671,27 @@ pub fn v_numbers(x: &JArray, y: &JArray) -> Result<JArray> {
 pub fn v_default_format(y: &JArray) -> Result<JArray> {
     Ok(JArray::from_string(format!("{y}").trim_end_matches('\n')))
 }
+/// `":`, with floats shown to `digits` significant digits
+pub fn format_precision(y: &JArray, digits: usize) -> Result<JArray> {
+    fn round(v: f64, digits: usize) -> f64 {
+        match v.is_finite() && digits > 0 {
+            true => format!("{v:.*e}", digits - 1).parse().unwrap_or(v),
+            false => v,
+        }
+    }
+    fn rounded(y: &JArray, digits: usize) -> JArray {
+        match y {
+            FloatArray(a) => FloatArray(a.map(|&v| round(v, digits)).into_shared()),
+            ComplexArray(a) => ComplexArray(
+                a.map(|v| Complex64::new(round(v.re, digits), round(v.im, digits)))
+                    .into_shared(),
+            ),
+            BoxArray(a) => BoxArray(a.map(|b| rounded(b, digits)).into_shared()),
+            _ => y.clone(),
+        }
+    }
+    v_default_format(&rounded(y, digits))
+}
 /// ": (dyad)
 pub fn v_format(_x: &JArray, _y: &JArray) -> Result<JArray> {
     Err(JError::NonceError.into())
@@ -562,7 +723,12 @@ pub fn v_raze_in(_y: &JArray) -> Result<JArray> {
 }
 /// e. (dyad)
 pub fn v_member_in(x: &JArray, y: &JArray) -> Result<JArray> {
-    let ido = v_index_of(y, x).context("member in idot")?;
+    member_in(x, y, DEFAULT_TOLERANCE)
+}
+
+/// `e.`, with membership as in [`index_of`]
+pub fn member_in(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
+    let ido = index_of(y, x, tolerance).context("member in idot")?;
     let tally = Num::Int(i64::try_from(y.len_of_0())?);
     ensure!(ido.shape().len() <= 1);
 
@@ -594,17 +760,26 @@ pub fn v_integers(y: &JArray) -> Result<JArray> {
 }
 /// i. (dyad)
 pub fn v_index_of(x: &JArray, y: &JArray) -> Result<JArray> {
+    index_of(x, y, DEFAULT_TOLERANCE)
+}
+
+/// `i.`, finding the first atom of x within `tolerance` of each atom of y
+pub fn index_of(x: &JArray, y: &JArray, tolerance: f64) -> Result<JArray> {
     if x.shape().len() > 1 {
         return Err(JError::NonceError)
             .with_context(|| anyhow!("input x must be a list, not {x:?} for {y:?}"));
     }
-    let x = x.clone().into_elems();
+    let x = x.clone().into_elems()?;
     let output_shape = y.shape();
     let y = y
         .clone()
-        .into_elems()
+        .into_elems()?
         .into_iter()
-        .map(|y| x.iter().position(|x| x == &y).unwrap_or(x.len()))
+        .map(|y| {
+            x.iter()
+                .position(|x| elems_equal(x, &y, tolerance))
+                .unwrap_or(x.len())
+        })
         .map(|o| i64::try_from(o).expect("arrays that fit in memory"))
         .collect_vec();
     JArray::from_list(y).reshape(output_shape)
@@ -616,8 +791,8 @@ pub fn v_member_interval(x: &JArray, y: &JArray) -> Result<JArray> {
         return Err(JError::NonceError)
             .with_context(|| anyhow!("inputs must be lists: {x:?} {y:?}"));
     }
-    let x = x.clone().into_elems();
-    let y = y.clone().into_elems();
+    let x = x.clone().into_elems()?;
+    let y = y.clone().into_elems()?;
     ensure!(!x.is_empty());
     Ok(JArray::from_list(
         y.windows(x.len())
@@ -629,12 +804,15 @@ pub fn v_member_interval(x: &JArray, y: &JArray) -> Result<JArray> {
 
 /// L. (monad) (_)
 pub fn v_levels(y: &JArray) -> Result<JArray> {
-    return Ok(JArray::from(match y {
-        // yes it would probably be easier to implement the whole thing
-        BoxArray(b) if b.iter().all(|c| !matches!(c, BoxArray(_))) => arr0d(1i64),
-        BoxArray(_) => return Err(JError::NonceError).context("levels > 0"),
-        _ => arr0d(0i64),
-    }));
+    Ok(JArray::from(arr0d(i64::try_from(levels_of(y))?)))
+}
+
+/// how deeply y is boxed: 0 for an open array, and an empty box array is 1
+pub fn levels_of(y: &JArray) -> usize {
+    match y {
+        BoxArray(b) => 1 + b.iter().map(levels_of).max().unwrap_or(0),
+        _ => 0,
+    }
 }
 
 /// i: (monad)
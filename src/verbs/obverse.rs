@@ -0,0 +1,165 @@
+//! Obverses, which `^:_1` and `&.` run to undo a verb
+//! https://code.jsoftware.com/wiki/Vocabulary/codot
+//!
+//! A primitive's obverse is its `inverse`; a derived verb's is built from the obverses of the
+//! verbs it was derived from, unless `:.` gave it one explicitly.
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::eval::{eval, MaybeVerb, VerbNoun};
+use crate::scan::scan;
+use crate::verbs::{
+    antibase_enough, v_grade_up, v_negate, BivalentOwned, PartialDef, PartialImpl, VerbImpl,
+};
+use crate::{primitive_conjunctions, primitive_verbs, rank, Ctx, JArray, JError, Num, Word};
+
+pub fn obverse(ctx: &mut Ctx, v: &VerbImpl) -> Result<VerbImpl> {
+    let none = || anyhow!("{} has no obverse", v.name());
+    match v {
+        VerbImpl::Primitive(imp) => match imp.inverse {
+            Some(inverse) => phrase(ctx, inverse),
+            None => Err(JError::DomainError).with_context(none),
+        },
+        // [: u v is u@:v
+        VerbImpl::Fork { f, g, h } if matches!(**f, Word::Verb(VerbImpl::Cap)) => {
+            let (g, h) = (verb(ctx, g)?, verb(ctx, h)?);
+            let (gi, hi) = (obverse(ctx, &g)?, obverse(ctx, &h)?);
+            derive(ctx, Word::Verb(hi), "@:", Word::Verb(gi))
+        }
+        VerbImpl::Partial(PartialImpl { def, .. }) => {
+            let found = match &**def {
+                PartialDef::Conjunction(_, c, VerbNoun::Verb(v)) if c.name() == ":." => {
+                    Some(v.to_verb(ctx.eval())?)
+                }
+                PartialDef::Conjunction(VerbNoun::Verb(u), c, v) => {
+                    let u = u.to_verb(ctx.eval())?;
+                    derived_obverse(ctx, &u, &c.name(), v)?
+                }
+                PartialDef::Conjunction(VerbNoun::Noun(m), c, VerbNoun::Verb(v))
+                    if c.name() == "&" =>
+                {
+                    let v = v.to_verb(ctx.eval())?;
+                    bonded_left(ctx, m, &v)?
+                }
+                PartialDef::Primitive("s:") => Some(phrase(ctx, "5&s:")?),
+                _ => None,
+            };
+            found.ok_or(JError::DomainError).with_context(none)
+        }
+        _ => Err(JError::DomainError).with_context(none),
+    }
+}
+
+fn verb(ctx: &Ctx, w: &Word) -> Result<VerbImpl> {
+    match w {
+        Word::Verb(v) => Ok(v.clone()),
+        Word::Name(n) => MaybeVerb::Name(n.clone()).to_verb(ctx.eval()),
+        _ => Err(JError::DomainError).with_context(|| anyhow!("{w:?} isn't a verb")),
+    }
+}
+
+/// `u c v`, a verb made by a primitive conjunction
+fn derive(ctx: &mut Ctx, u: Word, c: &'static str, v: Word) -> Result<VerbImpl> {
+    let c = primitive_conjunctions(c).expect("static conjunction");
+    match c.form_conjunction(ctx, &u, &v)?.1 {
+        Word::Verb(v) => Ok(v),
+        w => Err(JError::DomainError).with_context(|| anyhow!("{w:?} isn't a verb")),
+    }
+}
+
+/// a primitive's `inverse`, a primitive or a tacit phrase of them like `*/`
+fn phrase(ctx: &mut Ctx, inverse: &str) -> Result<VerbImpl> {
+    if let Some(v) = primitive_verbs(inverse) {
+        return Ok(v);
+    }
+    match eval(scan(inverse)?, ctx)? {
+        Word::Verb(v) => Ok(v),
+        w => Err(JError::DomainError).with_context(|| anyhow!("{w:?} isn't a verb")),
+    }
+}
+
+fn prim(name: &str) -> Word {
+    Word::Verb(primitive_verbs(name).expect("static verb"))
+}
+
+/// the obverse of `u c v`, for the conjunctions which have one
+fn derived_obverse(ctx: &mut Ctx, u: &VerbImpl, c: &str, v: &VerbNoun) -> Result<Option<VerbImpl>> {
+    Ok(Some(match (c, v) {
+        ("@" | "@:" | "&" | "&:", VerbNoun::Verb(v)) => {
+            let v = v.to_verb(ctx.eval())?;
+            let (ui, vi) = (obverse(ctx, u)?, obverse(ctx, &v)?);
+            let c = match c {
+                "@" => "@",
+                "&" => "&",
+                _ => "@:",
+            };
+            derive(ctx, Word::Verb(vi), c, Word::Verb(ui))?
+        }
+        ("&.", VerbNoun::Verb(v)) => {
+            let ui = obverse(ctx, u)?;
+            derive(ctx, Word::Verb(ui), "&.", v.clone().into())?
+        }
+        ("\"", VerbNoun::Noun(n)) => {
+            let ui = obverse(ctx, u)?;
+            derive(ctx, Word::Verb(ui), "\"", Word::Noun(n.clone()))?
+        }
+        ("^:", VerbNoun::Noun(n)) => {
+            derive(ctx, Word::Verb(u.clone()), "^:", Word::Noun(v_negate(n)?))?
+        }
+        ("&", VerbNoun::Noun(n)) => return bonded_right(ctx, u, n),
+        _ => return Ok(None),
+    }))
+}
+
+/// the obverse of `m&v`, which is `m v y`
+fn bonded_left(ctx: &mut Ctx, m: &JArray, v: &VerbImpl) -> Result<Option<VerbImpl>> {
+    let noun = Word::Noun(m.clone());
+    let (u, v) = match v.token() {
+        Some("+") => (prim("-"), noun),
+        Some("*") => (prim("%"), noun),
+        Some("%:") => (prim("^"), noun),
+        Some("-") => (noun, prim("-")),
+        Some("%") => (noun, prim("%")),
+        Some("^") => (noun, prim("^.")),
+        Some("^.") => (noun, prim("^")),
+        // an atomic base has as many digits as it takes, which `m&#:` doesn't know
+        Some("#.") if m.shape().is_empty() => return enough_digits(ctx, m, v).map(Some),
+        Some("#.") => (noun, prim("#:")),
+        Some("#:") => (noun, prim("#.")),
+        Some("|.") => (Word::Noun(v_negate(m)?), prim("|.")),
+        Some("|:") => (Word::Noun(v_grade_up(m)?), prim("|:")),
+        _ => return Ok(None),
+    };
+    derive(ctx, u, "&", v).map(Some)
+}
+
+/// `(m&#.)^:_1`, which makes as many digits in base m as each y needs
+fn enough_digits(ctx: &mut Ctx, m: &JArray, v: &VerbImpl) -> Result<VerbImpl> {
+    let base = m.approx_i64_one().context("#. needs an integer base")?;
+    let bonded = derive(ctx, Word::Noun(m.clone()), "&", Word::Verb(v.clone()))?;
+    Ok(VerbImpl::Partial(PartialImpl {
+        imp: BivalentOwned {
+            biv: BivalentOwned::from_monad(move |_, y| antibase_enough(base, y)),
+            ranks: rank!(_ _ _),
+        },
+        def: Box::new(PartialDef::Conjunction(
+            VerbNoun::Verb(MaybeVerb::Verb(bonded)),
+            primitive_conjunctions("^:").expect("static conjunction"),
+            VerbNoun::Noun(JArray::from(Num::from(-1i64))),
+        )),
+    }))
+}
+
+/// the obverse of `u&n`, which is `y u n`
+fn bonded_right(ctx: &mut Ctx, u: &VerbImpl, n: &JArray) -> Result<Option<VerbImpl>> {
+    let noun = Word::Noun(n.clone());
+    let (u, v) = match u.token() {
+        Some("+") => (prim("-"), noun),
+        Some("-") => (prim("+"), noun),
+        Some("*") => (prim("%"), noun),
+        Some("%") => (prim("*"), noun),
+        Some("^") => (noun, prim("%:")),
+        _ => return Ok(None),
+    };
+    derive(ctx, u, "&", v).map(Some)
+}
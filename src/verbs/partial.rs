@@ -4,6 +4,7 @@ use std::sync::Arc;
 use anyhow::{Context, Result};
 
 use crate::eval::VerbNoun;
+use crate::foreign;
 use crate::modifiers::ModifierImpl;
 use crate::verbs::stringify;
 use crate::{Ctx, JArray, JError, Word};
@@ -24,6 +25,8 @@ pub enum PartialDef {
     Adverb(ModifierImpl, VerbNoun),
     Conjunction(VerbNoun, ModifierImpl, VerbNoun),
     Cor(i64, Vec<Word>),
+    /// a primitive which needs the `Ctx`, like `s:`
+    Primitive(&'static str),
 }
 
 #[derive(Clone)]
@@ -38,8 +41,21 @@ impl PartialImpl {
             PartialDef::Adverb(a, u) => format!("({} {})", u.name(), a.name()),
             PartialDef::Conjunction(u, a, v) => format!("({} {} {})", u.name(), a.name(), v.name()),
             PartialDef::Cor(i, def) => format!("({i} : '{}')", stringify(def)),
+            PartialDef::Primitive(name) => name.to_string(),
         }
     }
+
+    /// whether this is a foreign which is given sparse arguments as they are
+    pub fn takes_sparse(&self) -> bool {
+        let PartialDef::Conjunction(VerbNoun::Noun(l), c, VerbNoun::Noun(r)) = &*self.def else {
+            return false;
+        };
+        c.name() == "!:"
+            && match (l.approx_i64_one(), r.approx_i64_one()) {
+                (Ok(l), Ok(r)) => foreign::takes_sparse(l, r),
+                _ => false,
+            }
+    }
 }
 
 impl fmt::Debug for PartialImpl {
@@ -69,6 +85,17 @@ impl BivalentOwned {
             f(ctx, y)
         })
     }
+
+    pub fn from_dyad(
+        f: impl Fn(&mut Ctx, &JArray, &JArray) -> Result<JArray> + 'static + Clone + Sync + Send,
+    ) -> BivalentOwnedF {
+        Arc::new(move |ctx, x, y| f(ctx, ensure_dyad(x)?, y))
+    }
+}
+
+fn ensure_dyad(x: Option<&JArray>) -> Result<&JArray> {
+    x.ok_or(JError::DomainError)
+        .context("monadic invocation of dyad-only verb")
 }
 
 fn ensure_monad(x: Option<&JArray>) -> Result<()> {
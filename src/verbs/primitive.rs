@@ -5,6 +5,10 @@ use anyhow::Result;
 use crate::JArray;
 
 use super::ranks::{DyadRank, Rank};
+use super::{
+    ceiling, equal, floor, index_of, larger_or_equal, larger_than, less, less_or_equal, less_than,
+    match_, member_in, not_equal, nub_, nub_sieve, self_classify,
+};
 
 #[derive(Copy, Clone)]
 pub struct Monad {
@@ -26,6 +30,41 @@ pub struct PrimitiveImpl {
     pub inverse: Option<&'static str>,
 }
 
+/// a valence which compares numbers, given the tolerance it compares them with
+pub type TolerantMonad = fn(&JArray, f64) -> Result<JArray>;
+pub type TolerantDyad = fn(&JArray, &JArray, f64) -> Result<JArray>;
+
+impl PrimitiveImpl {
+    /// the monad, if it's one which depends on the comparison tolerance
+    pub fn tolerant_monad(&self) -> Option<TolerantMonad> {
+        Some(match self.name {
+            "=" => self_classify,
+            "<." => floor,
+            ">." => ceiling,
+            "~." => nub_,
+            "~:" => nub_sieve,
+            _ => return None,
+        })
+    }
+
+    /// the dyad, if it's one which depends on the comparison tolerance
+    pub fn tolerant_dyad(&self) -> Option<TolerantDyad> {
+        Some(match self.name {
+            "=" => equal,
+            "~:" => not_equal,
+            "<" => less_than,
+            "<:" => less_or_equal,
+            ">" => larger_than,
+            ">:" => larger_or_equal,
+            "-:" => match_,
+            "-." => less,
+            "i." => index_of,
+            "e." => member_in,
+            _ => return None,
+        })
+    }
+}
+
 impl fmt::Debug for PrimitiveImpl {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "PrimitiveImpl({})", self.name)
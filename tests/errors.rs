@@ -1,6 +1,6 @@
 use anyhow::Result;
 use jr::test_impls::scan_eval;
-use jr::JError;
+use jr::{feed, Ctx, JError};
 
 #[test]
 fn test_not_impl() -> Result<()> {
@@ -10,3 +10,29 @@ fn test_not_impl() -> Result<()> {
     assert_eq!("feature not supported yet", format!("{}", err.root_cause()));
     Ok(())
 }
+
+fn report(lines: &str) -> String {
+    let mut ctx = Ctx::root();
+    for line in lines.lines() {
+        if let Err(e) = feed(line, &mut ctx) {
+            return JError::report(&e);
+        }
+    }
+    panic!("no error from {lines:?}");
+}
+
+#[test]
+fn test_report_sentence() {
+    assert_eq!("|length error\n|   1 2    + 1 2 3\n", report("1 2 + 1 2 3"));
+    assert_eq!("|domain error\n|       - 'a'\n", report("- 'a'"));
+    assert_eq!("|domain error\n|   (1+2)    + 'a'\n", report("(1+2) + 'a'"));
+    assert_eq!("|open quote\n|   'abc    \n", report("'abc"));
+}
+
+#[test]
+fn test_report_definition() {
+    assert_eq!(
+        "|domain error: f\n|   y    + 'a'\n|f[1]\n",
+        report("f =: {{\nz =. 1\ny + 'a'\n}}\ng =: {{ f y }}\ng 2")
+    );
+}
@@ -15,6 +15,22 @@ pub fn scan_eval_unwrap(sentence: impl AsRef<str>) -> Word {
 
 use scan_eval_unwrap as s;
 
+/// runs lines one after another in the same `Ctx`, like a session in the interpreter,
+/// giving the result of the last
+pub fn session() -> impl FnMut(&str) -> Result<Word> {
+    let mut ctx = Ctx::root();
+    move |lines| {
+        let mut output = None;
+        for line in lines.lines() {
+            output = Some(jr::feed(line, &mut ctx)?);
+        }
+        match output {
+            Some(jr::EvalOutput::Regular(w)) => Ok(w),
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+}
+
 pub fn idot(s: &[usize]) -> JArray {
     let p = s.iter().map(|i| *i as i64).product();
     //Noun(IntArray(ArrayD::from_shape_vec(IxDyn(&s), (0..p).collect()).unwrap(),))
@@ -380,9 +396,9 @@ fn test_drop_empty() -> Result<()> {
 
     assert_eq!(
         scan_eval("10 }. ''")?,
-        Word::Noun(JArray::CharArray(ArcArray::from_shape_vec(
+        Word::Noun(JArray::from(ArcArray::from_shape_vec(
             IxDyn(&[0]),
-            [].to_vec()
+            Vec::<char>::new()
         )?))
     );
 
@@ -462,9 +478,9 @@ fn test_box_equals() {
 }
 
 #[test]
-fn test_jarray_rank_iter() {
+fn test_jarray_rank_iter() -> Result<()> {
     let a = idot(&[2, 3]);
-    let v = a.rank_iter(0);
+    let v = a.rank_iter(0)?;
     println!("v.len(): {}", v.len());
     println!("{:?}", v);
     assert_eq!(
@@ -480,7 +496,7 @@ fn test_jarray_rank_iter() {
     );
 
     let a = idot(&[2, 2, 3]);
-    let v = a.rank_iter(1);
+    let v = a.rank_iter(1)?;
     println!("v.len(): {}", v.len());
     println!("{:?}", v);
     assert_eq!(
@@ -494,7 +510,7 @@ fn test_jarray_rank_iter() {
     );
 
     let a = idot(&[2, 2, 3]);
-    let v = a.rank_iter(2);
+    let v = a.rank_iter(2)?;
     println!("v.len(): {}", v.len());
     println!("{:?}", v);
     assert_eq!(
@@ -506,16 +522,17 @@ fn test_jarray_rank_iter() {
     );
 
     let a = idot(&[2, 2, 3]);
-    let v = a.rank_iter(3);
+    let v = a.rank_iter(3)?;
     println!("v.len(): {}", v.len());
     println!("{:?}", v);
     assert_eq!(v, vec![idot(&[2, 2, 3])]);
 
     let a = idot(&[2, 2, 3]);
-    let v = a.rank_iter(Rank::infinite().raw_u8().into());
+    let v = a.rank_iter(Rank::infinite().raw_u8().into())?;
     println!("v.len(): {}", v.len());
     println!("{:?}", v);
     assert_eq!(v, vec![idot(&[2, 2, 3])]);
+    Ok(())
 }
 
 #[test]
@@ -614,7 +631,7 @@ fn test_take() -> Result<()> {
 fn test_take_agreement() -> Result<()> {
     assert_eq!(
         scan_eval("(2 1 $ 2) {. 'abcdef'")?,
-        Noun(CharArray(
+        Noun(JArray::from(
             ArcArray::from_shape_vec(IxDyn(&[2, 2]), vec!['a', 'b', 'a', 'b']).unwrap(),
         ))
     );
@@ -709,3 +726,767 @@ fn test_user_defined_monadic_verb() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_loop_controls() -> Result<()> {
+    assert_eq!(
+        scan_eval("{{ r =. 5\nwhilst. 0 do. r =. r + 1 end.\nr }} 0").unwrap(),
+        Word::from(6i64)
+    );
+    assert_eq!(
+        scan_eval("{{ r =. 5\nwhile. 0 do. r =. r + 1 end.\nr }} 0").unwrap(),
+        Word::from(5i64)
+    );
+
+    let err = scan_eval("{{ break. }} 0").unwrap_err();
+    let root = dbg!(err.root_cause())
+        .downcast_ref::<JError>()
+        .expect("caused by jerror");
+    assert!(matches!(root, JError::ControlError));
+
+    Ok(())
+}
+
+#[test]
+fn test_throw() -> Result<()> {
+    let err = scan_eval("{{ try. throw. catch. 5 end. }} 0").unwrap_err();
+    let root = dbg!(err.root_cause())
+        .downcast_ref::<JError>()
+        .expect("caused by jerror");
+    assert!(matches!(root, JError::UncaughtThrow));
+
+    assert_eq!(
+        scan_eval("{{ try. 1 + 'a' catchd. 13!:12 '' end. }} 0").unwrap(),
+        Word::Noun(JArray::from_string("|domain error\n|   1    + 'a'\n"))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_exit() -> Result<()> {
+    let err = scan_eval("{{ try. 2!:55 ] 3 catch. 5 end. }} 0").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::Exit(3))));
+
+    let err = scan_eval("2!:55 ''").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::Exit(0))));
+    Ok(())
+}
+
+#[test]
+fn test_write_files() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("jr-files-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("a.txt").display().to_string();
+
+    scan_eval(&format!("'hello' 1!:2 <'{path}'"))?;
+    scan_eval(&format!("' world' 1!:3 <'{path}'"))?;
+    assert_eq!(
+        scan_eval(&format!("1!:1 <'{path}'"))?,
+        Word::Noun(JArray::from_string("hello world"))
+    );
+    assert_eq!(
+        scan_eval(&format!("0 2 {{\"1 ] 1!:0 '{}/*.txt'", dir.display()))?,
+        s("1 2 $ 'a.txt' ; 11")
+    );
+    scan_eval(&format!("1!:55 <'{path}'"))?;
+    let err = scan_eval(&format!("1!:1 <'{path}'")).unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::FileNameError)));
+
+    std::fs::remove_dir(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_write_bytes() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("jr-bytes-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("a.bin").display().to_string();
+
+    scan_eval(&format!("(3!:1 i.3) 1!:2 <'{path}'"))?;
+    let written = std::fs::read(&path)?;
+    assert_eq!(written.len(), 64);
+    let bytes = written
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(" ");
+    assert_eq!(s(&format!("{bytes} -: a. i. 3!:1 i.3")), s("1"));
+
+    scan_eval(&format!("(u: 256) 1!:2 <'{path}'"))?;
+    assert_eq!(std::fs::read(&path)?, "\u{100}".as_bytes());
+
+    std::fs::remove_file(&path)?;
+    std::fs::remove_dir(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_file_numbers() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("jr-numbers-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("b.txt").display().to_string();
+
+    let mut run = session();
+    run(&format!("'0123456789' 1!:2 <'{path}'"))?;
+    run(&format!("h =: 1!:21 <'{path}'"))?;
+    assert_eq!(run("1!:11 h;2 3")?, s("'234'"));
+    assert_eq!(run(&format!("1!:11 '{path}';_3"))?, s("'789'"));
+    assert_eq!(run(&format!("1!:11 '{path}';_3 2"))?, s("'78'"));
+    run("'ab' 1!:12 h;1")?;
+    assert_eq!(run("1!:1 h")?, s("'0ab3456789'"));
+    assert_eq!(run("1!:4 h")?, s("10"));
+    assert_eq!(run("# 1!:20 ''")?, s("1"));
+    run("1!:22 h")?;
+
+    let err = run("1!:22 h").unwrap_err();
+    assert!(matches!(
+        JError::extract(&err),
+        Some(JError::FileNumberError)
+    ));
+
+    std::fs::remove_file(&path)?;
+    std::fs::remove_dir(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_indexed_bytes() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("jr-indexed-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("c.bin").display().to_string();
+    let bytes = [0x68, 0xc3, 0xa9, 0x6c, 0x6c, 0x6f];
+    std::fs::write(&path, bytes)?;
+
+    let mut run = session();
+    run(&format!("h =: 1!:21 <'{path}'"))?;
+    assert_eq!(run("a. i. 1!:1 h")?, s("104 195 169 108 108 111"));
+    assert_eq!(
+        run(&format!("a. i. 1!:1 <'{path}'"))?,
+        s("104 195 169 108 108 111")
+    );
+    run("(1!:11 h;0 6) 1!:12 h;6")?;
+    run("1!:22 h")?;
+    assert_eq!(std::fs::read(&path)?, [bytes, bytes].concat());
+
+    std::fs::remove_file(&path)?;
+    std::fs::remove_dir(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_binary_rep() -> Result<()> {
+    for y in [
+        "1 0 1",
+        "'hello'",
+        "i. 2 3",
+        "1.5 _2.25",
+        "1j2 3j_4",
+        "(1;'two');<<3 4",
+        "123456789x * _987654321x",
+        "1r3 _22r7 5",
+        "0 $ <''",
+    ] {
+        for mode in ["", "0 ", "1 ", "2 ", "3 ", "11 "] {
+            assert_eq!(
+                s(format!("(3!:2) {mode}(3!:1) {y}")),
+                s(y),
+                "{mode}3!:1 {y}"
+            );
+        }
+        assert_eq!(s(format!("(3!:2) (3!:3) {y}")), s(y), "3!:3 {y}");
+    }
+
+    assert_eq!(s("$ 3!:3 ] 1 2"), s("7 16"));
+    assert_eq!(
+        s("0 1 5 { 3!:3 ] 1 2"),
+        s("3 16 $ 'e300000000000000' , '0400000000000000' , '0100000000000000'")
+    );
+    assert_eq!(s("# 1 (3!:1) 1 2"), s("28"));
+
+    let err = scan_eval("1 (3!:1) 1099511627776").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::LimitError)));
+    Ok(())
+}
+
+#[test]
+fn test_binary_rep_file() -> Result<()> {
+    let dir = std::env::temp_dir().join(format!("jr-binary-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("d.bin").display().to_string();
+
+    for y in ["i. 2 3", "1.5 _2.25", "(1;'two');<<3 4", "1r3 _22r7 5"] {
+        scan_eval(&format!("((3!:1) {y}) 1!:2 <'{path}'"))?;
+        assert_eq!(s(format!("(3!:2) 1!:1 <'{path}'")), s(y), "{y}");
+    }
+
+    std::fs::remove_file(&path)?;
+    std::fs::remove_dir(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_dot() -> Result<()> {
+    assert_eq!(s("1 2 3 +/ . * 4 5 6"), s("32"));
+    assert_eq!(s("(i. 2 3) +/ . * i. 3 2"), s("2 2 $ 10 13 28 40"));
+    assert_eq!(s("1.5 2 +/ . * 2 2 $ 1 2 3 4"), s("7.5 11"));
+    assert_eq!(s("(i. 2 3) +/ . + i. 3 2"), s("2 2 $ 9 12 18 21"));
+    assert_eq!(s("2 3 */ . + 4 5"), s("48"));
+    assert_eq!(s("(2 2 $ 1 2 3 4) +/ . * 2 1 $ 1 1"), s("2 1 $ 3 7"));
+
+    assert_eq!(s("-/ . * 2 2 $ 1 2 3 4"), s("_2"));
+    assert_eq!(s("-/ . * 3 3 $ 1 2 3 4 5 6 7 8 9"), s("0"));
+    assert_eq!(s("-/ . * 4 4 $ 1 2 3 4 5 6 7 8 2 6 4 8 3 1 1 2"), s("72"));
+    assert_eq!(s("*/ . + 2 2 $ 1 2 3 4"), s("25"));
+    assert_eq!(s("-/ . * 5"), s("5"));
+    assert_eq!(s("-/ . * 2 3 $ i. 6"), s("0"));
+    assert_eq!(s("-/ . * 0 3 $ 0"), s("0"));
+    assert_eq!(s("-/ . * 2 2 $ 0.5 1 2 3"), s("_0.5"));
+    let m = "8 8 $ 3 1 4 1 5 9 2 6 5 3 5 8 9 7 9";
+    assert_eq!(s(format!("-/ . * {m}")), s("_4605827"));
+    let m = "5 5 $ 3 1 4 1 5 9 2 6 5 3 5 8 9 7 9";
+    assert_eq!(s(format!("-/ . * {m}")), s(format!("-/@] . * {m}")));
+
+    assert_eq!(s("(2 2 $ 4611686018427387904) +/ . * 2 2"), s("2 $ 2 ^ 64"));
+    Ok(())
+}
+
+#[test]
+fn test_matrix_divide() -> Result<()> {
+    let close = |a: &str, b: &str| s(format!("*./ , 0.0000000001 > *: +. ({a}) - {b}"));
+    assert_eq!(close("%. 2 2 $ 1 2 3 4", "2 2 $ _2 1 1.5 _0.5"), s("1"));
+    assert_eq!(close("1 2 %. 2 2 $ 1 2 3 4", "0 0.5"), s("1"));
+    let m = "3 3 $ 2 0 1 1 3 2 1 1 2";
+    assert_eq!(close(&format!("(%. {m}) +/ . * {m}"), "=/~ i. 3"), s("1"));
+    assert_eq!(close("%. 1 2 3", "1 2 3 % 14"), s("1"));
+    assert_eq!(s("%. 4"), s("0.25"));
+    assert_eq!(s("3 %. 4"), s("0.75"));
+
+    // least squares: y %. x ,. 1 fits a line
+    assert_eq!(close("1 3 5 8 %. 1 2 3 4 ,. 1", "2.3 _1.5"), s("1"));
+    assert_eq!(s("$ (i. 3 2 2) %. 3 2 $ 1 2 3 4 5 7"), s("2 2 2"));
+    assert_eq!(s("$ %. 3 2 $ 1 2 3 4 5 7"), s("2 3"));
+
+    let m = "2 2 $ 1j1 2 3 4j_1";
+    assert_eq!(
+        close(&format!("(%. {m}) +/ . * {m}"), "2 2 $ 1 0 0 1"),
+        s("1")
+    );
+
+    assert_eq!(s("%. 2 2 $ 1 2 3 4x"), s("2 2 $ _2 1 3r2 _1r2"));
+    assert_eq!(s("1 3 5 8 %. 1 2 3 4x ,. 1"), s("23r10 _3r2"));
+
+    for bad in [
+        "%. 2 2 $ 1 2 2 4",
+        "%. 2 2 $ 1 2 2 4x",
+        "%. 2 3 $ 1",
+        "%. 'ab'",
+    ] {
+        let err = scan_eval(bad).unwrap_err();
+        assert!(
+            matches!(JError::extract(&err), Some(JError::DomainError)),
+            "{bad}"
+        );
+    }
+    let err = scan_eval("1 2 3 %. 2 2 $ 1").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::LengthError)));
+
+    assert_eq!(s("1 2 3 4 ,. 5"), s("4 2 $ 1 5 2 5 3 5 4 5"));
+    assert_eq!(s("5 ,. i. 2 2"), s("2 3 $ 5 0 1 5 2 3"));
+    Ok(())
+}
+
+#[test]
+fn test_primes() -> Result<()> {
+    assert_eq!(s("p: i. 10"), s("2 3 5 7 11 13 17 19 23 29"));
+    assert_eq!(s("p: 1000000"), s("15485867"));
+    assert_eq!(s("_1 p: 10 100 1000 10.5 _3"), s("4 25 168 4 0"));
+    assert_eq!(s("1 p: i. 12"), s("0 0 1 1 0 1 0 1 0 0 0 1"));
+    assert_eq!(s("0 p: 7 9 2.5"), s("0 1 1"));
+    assert_eq!(s("2 p: 700"), s("2 3 $ 2 5 7 2 2 1"));
+    assert_eq!(s("$ 2 p: 1"), s("2 0"));
+    assert_eq!(s("3 p: 700"), s("q: 700"));
+    assert_eq!(s("4 p: 1 2 10 _5 2.5"), s("2 3 11 2 3"));
+    assert_eq!(s("_4 p: 3 10 2.5 100"), s("2 7 2 97"));
+    assert_eq!(s("5 p: 1 0 12 97"), s("1 0 4 96"));
+
+    assert_eq!(s("q: 700 12"), s("2 5 $ 2 2 5 5 7 2 2 3 0 0"));
+    assert_eq!(s("$ q: 1"), s("$ i. 0"));
+    assert_eq!(s("q: 600851475143"), s("71 839 1471 6857"));
+    assert_eq!(s("3 q: 100"), s("2 0 2"));
+    assert_eq!(s("_ q: 700"), s("2 0 2 1"));
+    assert_eq!(s("__ q: 700"), s("2 p: 700"));
+
+    // extended integers stay extended, and are factored by rho
+    assert_eq!(s("p: 10x"), s("31x"));
+    assert_eq!(s("q: 998244359987710471x"), s("998244353 1000000007x"));
+    assert_eq!(
+        s("q: 4951760154835678088235319297x"),
+        s("2147483647 2305843009213693951x")
+    );
+    assert_eq!(s("1 p: 170141183460469231731687303715884105727x"), s("1"));
+    assert_eq!(s("4 p: 18446744073709551616x"), s("18446744073709551629x"));
+
+    for bad in ["q: 0", "q: _6", "_4 p: 2", "p: _1", "6 p: 10"] {
+        let err = scan_eval(bad).unwrap_err();
+        assert!(
+            matches!(JError::extract(&err), Some(JError::DomainError)),
+            "{bad}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_symbols() -> Result<()> {
+    assert_eq!(s("3!:0 s: ' a b'"), s("65536"));
+    assert_eq!(s("5 s: s: ' ab c'"), s(";: 'ab c'"));
+    assert_eq!(s("5 s: s: 'ab';'c'"), s(";: 'ab c'"));
+    assert_eq!(s("5 s: _2 s: 'ab.c.'"), s(";: 'ab c'"));
+    assert_eq!(s("5 s: s: 2 3 $ 'ab c  '"), s(";: 'ab c'"));
+    assert_eq!(s("1 s: s: ' ab c'"), s("'`ab`c'"));
+    assert_eq!(s("3 s: s: ' ab c'"), s("2 2 $ 'abc', 0 { a."));
+    assert_eq!(s("(s: ' b a b') = 0 { s: ' b'"), s("1 0 1"));
+    assert_eq!(s("5 s: /:~ s: ' b c a'"), s(";: 'a b c'"));
+    assert_eq!(s("(s: ' a b c') i. s: ' c'"), s(",2"));
+    assert_eq!(s("5 s: 3 {. s: ' a b'"), s("(;: 'a b'), <''"));
+
+    let mut run = session();
+    run("t =: s: ' x y z'")?;
+    assert_eq!(run("6 s: t")?, s("0 1 2"));
+    assert_eq!(run("(_6 s: 2 0) = 2 0 { t")?, s("1 1"));
+    assert_eq!(run("0 s: 0")?, s("3"));
+
+    let err = scan_eval("1 2 , s: ' a'").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    let err = scan_eval("_6 s: 7").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::IndexError)));
+    Ok(())
+}
+
+#[test]
+fn test_unicode() -> Result<()> {
+    assert_eq!(s("3!:0 'abc'"), s("2"));
+    assert_eq!(s("3!:0 u: 26085"), s("131072"));
+    assert_eq!(s("3!:0 (10 u: 128512)"), s("262144"));
+    assert_eq!(s("3 u: u: 97 26085 233"), s("97 26085 233"));
+    assert_eq!(s("3 u: 8 u: u: 233 26085"), s("195 169 230 151 165"));
+    assert_eq!(s("7 u: 8 u: 10 u: 97 128512"), s("10 u: 97 128512"));
+    assert_eq!(s("1 u: u: 26085"), s("u: 229"));
+    assert_eq!(s("6 u: 65 0 66 0 { a."), s("'AB'"));
+    assert_eq!(s("(-: 3!:2@(3!:1)) 2 2 $ 10 u: 97 26085 128512 98"), s("1"));
+    assert_eq!(s("3!:0 u: 'abc'"), s("131072"));
+    assert_eq!(s("3!:0 u: 97"), s("131072"));
+    assert_eq!(s("3!:0 }. 2 2 $ u: 'abc'"), s("131072"));
+    assert_eq!(s("3!:0 'abc' , 10 u: 97"), s("262144"));
+    assert_eq!(s("7 u: 'café'"), s("'café'"));
+    assert_eq!(s("7 u: 8 u: 'café'"), s("'café'"));
+    assert_eq!(s("3!:0 (9 u: 8 u: 'café')"), s("262144"));
+    assert_eq!(s("'abc' -: u: 'abc'"), s("1"));
+
+    let err = scan_eval("4 u: 128512").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    let err = scan_eval("7 u: 195 { a.").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+
+    // wide characters take two columns
+    let Word::Noun(boxed) = s("(u: 26085 26412);'ab'") else {
+        panic!("noun")
+    };
+    let mut out = String::new();
+    jr::display::jsoft(&mut out, &boxed)?;
+    assert_eq!(out, "┌────┬──┐\n│日本│ab│\n└────┴──┘\n");
+    Ok(())
+}
+
+#[test]
+fn test_sparse() -> Result<()> {
+    assert_eq!(s("3!:0 $. 0 0 1 0 1"), s("1024"));
+    assert_eq!(s("3!:0 $. 0 0 1 0 4"), s("4096"));
+    assert_eq!(s("3!:0 $. 'a b'"), s("2048"));
+    assert_eq!(s("0 $. $. 2 3 $ 0 1 0 0 0 5"), s("2 3 $ 0 1 0 0 0 5"));
+    assert_eq!(s("4 $. $. 2 3 $ 0 1 0 0 0 5"), s("2 2 $ 0 1 1 2"));
+    assert_eq!(s("5 $. $. 2 3 $ 0 1 0 0 0 5"), s("1 5"));
+    assert_eq!(s("3 $. (3;7) $. $. 0 1 0"), s("7"));
+    assert_eq!(s("7 $. 8 $. 0 * $. 0 1 2"), s("0"));
+
+    // none of these make a dense copy of the huge argument
+    assert_eq!(s("$ 1 $. 1000000 1000000"), s("1000000 1000000"));
+    assert_eq!(s("3 $. 1 + 1 $. 1000000 1000000"), s("1"));
+    assert_eq!(s("$ +/ 2 * 1 $. 1000000 1000000"), s(",1000000"));
+    assert_eq!(s("$ 1 0 |: 1 $. 1000000 3"), s("3 1000000"));
+    assert_eq!(s("$ , 1 $. 1000000 1000000"), s(",1000000000000"));
+    assert_eq!(s("$ 5 { 1 $. 1000000 1000000"), s(",1000000"));
+    assert_eq!(s("# 1 $. 1000000 1000000"), s("1000000"));
+    assert_eq!(s("3!:0 (1 $. 1000000 1000000)"), s("1024"));
+    let err = scan_eval("/: 1 $. 1000000 1000000").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::LimitError)));
+    let err = scan_eval("'a b' =: 1 $. 2 1000000000").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::LimitError)));
+    assert_eq!(s("(1 $. 1000000 1000000) -: 1 $. 1000000 1000000"), s("1"));
+    assert_eq!(s("(1 $. 1000000 1000000) -: 1 $. 1000000 1000001"), s("0"));
+    assert_eq!(
+        s("(1 $. 1000000 1000000) -: 1 + 1 $. 1000000 1000000"),
+        s("0")
+    );
+    assert_eq!(s("5 $. 7 (<1 1) } 1 $. 1000000 1000000"), s(",7"));
+    assert_eq!(s("4 $. 7 (<1 1) } 1 $. 1000000 1000000"), s("1 2 $ 1 1"));
+    assert_eq!(
+        s("4 $. 0 (<1 1) } 7 (<1 1) } 1 $. 1000000 1000000"),
+        s("0 2 $ 0")
+    );
+
+    assert_eq!(s("+/ $. 2 3 $ 0 1 0 0 0 5"), s("0 1 5"));
+    assert_eq!(s("7 $. +/ $. 2 3 $ 0 1 0 0 0 5"), s("2"));
+    assert_eq!(s("($. 0 1 0) + 1 2 3"), s("1 3 3"));
+    assert_eq!(s("_1 0 { $. 2 2 $ 1 0 0 3"), s("2 2 $ 0 3 1 0"));
+    assert_eq!(s("($. 0 1) , $. 0 2"), s("0 1 0 2"));
+    assert_eq!(s("|: $. 2 3 $ 0 1 0 0 0 5"), s("|: 2 3 $ 0 1 0 0 0 5"));
+    assert_eq!(s("0 |: 2 3 $ i. 6"), s("|: 2 3 $ i. 6"));
+    assert_eq!(s("(+/ % #) $. 1 2 0"), s("1"));
+    assert_eq!(s("($. 0 1 0) -: 0 1 0"), s("1"));
+    assert_eq!(s("($. 0 1 0) -: 0 1 0.5"), s("0"));
+    assert_eq!(s("((3;7) $. $. 7 1) -: $. 7 1"), s("1"));
+    assert_eq!(s("($. 0 1 0) -: 0 1"), s("0"));
+    assert_eq!(s("(<$. 0 1) -: <0 1"), s("1"));
+    assert_eq!(
+        s("0 $. (2 3 $ 10 + i. 6) 0 2 } $. i. 3 3"),
+        s("(2 3 $ 10 + i. 6) 0 2 } i. 3 3")
+    );
+    assert_eq!(
+        s("0 $. 9 8 (0 0;2 1) } $. i. 3 3"),
+        s("3 3 $ 9 1 2 3 4 5 6 8 8")
+    );
+    assert_eq!(s("0 $. 1.5 (<_1 _1) } $. i. 2 2"), s("2 2 $ 0 1 2 1.5"));
+    let err = scan_eval("7 (<1) } 1 $. 3 3").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::NonceError)));
+    let err = scan_eval("7 (<1 1) } i. 3 3").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::NonceError)));
+
+    let err = scan_eval("1 $. 2 3;0;0").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::NonceError)));
+
+    let Word::Noun(sparse) = s("$. 2 3 $ 0 1 0 0 0 15") else {
+        panic!("noun")
+    };
+    let mut out = String::new();
+    jr::display::jsoft(&mut out, &sparse)?;
+    assert_eq!(out, "0 1 │  1\n1 2 │ 15\n");
+    Ok(())
+}
+
+#[test]
+fn test_folds() -> Result<()> {
+    assert_eq!(s("] F.. + 1 2 3 4"), s("10"));
+    assert_eq!(s("] F:. + 1 2 3 4"), s("3 6 10"));
+    assert_eq!(s("0 ] F:. + 1 2 3 4"), s("1 3 6 10"));
+    assert_eq!(s("] F:: + 1 2 3 4"), s("7 9 10"));
+    assert_eq!(s("10 ] F.: , 1 2 3"), s("1 2 3 10"));
+    assert_eq!(s("] F.. - 1 2 3"), s("2"));
+    assert_eq!(s("i.@] F:. + 1 2 3"), s("2 6 $ 0 1 2 0 0 0 0 1 2 3 4 5"));
+
+    // Z: skips, omits or stops
+    assert_eq!(s("] F:. (+ [ _1 Z: 2 = [) 1 2 3 4"), s("4 8"));
+    assert_eq!(s("] F:. (+ [ 0 Z: 2 = [) 1 2 3 4"), s("6 10"));
+    assert_eq!(s("] F:. (+ [ _2 Z: 3 = [) 1 2 3 4"), s(",3"));
+    assert_eq!(s("] F: (>: [ 1 Z: 5 <: ]) 0"), s("1 2 3 4 5 6"));
+    assert_eq!(s("2 ] F. (* [ 1 Z: 100 < ]) 1"), s("256"));
+
+    let err = scan_eval("] F. (>: [ _3 Z: 100\"_) 0").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::FoldLimit)));
+    let err = scan_eval("1 Z: 1").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    let err = scan_eval("] F.. + ,5").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    Ok(())
+}
+
+#[test]
+fn test_levels() -> Result<()> {
+    assert_eq!(s("L. 1 2"), s("0"));
+    assert_eq!(s("L. a:"), s("1"));
+    assert_eq!(s("L. 1;<2;<<3"), s("3"));
+
+    assert_eq!(s("+: L: 0 (1;<2 3;<4)"), s("2;<4 6;<8"));
+    assert_eq!(s("# L: 1 (1;<2 3;<4)"), s("1;2"));
+    assert_eq!(s("# L: _1 (1;<2 3;<4 5)"), s("1;2"));
+    assert_eq!(s("10 + L: 0 (1;<2 3;<4)"), s("11;<12 13;<14"));
+    assert_eq!(s("(1;2) + L: 0 (10;20)"), s("11;22"));
+
+    assert_eq!(s("+: S: 0 (1;<2 3;<4)"), s("3 2 $ 2 0 4 6 8 0"));
+    assert_eq!(s("# S: 1 ('ab';<'cde';<'f')"), s("2 2"));
+    assert_eq!(s("< S: 0 (1;<2;<3)"), s("1;2;3"));
+
+    let err = scan_eval("(1;2) + L: 0 (1;2;3)").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::LengthError)));
+    Ok(())
+}
+
+#[test]
+fn test_memo() -> Result<()> {
+    let mut run = session();
+    run("fib =: 3 : 'if. y < 2 do. y else. (fib y - 1) + fib y - 2 end.' M.")?;
+    assert_eq!(run("fib 15")?, s("610"));
+
+    // the second call with the same argument doesn't run u
+    run("count =: 0")?;
+    run("twice =: (3 : 'count =: count + 1' ] +:) M.")?;
+    assert_eq!(run("twice 5")?, s("10"));
+    assert_eq!(run("twice 5")?, s("10"));
+    assert_eq!(run("count")?, s("1"));
+    assert_eq!(run("3 (+ [ 4 : 'count =: count + 1') M. 4")?, s("7"));
+    assert_eq!(run("count")?, s("2"));
+    Ok(())
+}
+
+#[test]
+fn test_self_reference() -> Result<()> {
+    assert_eq!(scan_eval("(1:`(] * $:@<:)@.*) 5")?, s("120"));
+    assert_eq!(scan_eval("(1:`(] * $:@<:)@.*)\"0 i. 5")?, s("1 1 2 6 24"));
+    assert_eq!(
+        scan_eval("3 (4 : 'if. y do. x + x $: y - 1 else. 0 end.') 4")?,
+        s("12")
+    );
+
+    let err = scan_eval("$: 5").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::StackError)));
+
+    let mut run = session();
+    run("9!:99 ] 10")?;
+    assert_eq!(run("9!:98 ''")?, s("10"));
+    run("sum =: 3 : 'if. y do. y + $: y - 1 else. 0 end.'")?;
+    assert_eq!(run("sum 3")?, s("6"));
+    let err = run("sum 100").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::StackError)));
+    let err = run("(1:`(] * $:@<:)@.*) 100").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::StackError)));
+    run("g =: >:@g")?;
+    let err = run("g 1").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::StackError)));
+    assert_eq!(run("sum 3")?, s("6"));
+    Ok(())
+}
+
+#[test]
+fn test_fix() -> Result<()> {
+    let mut run = session();
+    // sum isn't defined yet, so mean looks it up each time
+    run("mean =: sum % #")?;
+    run("sum =: +/")?;
+    run("fixed =: mean f.")?;
+    run("sum =: */")?;
+    assert_eq!(run("mean 1 2 3 4")?, s("6"));
+    assert_eq!(run("fixed 1 2 3 4")?, s("2.5"));
+    assert_eq!(run("(sum\"1 f.) i. 2 3")?, s("0 60"));
+
+    // recursion back to the fixed name becomes $:
+    run("count =: (>:@count@<:)^:(0 < ])")?;
+    run("fixed =: count f.")?;
+    run("count =: 0:")?;
+    assert_eq!(run("fixed 5")?, s("5"));
+
+    let err = run("undefined f.").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::ValueError)));
+    let err = run("5 f.").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    Ok(())
+}
+
+#[test]
+fn test_gerunds() -> Result<()> {
+    assert_eq!(scan_eval("(+:`-:`:0) 4")?, s("8 2"));
+    assert_eq!(scan_eval("(i.`i.`:0) 2")?, s("2 2 $ 0 1"));
+    assert_eq!(scan_eval("(+`*`:3) 1 2 3 4")?, s("15"));
+    assert_eq!(scan_eval("((<,'+')`:3) 1 2 3 4")?, s("10"));
+    assert_eq!(scan_eval("((+/`%`#)`:6) 1 2 3 4")?, s("2.5"));
+    assert_eq!(scan_eval("((+`%)`:6) 4")?, s("4.25"));
+    assert_eq!(scan_eval("(((<(,'0');1 2),(<,'+'),<,']')`:6) 3")?, s("4 5"));
+    let err = scan_eval("(+`-)`:2").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+
+    // the ars of verbs are decoded back into working verbs
+    for verb in [
+        "+/ % #",
+        "-@+",
+        "+:^:2",
+        "+\"1",
+        "[: - +",
+        "(+&2) 1:",
+        "_2:",
+        "3 : 'y * 2'",
+    ] {
+        let direct = scan_eval(&format!("5 ({verb}) 3 4"))
+            .or_else(|_| scan_eval(&format!("({verb}) 3 4")))?;
+        let decoded = scan_eval(&format!("5 (({verb})`]@.0) 3 4"))
+            .or_else(|_| scan_eval(&format!("(({verb})`]@.0) 3 4")))?;
+        assert_eq!(direct, decoded, "{verb}");
+    }
+    assert_eq!(scan_eval("(-`+:)@.* _2 0 3")?, s("_4 0 6"));
+    Ok(())
+}
+
+#[test]
+fn test_representations() -> Result<()> {
+    let mut run = session();
+    run("mean =: +/ % #")?;
+    assert_eq!(run("5!:5 <'mean'")?, s("'+/ % #'"));
+    assert_eq!(run("5!:6 <'mean'")?, s("'(+/) % #'"));
+    assert_eq!(run("5!:2 <'mean'")?, s("(<(<,'+'),<,'/'),(<,'%'),<,'#'"));
+    assert_eq!(
+        run("5!:4 <'mean'")?,
+        s("3 12 $ '  ┌─ / ─── +──┼─ %        └─ #      '")
+    );
+    assert_eq!(run("((5!:1 <'mean') 5!:0) 1 2 3 4")?, s("2.5"));
+
+    // the linear representation is J, which makes the same verb again
+    for definition in [
+        "(+ - *) % #",
+        "+/@:*\"1",
+        "1 2&+ , 3 : 'y + 1'",
+        "1 2 (3&+) ]",
+        "[: +/ _3: , ]",
+        "(+ %)/",
+        "]`-@.(0&>)",
+        "3 : 0\na =. y + 1\na * 2\n)",
+    ] {
+        run(&format!("verb =: {definition}"))?;
+        let Word::Noun(linear) = run("5!:5 <'verb'")? else {
+            panic!("a noun")
+        };
+        let linear = linear.when_string().expect("a string");
+        run(&format!("again =: {linear}"))?;
+        assert_eq!(
+            run("verb _3")?,
+            run("again _3")?,
+            "{definition} as {linear}"
+        );
+    }
+
+    run("twice =: 3 : 0\na =. y + 1\na * 2\n)")?;
+    assert_eq!(
+        run("5!:5 <'twice'")?,
+        Noun(JArray::from_string("3 : 0\na =. y + 1\na * 2\n)"))
+    );
+
+    run("noun =: (<1 2),<2 2 $ 'ab''d'")?;
+    let Word::Noun(linear) = run("5!:5 <'noun'")? else {
+        panic!("a noun")
+    };
+    let linear = linear.when_string().expect("a string");
+    assert_eq!(run(&linear)?, run("noun")?);
+
+    let err = run("5!:5 <'undefined'").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::ValueError)));
+    Ok(())
+}
+
+#[test]
+fn test_obverse() -> Result<()> {
+    assert_eq!(scan_eval("-&3 (10)")?, s("7"));
+    assert_eq!(scan_eval("(3&+)^:_1 (10)")?, s("7"));
+    assert_eq!(scan_eval("(3&-)^:_1 (10)")?, s("_7"));
+    assert_eq!(scan_eval("2 +^:_1 ] 10")?, s("8"));
+    assert_eq!(scan_eval("(+:@>:)^:_1 ] 10")?, s("4"));
+    assert_eq!(scan_eval("([: -: >:)^:_1 ] 3")?, s("5"));
+    assert_eq!(scan_eval("(>:^:3)^:_1 ] 10")?, s("7"));
+    assert_eq!(scan_eval("(+: :. %)^:_1 ] 4")?, s("0.25"));
+    assert_eq!(scan_eval("(+&3 :. (-&3))^:_1 ] 10")?, s("7"));
+    assert_eq!(scan_eval("(*&2 @: +&1)&.(-&1) 10")?, s("21"));
+    assert_eq!(scan_eval("1&|.^:_1 i.5")?, s("4 0 1 2 3"));
+    assert_eq!(
+        scan_eval("(2 0 1&|:)^:_1 (2 0 1&|: i.2 3 4)")?,
+        s("i.2 3 4")
+    );
+    assert_eq!(scan_eval("|.&.> 1 2 3;4 5")?, s("3 2 1;5 4"));
+    assert_eq!(scan_eval("#.&.|. 1 1 0")?, s("3"));
+
+    assert_eq!(scan_eval("#. 1 0 1")?, s("5"));
+    assert_eq!(scan_eval("#: 0 1 5")?, s("3 3$0 0 0 0 0 1 1 0 1"));
+    assert_eq!(scan_eval("#.^:_1 ] 5")?, s("1 0 1"));
+    assert_eq!(scan_eval("24 60 60 #. 1 2 3")?, s("3723"));
+    assert_eq!(scan_eval("(24 60 60&#.)^:_1 ] 3723")?, s("1 2 3"));
+    assert_eq!(scan_eval("(10&#.)^:_1 ] 123")?, s("1 2 3"));
+    assert_eq!(scan_eval("(10&#.) (10&#.)^:_1 ] 123")?, s("123"));
+    assert_eq!(scan_eval("(10&#.)^:_1 ] 5 123")?, s("2 3 $ 0 0 5 1 2 3"));
+    assert_eq!(scan_eval("(^&2)^:_1 ] 9")?, s("3"));
+    assert_eq!(scan_eval("3 %: _27")?, s("_3"));
+    assert_eq!(scan_eval("0 10 #: 123")?, s("12 3"));
+
+    assert_eq!(scan_eval("q:^:_1 ] 2 2 3")?, s("12"));
+    assert_eq!(scan_eval("p:^:_1 ] 11")?, s("4"));
+    assert_eq!(scan_eval(">:&.(p:^:_1) 7")?, s("11"));
+    assert_eq!(scan_eval("j.^:_1 ] 0j2")?, s("2"));
+    assert_eq!(scan_eval("o.^:_1 o. 2")?, s("2"));
+    assert_eq!(scan_eval("+.^:_1 ] 3 4")?, s("3j4"));
+    assert_eq!(scan_eval("u:^:_1 u: 97 98")?, s("97 98"));
+    assert_eq!(scan_eval("$.^:_1 $. 0 1 0")?, s("0 1 0"));
+    assert_eq!(scan_eval("\":^:_1 '12'")?, s("12"));
+    assert_eq!(scan_eval("\".^:_1 ] 12")?, s("'12'"));
+    assert_eq!(scan_eval("s:^:_1 s: ' ab'")?, s(",<'ab'"));
+
+    let err = scan_eval("{.^:_1 ] 1 2").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    Ok(())
+}
+
+#[test]
+fn test_fit() -> Result<()> {
+    assert_eq!(scan_eval("1 =!.0 ] 1 + 2^_50")?, s("0"));
+    assert_eq!(scan_eval("1 =!.(2^_40) ] 1 + 2^_50")?, s("1"));
+    assert_eq!(scan_eval("1 ~:!.(2^_40) ] 1 + 2^_50")?, s("0"));
+    assert_eq!(scan_eval("1 <!.(2^_40) ] 1 + 2^_50")?, s("0"));
+    assert_eq!(scan_eval("1 <:!.(2^_40) ] 1 - 2^_50")?, s("1"));
+    assert_eq!(scan_eval("=!.0 ] 1 2 1")?, s("2 3$1 0 1 0 1 0"));
+    let err = scan_eval("1 =!.1 ] 2").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+
+    assert_eq!(scan_eval("5 {.!.9 ] 1 2")?, s("1 2 9 9 9"));
+    assert_eq!(scan_eval("_5 {.!.9 ] 1 2")?, s("9 9 9 1 2"));
+    assert_eq!(scan_eval("4 {.!.'x' 'ab'")?, s("'abxx'"));
+    assert_eq!(scan_eval("3 {.!.7 ] 2 2$1")?, s("3 2$1 1 1 1 7 7"));
+    assert_eq!(scan_eval("1 |.!.0 ] 1 2 3")?, s("2 3 0"));
+    assert_eq!(scan_eval("_1 |.!.0 ] 1 2 3")?, s("0 1 2"));
+    assert_eq!(scan_eval("_5 |.!.0 ] 1 2 3")?, s("0 0 0"));
+    assert_eq!(scan_eval("2 3 $!.9 ] 1 2")?, s("2 3$1 2 9 9 9 9"));
+    assert_eq!(scan_eval("3 $!.'-' 'a'")?, s("'a--'"));
+    let err = scan_eval("4 {.!.'x' 1 2").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+
+    assert_eq!(scan_eval("\":!.3 ] 3.14159 2")?, s("'3.14 2'"));
+    assert_eq!(scan_eval("3 ^!.1 ] 4")?, s("360"));
+    assert_eq!(scan_eval("2 ^!._1 ] 3")?, s("0"));
+    assert_eq!(scan_eval("3 ^!.0 ] 2")?, s("9"));
+    Ok(())
+}
+
+#[test]
+fn test_tolerance() -> Result<()> {
+    assert_eq!(scan_eval("(0.1 + 0.2) = 0.3")?, s("1"));
+    assert_eq!(scan_eval("(0.1 + 0.2) =!.0 ] 0.3")?, s("0"));
+    assert_eq!(scan_eval("(0.1 + 0.2) ~: 0.3")?, s("0"));
+    assert_eq!(scan_eval("(0.1 + 0.2) > 0.3")?, s("0"));
+    assert_eq!(scan_eval("1 = 1 + 2^_50")?, s("1"));
+    assert_eq!(scan_eval("<. 3 - 2^_50")?, s("3"));
+    assert_eq!(scan_eval(">. 3 + 2^_50")?, s("3"));
+    assert_eq!(scan_eval("<.!.0 ] 3 - 2^_50")?, s("2"));
+    assert_eq!(scan_eval("~. 1 2, 1 + 2^_50")?, s("1 2"));
+    assert_eq!(scan_eval("~: 1 2, 1 + 2^_50")?, s("1 1 0"));
+    assert_eq!(scan_eval("= 1 2, 1 + 2^_50")?, s("2 3$1 0 1 0 1 0"));
+    assert_eq!(scan_eval("1 2 3 i. 2 + 2^_50")?, s("1"));
+    assert_eq!(scan_eval("1 2 3 i.!.0 ] 2 + 2^_50")?, s("3"));
+    assert_eq!(scan_eval("(2 + 2^_50) e. 1 2 3")?, s(",1"));
+    assert_eq!(scan_eval("1 2 3 -. 2 + 2^_50")?, s("1 3"));
+    assert_eq!(scan_eval("(1 ; 0.1 + 0.2) -: 1 ; 0.3")?, s("1"));
+    assert_eq!(scan_eval("0.3 = $. 0 , 0.1 + 0.2")?, s("0 1"));
+
+    let mut run = session();
+    assert_eq!(run("(9!:18 '') = 2^_44")?, s("1"));
+    run("9!:19 ] 0")?;
+    assert_eq!(run("9!:18 ''")?, s("0"));
+    assert_eq!(run("(0.1 + 0.2) = 0.3")?, s("0"));
+    assert_eq!(run("# ~. 1 2, 1 + 2^_50")?, s("3"));
+    assert_eq!(run("(0.1 + 0.2) =!.(2^_44) ] 0.3")?, s("1"));
+    assert_eq!(run("0.3 = $. 0 , 0.1 + 0.2")?, s("0 0"));
+    let err = run("9!:19 ] 2^_30").unwrap_err();
+    assert!(matches!(JError::extract(&err), Some(JError::DomainError)));
+    Ok(())
+}